@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE game_moves;
+DROP TABLE games
\ No newline at end of file
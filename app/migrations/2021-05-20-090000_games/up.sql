@@ -0,0 +1,20 @@
+-- Your SQL goes here
+CREATE TABLE games (
+  id VARCHAR NOT NULL PRIMARY KEY,
+  white_player VARCHAR NOT NULL,
+  black_player VARCHAR NOT NULL,
+  variant VARCHAR NOT NULL,
+  started_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  finished_at TIMESTAMP,
+  result VARCHAR
+);
+
+CREATE TABLE game_moves (
+  id SERIAL PRIMARY KEY,
+  game_id VARCHAR NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+  ply INTEGER NOT NULL,
+  from_square VARCHAR NOT NULL,
+  to_square VARCHAR NOT NULL,
+  captured_squares VARCHAR[] NOT NULL DEFAULT '{}',
+  promotion BOOLEAN NOT NULL DEFAULT 'f'
+)
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE games ADD COLUMN initial_fen VARCHAR
\ No newline at end of file
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE games ADD COLUMN is_rated BOOLEAN NOT NULL DEFAULT 'f'
\ No newline at end of file
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE puzzle_attempts;
+DROP TABLE puzzle_ratings;
+DROP TABLE puzzles
\ No newline at end of file
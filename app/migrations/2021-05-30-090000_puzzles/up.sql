@@ -0,0 +1,27 @@
+-- Your SQL goes here
+CREATE TABLE puzzles (
+  id SERIAL PRIMARY KEY,
+  variant VARCHAR NOT NULL,
+  fen VARCHAR NOT NULL,
+  solution VARCHAR[] NOT NULL,
+  rating INTEGER NOT NULL DEFAULT 1500
+);
+
+CREATE TABLE puzzle_ratings (
+  user_name VARCHAR NOT NULL PRIMARY KEY,
+  rating INTEGER NOT NULL DEFAULT 1500
+);
+
+CREATE TABLE puzzle_attempts (
+  id SERIAL PRIMARY KEY,
+  puzzle_id INTEGER NOT NULL REFERENCES puzzles(id) ON DELETE CASCADE,
+  user_name VARCHAR NOT NULL,
+  is_solved BOOLEAN NOT NULL,
+  attempted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO puzzles (variant, fen, solution, rating) VALUES
+  ('Russian', 'W:W5,19,23,24:B7,8,12,22,25,28', '{"23-18","22x15","19x10x3"}', 1300),
+  ('Russian', 'W:W5,13,17,25,28:B10,16,19,20,24,26', '{"17-14","10x17","13x22x31"}', 1400),
+  ('Russian', 'W:W8,9,14,17,21:B5,7,10,18,19,23,28', '{"17-13","10x17","13x22x15x24"}', 1450),
+  ('Russian', 'W:W5,9,13,14,18,21:B12,16,19,26,27,28', '{"18-15","19x10x17","13x22x31x24"}', 1500)
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE TABLE player_ratings (
+  user_name VARCHAR NOT NULL PRIMARY KEY,
+  rating DOUBLE PRECISION NOT NULL DEFAULT 1500,
+  deviation DOUBLE PRECISION NOT NULL DEFAULT 350,
+  volatility DOUBLE PRECISION NOT NULL DEFAULT 0.06,
+  games_played INTEGER NOT NULL DEFAULT 0
+)
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE tournament_games;
+DROP TABLE tournament_players;
+DROP TABLE tournaments
\ No newline at end of file
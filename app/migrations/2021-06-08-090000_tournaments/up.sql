@@ -0,0 +1,30 @@
+-- Your SQL goes here
+CREATE TABLE tournaments (
+  id SERIAL PRIMARY KEY,
+  name VARCHAR NOT NULL,
+  system VARCHAR NOT NULL,
+  variant VARCHAR NOT NULL,
+  time_control VARCHAR,
+  rounds INTEGER NOT NULL DEFAULT 0,
+  current_round INTEGER NOT NULL DEFAULT 0,
+  status VARCHAR NOT NULL DEFAULT 'registration',
+  created_by VARCHAR NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE tournament_players (
+  id SERIAL PRIMARY KEY,
+  tournament_id INTEGER NOT NULL REFERENCES tournaments(id) ON DELETE CASCADE,
+  user_name VARCHAR NOT NULL,
+  UNIQUE (tournament_id, user_name)
+);
+
+CREATE TABLE tournament_games (
+  id SERIAL PRIMARY KEY,
+  tournament_id INTEGER NOT NULL REFERENCES tournaments(id) ON DELETE CASCADE,
+  round INTEGER NOT NULL,
+  white_player VARCHAR NOT NULL,
+  black_player VARCHAR,
+  game_id VARCHAR REFERENCES games(id) ON DELETE SET NULL,
+  result VARCHAR
+)
\ No newline at end of file
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+ALTER TABLE games ADD COLUMN is_played_on_server BOOLEAN NOT NULL DEFAULT 'f';
+
+-- games of the server are played by its users and computer players, imported games name the players of the PDN file
+UPDATE games SET is_played_on_server = 't'
+  WHERE (white_player IN (SELECT user_name FROM users_data) OR white_player LIKE 'computer\_%')
+    AND (black_player IN (SELECT user_name FROM users_data) OR black_player LIKE 'computer\_%')
+    AND white_player <> black_player
\ No newline at end of file
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE game_moves DROP COLUMN path
\ No newline at end of file
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- moves saved before are known by their first and last cells only, their path stays empty
+ALTER TABLE game_moves ADD COLUMN path VARCHAR[] NOT NULL DEFAULT '{}'
\ No newline at end of file
@@ -4,13 +4,21 @@ use actix::*;
 use actix_web::{web, Error, HttpRequest, HttpResponse};
 use actix_web_actors::ws;
 
-use crate::checkers_game::chat::chat_models::{WsRequest, WsResponse, Info};
+use crate::checkers_game::chat::chat_models::
+    {
+        WsRequest, WsResponse, Info, InvitationData, JoinedGame, OnlineUser, GameSearch, SessionToken,
+    };
 use serde_json;
 
 use crate::checkers_game::chat::server;
+use crate::checkers_game::chat::computer_player::ComputerPlayer;
 
-use crate::DbPool;
+use crate::{DbPool, decode_token};
 use crate::checkers_game::chat::chat_database;
+use crate::checkers_game::rating::rating_database;
+use crate::checkers_game::rating::rating_models::PlayerRating;
+
+use checkers_rules::Game;
 
 
 /// How often heartbeat pings are sent
@@ -19,7 +27,19 @@ const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
 
 
-/// Entry point for our route
+/// Token of the user from the `authorization` header or from the query of the websocket request.
+fn session_token(req: &HttpRequest) -> Option<String>
+{
+    if let Some(token) = req.headers().get("authorization").and_then(|token| token.to_str().ok())
+    {
+        return Some(token.to_owned());
+    }
+    web::Query::<SessionToken>::from_query(req.query_string()).ok().and_then(|query| query.into_inner().token)
+}
+
+
+/// Entry point for our route.
+/// The name of the session is taken from the token, sessions without a token belong to guests.
 pub async fn chat_route(
         req: HttpRequest, stream: web::Payload,
         srv: web::Data<Addr<server::ChatServer>>,
@@ -27,13 +47,23 @@ pub async fn chat_route(
     )
     -> Result<HttpResponse, Error>
 {
+    let name = match session_token(&req)
+    {
+        Some(token) => match decode_token(&token).await
+            {
+                Ok(decoded_user) if !ComputerPlayer::is_reserved_name(&decoded_user.claims.user_name) =>
+                    Some(decoded_user.claims.user_name),
+                _ => return Ok(HttpResponse::Unauthorized().body("Session has expired, please login again.")),
+            },
+        None => None,
+    };
     ws::start(
         WsChatSession
         {
             id: 0,
             hb: Instant::now(),
             room: "Main".to_owned(),
-            name: None,
+            name,
             addr: srv.get_ref().clone(),
             pool
         },
@@ -67,6 +97,18 @@ pub async fn extract_chat_log(
 }
 
 
+/// Game rooms are entered only by the server, the request to join one is rejected.
+fn rejected_join_response() -> String
+{
+    let response = WsResponse
+        {
+            action: "join_to_room".to_owned(),
+            data: "rejected".to_owned()
+        };
+    serde_json::to_string(&response).unwrap()
+}
+
+
 struct WsChatSession
 {
     /// unique session id
@@ -76,7 +118,7 @@ struct WsChatSession
     hb: Instant,
     /// joined room
     room: String,
-    /// peer name
+    /// Name of the signed in user, it is set from the token only
     name: Option<String>,
     /// Chat server
     addr: Addr<server::ChatServer>,
@@ -85,6 +127,47 @@ struct WsChatSession
 }
 
 
+impl WsChatSession
+{
+    /// The server learns the name of the signed in user.
+    fn identify_user(&self)
+    {
+        if let Some(user_name) = &self.name
+        {
+            self.addr.do_send(server::SetUserName { id: self.id, user_name: user_name.to_owned() });
+        }
+    }
+
+
+    /// The unfinished game of the signed in user is continued in this session.
+    fn reconnect_game(&self, ctx: &mut ws::WebsocketContext<Self>)
+    {
+        if self.name.is_none()
+        {
+            return;
+        }
+        self.addr
+            .send(server::Reconnect { id: self.id })
+            .into_actor(self)
+            .then(|res, act, ctx|
+                {
+                    if let Ok(Some(joined_game)) = res
+                    {
+                        act.room = joined_game.game.room.to_owned();
+                        let response = WsResponse
+                            {
+                                action: "reconnect_game".to_owned(),
+                                data: serde_json::to_string(&joined_game).unwrap()
+                            };
+                        ctx.text(serde_json::to_string(&response).unwrap());
+                    }
+                    fut::ready(())
+                })
+            .wait(ctx);
+    }
+}
+
+
 impl Actor for WsChatSession
 {
     type Context = ws::WebsocketContext<Self>;
@@ -113,7 +196,11 @@ impl Actor for WsChatSession
                 {
                     match res
                     {
-                        Ok(res) => act.id = res,
+                        Ok(res) =>
+                            {
+                                act.id = res;
+                                act.identify_user();
+                            },
                         // something is wrong with chat server
                         _ => ctx.stop(),
                     }
@@ -139,6 +226,17 @@ impl Handler<server::Message> for WsChatSession
 
     fn handle(&mut self, msg: server::Message, ctx: &mut Self::Context)
     {
+        // the server puts the session into the room of the started game
+        if let Ok(response) = serde_json::from_str::<WsResponse>(&msg.0)
+        {
+            if response.action == "game_started"
+            {
+                if let Ok(joined_game) = serde_json::from_str::<JoinedGame>(&response.data)
+                {
+                    self.room = joined_game.game.room;
+                }
+            }
+        }
         ctx.text(msg.0);
     }
 }
@@ -183,6 +281,11 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsChatSession
                         {
                             "join_to_room" =>
                                 {
+                                    if data.starts_with(server::GAME_ROOM_PREFIX)
+                                    {
+                                        ctx.text(rejected_join_response());
+                                        return;
+                                    }
                                     self.room = data.to_owned();
                                     self.addr.do_send(server::Join
                                     {
@@ -196,15 +299,7 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsChatSession
                                             data: "joined".to_owned()
                                         };
                                     ctx.text(serde_json::to_string(&response).unwrap());
-                                },
-                            "set_name" =>
-                                {
-                                    self.name = Some(data.to_owned());
-                                    self.addr.do_send(server::SetUserName
-                                    {
-                                        id: self.id,
-                                        user_name: data.to_owned(),
-                                    });
+                                    self.reconnect_game(ctx);
                                 },
                             "send_message" =>
                                 {
@@ -241,18 +336,26 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsChatSession
                                             room: self.room.clone()
                                         })
                                         .into_actor(self)
-                                        .then(|res, _, ctx|
+                                        .then(|res, act, ctx|
                                             {
                                                 match res
                                                 {
                                                     Ok(user_names) =>
                                                         {
-                                                            for user_name in user_names
+                                                            let conn = act.pool.get().expect("couldn't get db connection from pool");
+                                                            let player_ratings = rating_database::extract_player_ratings(&user_names, &conn)
+                                                                .unwrap_or_else(|_| user_names.iter().map(|name| PlayerRating::new(name)).collect());
+                                                            for player_rating in player_ratings
                                                             {
+                                                                let online_user = OnlineUser
+                                                                    {
+                                                                        rating: player_rating.rounded_rating(),
+                                                                        user_name: player_rating.user_name,
+                                                                    };
                                                                 let response = WsResponse
                                                                     {
                                                                         action: "response_online_users".to_owned(),
-                                                                        data: user_name
+                                                                        data: serde_json::to_string(&online_user).unwrap()
                                                                     };
                                                                 ctx.text(serde_json::to_string(&response).unwrap());
                                                             }
@@ -263,15 +366,122 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsChatSession
                                             })
                                         .wait(ctx)
                                 },
+                            "request_live_games" =>
+                                {
+                                    self.addr
+                                        .send(server::ListLiveGames)
+                                        .into_actor(self)
+                                        .then(|res, _, ctx|
+                                            {
+                                                match res
+                                                {
+                                                    Ok(live_games) =>
+                                                        {
+                                                            let response = WsResponse
+                                                                {
+                                                                    action: "response_live_games".to_owned(),
+                                                                    data: serde_json::to_string(&live_games).unwrap()
+                                                                };
+                                                            ctx.text(serde_json::to_string(&response).unwrap());
+                                                        }
+                                                    _ => println!("Something is wrong"),
+                                                }
+                                                fut::ready(())
+                                            })
+                                        .wait(ctx)
+                                },
+                            "watch_game" =>
+                                {
+                                    let room = data.to_owned();
+                                    self.addr
+                                        .send(server::WatchGame
+                                        {
+                                            id: self.id,
+                                            room: room.clone(),
+                                        })
+                                        .into_actor(self)
+                                        .then(move |res, act, ctx|
+                                            {
+                                                let response = match res
+                                                {
+                                                    Ok(Some(live_game)) =>
+                                                        {
+                                                            act.room = room;
+                                                            WsResponse
+                                                            {
+                                                                action: "watch_game".to_owned(),
+                                                                data: serde_json::to_string(&live_game).unwrap()
+                                                            }
+                                                        },
+                                                    _ =>
+                                                        {
+                                                            WsResponse
+                                                            {
+                                                                action: "rejected_watch_game".to_owned(),
+                                                                data: "The game is not available.".to_owned()
+                                                            }
+                                                        },
+                                                };
+                                                ctx.text(serde_json::to_string(&response).unwrap());
+                                                fut::ready(())
+                                            })
+                                        .wait(ctx)
+                                },
+                            "find_game" =>
+                                {
+                                    match serde_json::from_str::<GameSearch>(data)
+                                    {
+                                        Ok(game_search) =>
+                                            {
+                                                self.addr.do_send(server::FindGame
+                                                {
+                                                    id: self.id,
+                                                    variant: game_search.variant,
+                                                    time_control: game_search.time_control,
+                                                    is_rated: game_search.is_rated,
+                                                });
+                                            },
+                                        Err(_) => println!("Incorrect game search data: {}", data),
+                                    }
+                                },
+                            "cancel_find_game" =>
+                                {
+                                    self.addr.do_send(server::CancelFindGame { id: self.id });
+                                },
                             "invitation" =>
                                 {
-                                    self.addr.do_send(server::Invitation
+                                    match serde_json::from_str::<InvitationData>(data)
                                     {
-                                        id: self.id,
-                                        to_user: data.to_owned(),
-                                        room: self.room.clone(),
-                                        action: action.to_owned(),
-                                    });
+                                        Ok(invitation_data) =>
+                                            {
+                                                if let Some(Err(e)) = invitation_data.initial_fen.as_ref()
+                                                    .map(|fen| Game::from_fen(invitation_data.variant, fen))
+                                                {
+                                                    println!("Incorrect initial position: {}", e);
+                                                    // the invitation with incorrect position is declined at once
+                                                    let response = WsResponse
+                                                        {
+                                                            action: "decline_invitation".to_owned(),
+                                                            data: invitation_data.user_name,
+                                                        };
+                                                    ctx.text(serde_json::to_string(&response).unwrap());
+                                                    return;
+                                                }
+                                                self.addr.do_send(server::Invitation
+                                                {
+                                                    id: self.id,
+                                                    to_user: invitation_data.user_name,
+                                                    room: self.room.clone(),
+                                                    action: action.to_owned(),
+                                                    variant: Some(invitation_data.variant),
+                                                    time_control: invitation_data.time_control,
+                                                    initial_fen: invitation_data.initial_fen,
+                                                    is_rated: invitation_data.is_rated,
+                                                    color_preference: invitation_data.color_preference,
+                                                });
+                                            },
+                                        Err(_) => println!("Incorrect invitation data: {}", data),
+                                    }
                                 },
                             "decline_invitation" =>
                                 {
@@ -281,6 +491,11 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsChatSession
                                         to_user: data.to_owned(),
                                         room: self.room.clone(),
                                         action: action.to_owned(),
+                                        variant: None,
+                                        time_control: None,
+                                        initial_fen: None,
+                                        is_rated: false,
+                                        color_preference: None,
                                     });
                                 },
                             "accept_invitation" =>
@@ -291,6 +506,11 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsChatSession
                                         to_user: data.to_owned(),
                                         room: self.room.clone(),
                                         action: action.to_owned(),
+                                        variant: None,
+                                        time_control: None,
+                                        initial_fen: None,
+                                        is_rated: false,
+                                        color_preference: None,
                                     });
                                 },
                             "send_checker_piece_move" =>
@@ -305,8 +525,31 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsChatSession
                                         room: self.room.clone(),
                                     })
                                 },
+                            "request_position_snapshot" =>
+                                {
+                                    self.addr.do_send(server::PositionRequest
+                                    {
+                                        id: self.id,
+                                        room: self.room.clone(),
+                                    })
+                                },
+                            "resign" | "offer_draw" | "accept_draw" | "decline_draw" |
+                            "request_takeback" | "accept_takeback" | "decline_takeback" =>
+                                {
+                                    self.addr.do_send(server::GameDecision
+                                    {
+                                        id: self.id,
+                                        room: self.room.clone(),
+                                        action: action.to_owned(),
+                                    })
+                                },
                             "send_leave_game_message" =>
                                 {
+                                    if data.starts_with(server::GAME_ROOM_PREFIX)
+                                    {
+                                        ctx.text(rejected_join_response());
+                                        return;
+                                    }
                                     self.room = data.to_owned();
                                     self.addr.do_send(server::LeaveGameMessage
                                     {
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::schema::checkers_game_chat;
 
+use checkers_rules::{Variant, TimeControl, ClockData, PieceColor};
+
 
 #[derive(Deserialize, Debug)]
 pub struct WsRequest
@@ -11,7 +13,7 @@ pub struct WsRequest
 }
 
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct WsResponse
 {
     pub action: String,
@@ -19,6 +21,106 @@ pub struct WsResponse
 }
 
 
+/// Query of the websocket request, browsers can not send the token in a header there.
+#[derive(Deserialize, Debug)]
+pub struct SessionToken
+{
+    pub token: Option<String>,
+}
+
+
+/// User of the room shown with the current rating.
+#[derive(Serialize, Debug)]
+pub struct OnlineUser
+{
+    pub user_name: String,
+    pub rating: i32,
+}
+
+
+/// Invitation to play, the user name is the invited user in requests
+/// and the inviting user in responses.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InvitationData
+{
+    pub user_name: String,
+    pub variant: Variant,
+    /// Games without time control are not limited in time
+    pub time_control: Option<TimeControl>,
+    /// Position the game starts from in the FEN notation
+    #[serde(default)]
+    pub initial_fen: Option<String>,
+    /// Hints are turned off in rated games
+    #[serde(default)]
+    pub is_rated: bool,
+    /// Color of the inviting user, it is chosen at random when absent
+    #[serde(default)]
+    pub color_preference: Option<PieceColor>,
+}
+
+
+/// Rules of the game the player is looking for in the matchmaking queue.
+#[derive(Deserialize, Debug)]
+pub struct GameSearch
+{
+    pub variant: Variant,
+    /// Games without time control are not limited in time
+    pub time_control: Option<TimeControl>,
+    #[serde(default)]
+    pub is_rated: bool,
+}
+
+
+/// Game of the tournament round which is started for the player.
+#[derive(Serialize, Debug)]
+pub struct TournamentGameReady
+{
+    pub tournament_id: i32,
+    pub tournament_name: String,
+    pub round: i32,
+    pub opponent: String,
+    pub piece_color: PieceColor,
+}
+
+
+/// Current position of the game sent to the session which requested it.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PositionSnapshot
+{
+    pub fen: String,
+    pub clock: Option<ClockData>,
+    /// Number of sessions watching the game
+    pub spectators: usize,
+}
+
+
+/// Game played at the moment, anyone is able to watch it.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LiveGame
+{
+    /// Name of the game room
+    pub room: String,
+    pub white_player: String,
+    pub black_player: String,
+    pub variant: Variant,
+    pub time_control: Option<TimeControl>,
+    pub is_rated: bool,
+    /// Number of sessions watching the game
+    pub spectators: usize,
+}
+
+
+/// Game the session plays, it is sent when the game starts and when the player comes back to it.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct JoinedGame
+{
+    pub game: LiveGame,
+    pub piece_color: PieceColor,
+    /// Current position and clock, they are sent when the player comes back to the game
+    pub position: Option<PositionSnapshot>,
+}
+
+
 #[derive(Insertable)]
 #[table_name="checkers_game_chat"]
 pub struct ChatMessage
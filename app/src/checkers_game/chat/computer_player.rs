@@ -0,0 +1,311 @@
+//! `ComputerPlayer` is an actor registered in `ChatServer` like a client session.
+//! It accepts unrated invitations while it is free and answers the moves of its opponent
+//! with moves found by the search of the rules crate.
+
+use actix::prelude::*;
+use actix_web::web;
+use std::time::Duration;
+
+use crate::checkers_game::chat::chat_models::{WsResponse, InvitationData, JoinedGame};
+use crate::checkers_game::chat::server;
+use serde_json;
+
+use checkers_rules::{Game, GameData, PieceColor, EngineLevel, best_move};
+
+
+/// Room where users invite each other
+const LOBBY_ROOM: &str = "checkers_game";
+/// Pause before every move, so the opponent has time to join the game room
+const MOVE_DELAY: Duration = Duration::from_millis(700);
+/// How long the accepted invitation waits for the server to start the game
+const PENDING_GAME_TIMEOUT: Duration = Duration::from_secs(10);
+/// Names of computer players start with it, users can not take such names
+const COMPUTER_PLAYER_PREFIX: &str = "computer_";
+
+
+pub struct ComputerPlayer
+{
+    /// Id of the session in the chat server
+    id: usize,
+    level: EngineLevel,
+    addr: Addr<server::ChatServer>,
+    /// Room of the current game
+    room: String,
+    game: Option<Game>,
+    /// Game of the accepted invitation with the name of the inviting user, it waits for the server to start it
+    pending_game: Option<(String, Game)>,
+    piece_color: PieceColor,
+}
+
+
+impl ComputerPlayer
+{
+    pub fn new(level: EngineLevel, addr: Addr<server::ChatServer>) -> Self
+    {
+        ComputerPlayer
+        {
+            id: 0, level, addr,
+            room: LOBBY_ROOM.to_owned(),
+            game: None,
+            pending_game: None,
+            piece_color: PieceColor::White,
+        }
+    }
+
+
+    pub fn user_name(level: &EngineLevel) -> String
+    {
+        format!("{}{}", COMPUTER_PLAYER_PREFIX, level.as_str())
+    }
+
+
+    /// Users can not take names of computer players.
+    pub fn is_reserved_name(user_name: &str) -> bool
+    {
+        user_name.starts_with(COMPUTER_PLAYER_PREFIX)
+    }
+
+
+    fn answer_invitation(&self, to_user: &str, action: &str)
+    {
+        self.addr.do_send(server::Invitation
+            {
+                id: self.id,
+                to_user: to_user.to_owned(),
+                room: LOBBY_ROOM.to_owned(),
+                action: action.to_owned(),
+                variant: None,
+                time_control: None,
+                initial_fen: None,
+                is_rated: false,
+                color_preference: None,
+            });
+    }
+
+
+    /// The computer plays one game at a time and only unrated ones, so no rating is won against it.
+    /// The game is kept until the server starts it and tells the color of the pieces.
+    fn accept_invitation(&mut self, invitation_data: InvitationData, ctx: &mut Context<Self>)
+    {
+        let inviting_user = invitation_data.user_name;
+        let game = match &invitation_data.initial_fen
+        {
+            Some(fen) => Game::from_fen(invitation_data.variant, fen).ok(),
+            None => Some(Game::new(invitation_data.variant)),
+        };
+        let game = match game
+        {
+            Some(game) if !invitation_data.is_rated && self.game.is_none() && self.pending_game.is_none() => game,
+            _ =>
+                {
+                    self.answer_invitation(&inviting_user, "decline_invitation");
+                    return;
+                },
+        };
+        self.answer_invitation(&inviting_user, "accept_invitation");
+        self.pending_game = Some((inviting_user.to_owned(), game));
+        // the inviting user may leave before the game starts
+        ctx.run_later(PENDING_GAME_TIMEOUT, move |act, _|
+            {
+                if act.pending_game.as_ref().map_or(false, |(pending_user, _)| *pending_user == inviting_user)
+                {
+                    act.pending_game = None;
+                }
+            });
+    }
+
+
+    /// Only the game of the accepted invitation is started.
+    fn start_game(&mut self, joined_game: JoinedGame, ctx: &mut Context<Self>)
+    {
+        let is_pending = self.pending_game.as_ref().map_or(false, |(inviting_user, _)|
+            joined_game.game.white_player == *inviting_user || joined_game.game.black_player == *inviting_user);
+        if !is_pending
+        {
+            return;
+        }
+        if let Some((_, game)) = self.pending_game.take()
+        {
+            self.game = Some(game);
+            self.room = joined_game.game.room;
+            self.piece_color = joined_game.piece_color;
+            self.schedule_move(ctx);
+        }
+    }
+
+
+    fn schedule_move(&mut self, ctx: &mut Context<Self>)
+    {
+        match &self.game
+        {
+            Some(game) if game.outcome.is_some() => self.leave_game(),
+            Some(game) if game.turn == self.piece_color =>
+                {
+                    ctx.run_later(MOVE_DELAY, |act, ctx| act.search_move(ctx));
+                },
+            _ => (),
+        }
+    }
+
+
+    /// The search runs in the thread pool, so other actors are not blocked.
+    fn search_move(&mut self, ctx: &mut Context<Self>)
+    {
+        let game = match &self.game
+        {
+            Some(game) => game.clone(),
+            None => return,
+        };
+        let level = self.level;
+        web::block(move || best_move(&game, level).ok_or(()))
+            .into_actor(self)
+            .then(|res, act, ctx|
+                {
+                    if let Ok(game_data) = res
+                    {
+                        act.send_move(game_data, ctx);
+                    }
+                    fut::ready(())
+                })
+            .spawn(ctx);
+    }
+
+
+    fn send_move(&mut self, game_data: GameData, ctx: &mut Context<Self>)
+    {
+        // the game may be already over while the move was searched
+        if let Some(game) = &mut self.game
+        {
+            if game.apply_move(&game_data).is_ok()
+            {
+                self.addr.do_send(server::GameMessage
+                    {
+                        id: self.id,
+                        msg: serde_json::to_string(&game_data).unwrap(),
+                        room: self.room.clone(),
+                    });
+                self.schedule_move(ctx);
+            }
+        }
+    }
+
+
+    fn receive_move(&mut self, game_data: GameData, ctx: &mut Context<Self>)
+    {
+        if let Some(game) = &mut self.game
+        {
+            if game.apply_move(&game_data).is_ok()
+            {
+                self.schedule_move(ctx);
+            }
+        }
+    }
+
+
+    fn decline(&self, action: &str)
+    {
+        self.addr.do_send(server::GameDecision
+            {
+                id: self.id,
+                room: self.room.clone(),
+                action: action.to_owned(),
+            });
+    }
+
+
+    fn leave_game(&mut self)
+    {
+        if self.game.take().is_some()
+        {
+            self.room = LOBBY_ROOM.to_owned();
+            self.addr.do_send(server::LeaveGameMessage { id: self.id, name: LOBBY_ROOM.to_owned() });
+        }
+    }
+}
+
+
+impl Actor for ComputerPlayer
+{
+    type Context = Context<Self>;
+
+
+    /// Registers in the chat server and waits for invitations in the lobby.
+    fn started(&mut self, ctx: &mut Self::Context)
+    {
+        let addr = ctx.address();
+        self.addr
+            .send(server::Connect
+            {
+                addr: addr.recipient(),
+            })
+            .into_actor(self)
+            .then(|res, act, ctx|
+                {
+                    match res
+                    {
+                        Ok(res) =>
+                            {
+                                act.id = res;
+                                act.addr.do_send(server::SetUserName
+                                    {
+                                        id: act.id,
+                                        user_name: ComputerPlayer::user_name(&act.level),
+                                    });
+                                act.addr.do_send(server::Join { id: act.id, name: LOBBY_ROOM.to_owned() });
+                            },
+                        // something is wrong with chat server
+                        _ => ctx.stop(),
+                    }
+                    fut::ready(())
+                })
+            .wait(ctx);
+    }
+}
+
+
+/// Messages of the chat server are the same as the ones sent to client sessions.
+impl Handler<server::Message> for ComputerPlayer
+{
+    type Result = ();
+
+    fn handle(&mut self, msg: server::Message, ctx: &mut Self::Context)
+    {
+        let response = match serde_json::from_str::<WsResponse>(&msg.0)
+        {
+            Ok(response) => response,
+            Err(_) => return,
+        };
+        match response.action.as_str()
+        {
+            "invitation" =>
+                {
+                    if let Ok(invitation_data) = serde_json::from_str::<InvitationData>(&response.data)
+                    {
+                        self.accept_invitation(invitation_data, ctx);
+                    }
+                },
+            // the inviting user left or started another game before the acceptance
+            "cancelled_invitation"
+                if self.pending_game.as_ref().map_or(false, |(inviting_user, _)| *inviting_user == response.data) =>
+                    self.pending_game = None,
+            "game_started" =>
+                {
+                    if let Ok(joined_game) = serde_json::from_str::<JoinedGame>(&response.data)
+                    {
+                        self.start_game(joined_game, ctx);
+                    }
+                },
+            "received_checker_piece_move" =>
+                {
+                    if let Ok(game_data) = serde_json::from_str::<GameData>(&response.data)
+                    {
+                        self.receive_move(game_data, ctx);
+                    }
+                },
+            "received_draw_offer" => self.decline("decline_draw"),
+            "received_takeback_request" => self.decline("decline_takeback"),
+            "game_over" | "received_leave_game_message" => self.leave_game(),
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! `DbExecutor` is a synchronous actor which runs the database queries of `ChatServer`.
+//! It works in its own thread, so the chat server never waits for the database,
+//! and it is the only thread, so the moves of every game are saved in the order they were made.
+
+use actix::prelude::*;
+use chrono::NaiveDateTime;
+use diesel::r2d2::{ConnectionManager, PooledConnection};
+use diesel::PgConnection;
+
+use crate::checkers_game::game::{game_database, game_models};
+use crate::checkers_game::rating::rating_database;
+use crate::checkers_game::tournament::tournament_database;
+use crate::DbPool;
+
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SaveGame(pub game_models::NewGame);
+
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SaveMove(pub game_models::NewGameMove);
+
+
+/// Moves taken back by the players are removed.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct DeleteMovesAfter
+{
+    pub game_id: String,
+    pub ply: i32,
+}
+
+
+/// Result of the finished game, it goes into the ratings of the players and the standings of the tournament.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct FinishGame
+{
+    pub game_id: String,
+    pub finished_at: NaiveDateTime,
+    pub result: String,
+    /// White player, black player and the score of white when the game is rated
+    pub rated_players: Option<(String, String, f64)>,
+    pub tournament_game_id: Option<i32>,
+}
+
+
+/// Id of the game played for the tournament game.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SetTournamentGameId
+{
+    pub tournament_game_id: i32,
+    pub game_id: String,
+}
+
+
+/// Rating of the player, players without rated games get the initial one.
+#[derive(Message)]
+#[rtype(result = "Option<f64>")]
+pub struct ExtractRating
+{
+    pub user_name: String,
+}
+
+
+pub struct DbExecutor
+{
+    pool: DbPool,
+}
+
+
+impl DbExecutor
+{
+    pub fn new(pool: DbPool) -> Self
+    {
+        DbExecutor { pool }
+    }
+
+
+    /// The query is skipped when the database is not available, the games go on without it.
+    fn connection(&self) -> Option<PooledConnection<ConnectionManager<PgConnection>>>
+    {
+        match self.pool.get()
+        {
+            Ok(conn) => Some(conn),
+            Err(e) =>
+                {
+                    println!("Error to get db connection: {} !!!", e);
+                    None
+                },
+        }
+    }
+}
+
+
+impl Actor for DbExecutor
+{
+    type Context = SyncContext<Self>;
+}
+
+
+impl Handler<SaveGame> for DbExecutor
+{
+    type Result = ();
+
+    fn handle(&mut self, msg: SaveGame, _: &mut Self::Context)
+    {
+        if let Some(conn) = self.connection()
+        {
+            game_database::insert_new_game(msg.0, &conn);
+        }
+    }
+}
+
+
+impl Handler<SaveMove> for DbExecutor
+{
+    type Result = ();
+
+    fn handle(&mut self, msg: SaveMove, _: &mut Self::Context)
+    {
+        if let Some(conn) = self.connection()
+        {
+            game_database::insert_new_move(msg.0, &conn);
+        }
+    }
+}
+
+
+impl Handler<DeleteMovesAfter> for DbExecutor
+{
+    type Result = ();
+
+    fn handle(&mut self, msg: DeleteMovesAfter, _: &mut Self::Context)
+    {
+        if let Some(conn) = self.connection()
+        {
+            game_database::delete_moves_after(msg.game_id, msg.ply, &conn);
+        }
+    }
+}
+
+
+impl Handler<FinishGame> for DbExecutor
+{
+    type Result = ();
+
+    fn handle(&mut self, msg: FinishGame, _: &mut Self::Context)
+    {
+        let conn = match self.connection()
+        {
+            Some(conn) => conn,
+            None => return,
+        };
+        game_database::finish_game(msg.game_id, msg.finished_at, msg.result.to_owned(), &conn);
+        if let Some((white_player, black_player, white_score)) = msg.rated_players
+        {
+            match rating_database::update_ratings(&white_player, &black_player, white_score, &conn)
+            {
+                Ok((white_rating, black_rating)) =>
+                    println!("New ratings: '{}' {}, '{}' {}",
+                        white_player, white_rating.rounded_rating(), black_player, black_rating.rounded_rating()),
+                Err(_) => println!("Error to save player ratings in database!!!"),
+            }
+        }
+        if let Some(tournament_game_id) = msg.tournament_game_id
+        {
+            tournament_database::save_result(tournament_game_id, msg.result, &conn);
+        }
+    }
+}
+
+
+impl Handler<SetTournamentGameId> for DbExecutor
+{
+    type Result = ();
+
+    fn handle(&mut self, msg: SetTournamentGameId, _: &mut Self::Context)
+    {
+        if let Some(conn) = self.connection()
+        {
+            tournament_database::set_game_id(msg.tournament_game_id, msg.game_id, &conn);
+        }
+    }
+}
+
+
+impl Handler<ExtractRating> for DbExecutor
+{
+    type Result = Option<f64>;
+
+    fn handle(&mut self, msg: ExtractRating, _: &mut Self::Context) -> Self::Result
+    {
+        let conn = self.connection()?;
+        match rating_database::extract_player_rating(&msg.user_name, &conn)
+        {
+            Ok(player_rating) => Some(player_rating.rating),
+            Err(_) =>
+                {
+                    println!("Error to extract player rating from database!!!");
+                    None
+                },
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::time::Instant;
+
+use checkers_rules::{TimeControl, ClockData, PieceColor};
+
+
+/// Clock of the game room, the time of the side to move is running since `turn_started`.
+pub struct GameClock
+{
+    time_control: TimeControl,
+    data: ClockData,
+    turn_started: Instant,
+}
+
+
+impl GameClock
+{
+    /// The clock of the side to move in the starting position is running.
+    pub fn new(time_control: TimeControl, turn: PieceColor) -> Self
+    {
+        let data = ClockData::new(&time_control, turn);
+        GameClock { time_control, data, turn_started: Instant::now() }
+    }
+
+
+    fn spent_millis(&self) -> u64
+    {
+        self.turn_started.elapsed().as_millis() as u64
+    }
+
+
+    /// Whether the side to move has run out of time.
+    pub fn is_time_over(&self) -> bool
+    {
+        self.spent_millis() >= self.data.millis(&self.data.turn)
+    }
+
+
+    pub fn turn(&self) -> &PieceColor
+    {
+        &self.data.turn
+    }
+
+
+    pub fn time_control(&self) -> &TimeControl
+    {
+        &self.time_control
+    }
+
+
+    /// Stops the clock of the moved side and starts the clock of its opponent.
+    pub fn switch(&mut self)
+    {
+        let color = self.data.turn.to_owned();
+        let millis = self.time_control.millis_after_move(self.data.millis(&color), self.spent_millis());
+        self.data.set_millis(&color, millis);
+        self.data.turn = color.opposite();
+        self.turn_started = Instant::now();
+    }
+
+
+    /// Remaining time of both players at the moment.
+    pub fn clock_data(&self) -> ClockData
+    {
+        let mut data = self.data.clone();
+        let color = data.turn.to_owned();
+        data.set_millis(&color, self.data.millis(&color).saturating_sub(self.spent_millis()));
+        data
+    }
+}
@@ -2,6 +2,9 @@ pub mod chat;
 pub mod server;
 pub mod chat_models;
 pub mod chat_database;
+pub mod game_clock;
+pub mod db_executor;
+pub mod computer_player;
 
 // pub use chat::chat_route;
 // pub use server::ChatServer;
@@ -5,10 +5,38 @@
 use actix::prelude::*;
 use rand::{self, rngs::ThreadRng, Rng};
 use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use uuid::Uuid;
 
-use crate::checkers_game::chat::chat_models::WsResponse;
+use crate::checkers_game::chat::chat_models::
+    {
+        WsResponse, InvitationData, PositionSnapshot, LiveGame, JoinedGame, TournamentGameReady,
+    };
+use crate::checkers_game::chat::db_executor::
+    {
+        DbExecutor, SaveGame, SaveMove, DeleteMovesAfter, FinishGame, SetTournamentGameId, ExtractRating,
+    };
+use crate::checkers_game::chat::game_clock::GameClock;
+use crate::checkers_game::game::game_models;
+use crate::checkers_game::tournament::tournament_models::DOUBLE_FORFEIT_RESULT;
+use crate::DbPool;
 use serde_json;
 
+use checkers_rules::{Game, GameData, GameOutcome, PieceColor, Variant, TimeControl};
+
+
+pub const GAME_ROOM_PREFIX: &str = "checkers_game_";
+/// How often clocks of all games are checked for the time over
+const CLOCK_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+/// How long the game of the disconnected player is held open for the player to come back
+const RECONNECT_GRACE_PERIOD: Duration = Duration::from_secs(60);
+/// Largest difference of ratings of the players paired at once
+const INITIAL_RATING_WINDOW: f64 = 100.0;
+/// The rating window grows by this number of points every step of waiting in the queue
+const RATING_WINDOW_GROWTH: f64 = 50.0;
+const RATING_WINDOW_STEP: Duration = Duration::from_secs(10);
+
 
 /// Chat server sends this messages to session
 #[derive(Message)]
@@ -91,6 +119,15 @@ pub struct Invitation
     pub to_user: String,
     pub room: String,
     pub action: String,
+    /// Rules chosen by the inviting user, sent with the invitation only
+    pub variant: Option<Variant>,
+    pub time_control: Option<TimeControl>,
+    /// Position the game starts from, the initial position of the variant when absent
+    pub initial_fen: Option<String>,
+    /// Hints are turned off in rated games
+    pub is_rated: bool,
+    /// Color of the inviting user, it is chosen at random when absent
+    pub color_preference: Option<PieceColor>,
 }
 
 
@@ -108,6 +145,119 @@ pub struct GameMessage
 }
 
 
+/// Resign, offer a draw or ask to take back the last move, accept or decline the opponent's offer or request
+/// in the game played in the room.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct GameDecision
+{
+    /// Id of the client session
+    pub id: usize,
+    /// Room name
+    pub room: String,
+    pub action: String,
+}
+
+
+/// Request of the current position of the game, it is sent back to the requesting session only.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct PositionRequest
+{
+    /// Id of the client session
+    pub id: usize,
+    /// Room name
+    pub room: String,
+}
+
+
+/// Check whether the user plays an unfinished rated game.
+#[derive(Message)]
+#[rtype(result = "bool")]
+pub struct RatedGamePlayer
+{
+    pub user_name: String,
+}
+
+
+/// Put the session back into the unfinished game of its user.
+#[derive(Message)]
+#[rtype(result = "Option<JoinedGame>")]
+pub struct Reconnect
+{
+    /// Id of the client session
+    pub id: usize,
+}
+
+
+/// List games which are played at the moment.
+#[derive(Message)]
+#[rtype(result = "Vec<LiveGame>")]
+pub struct ListLiveGames;
+
+
+/// Watch the game played in the room, the session joins the room without becoming a player.
+#[derive(Message)]
+#[rtype(result = "Option<LiveGame>")]
+pub struct WatchGame
+{
+    /// Id of the client session
+    pub id: usize,
+    /// Room name
+    pub room: String,
+}
+
+
+/// Put the session into the matchmaking queue, the game starts when an opponent
+/// with similar rating looks for the game with the same rules.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct FindGame
+{
+    /// Id of the client session
+    pub id: usize,
+    pub variant: Variant,
+    pub time_control: Option<TimeControl>,
+    pub is_rated: bool,
+}
+
+
+/// Remove the session from the matchmaking queue.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct CancelFindGame
+{
+    /// Id of the client session
+    pub id: usize,
+}
+
+
+/// Start games of the tournament round, the players are told about their games
+/// and their sessions are moved into the game rooms.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct StartTournamentRound
+{
+    pub tournament_id: i32,
+    pub tournament_name: String,
+    pub round: i32,
+    pub variant: Variant,
+    pub time_control: Option<TimeControl>,
+    /// Id of the tournament game with user names of the white and the black players
+    pub games: Vec<(i32, String, String)>,
+}
+
+
+/// Result of the tournament game set by the organizer, the game played at the moment is closed with it.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct AdjudicateGame
+{
+    pub tournament_game_id: i32,
+    pub result: String,
+}
+
+
 /// Leave current game and join to chat room, if room does not exists create new one.
 #[derive(Message)]
 #[rtype(result = "()")]
@@ -128,19 +278,131 @@ struct SessionData
 }
 
 
+/// Authoritative state of the game played in the room.
+struct GameRoom
+{
+    /// Id of the game in the database
+    game_id: String,
+    game: Game,
+    /// Piece color of every player by user name
+    players: HashMap<String, PieceColor>,
+    clock: Option<GameClock>,
+    /// Number of accepted moves
+    ply: i32,
+    is_rated: bool,
+    /// Ids of the sessions watching the game
+    spectators: HashSet<usize>,
+    /// Moments the players lost their connection by user name
+    disconnected_players: HashMap<String, Instant>,
+    /// Id of the game in the tournament, the result of the game goes into its standings
+    tournament_game_id: Option<i32>,
+}
+
+
+impl GameRoom
+{
+    fn player(&self, color: &PieceColor) -> Option<&str>
+    {
+        self.players
+            .iter()
+            .find(|(_, player_color)| *player_color == color)
+            .map(|(user_name, _)| user_name.as_str())
+    }
+
+
+    fn live_game(&self, room: &str) -> LiveGame
+    {
+        let player = |color: PieceColor| self.player(&color).unwrap_or_default().to_owned();
+        LiveGame
+        {
+            room: room.to_owned(),
+            white_player: player(PieceColor::White),
+            black_player: player(PieceColor::Black),
+            variant: self.game.board.variant,
+            time_control: self.clock.as_ref().map(|clock| *clock.time_control()),
+            is_rated: self.is_rated,
+            spectators: self.spectators.len(),
+        }
+    }
+
+
+    fn position_snapshot(&self) -> PositionSnapshot
+    {
+        PositionSnapshot
+        {
+            fen: self.game.to_fen(),
+            clock: self.clock.as_ref().map(|clock| clock.clock_data()),
+            spectators: self.spectators.len(),
+        }
+    }
+}
+
+
+/// Settings of the game chosen by the inviting user.
+struct InvitationSettings
+{
+    variant: Variant,
+    time_control: Option<TimeControl>,
+    initial_fen: Option<String>,
+    is_rated: bool,
+    color_preference: Option<PieceColor>,
+}
+
+
+/// Session waiting in the matchmaking queue.
+struct QueuedPlayer
+{
+    id: usize,
+    user_name: String,
+    rating: f64,
+    variant: Variant,
+    time_control: Option<TimeControl>,
+    is_rated: bool,
+    queued_at: Instant,
+}
+
+
+impl QueuedPlayer
+{
+    /// Difference of ratings the player accepts, it grows the longer the player waits.
+    fn rating_window(&self) -> f64
+    {
+        let steps = (self.queued_at.elapsed().as_secs() / RATING_WINDOW_STEP.as_secs()) as f64;
+        INITIAL_RATING_WINDOW + RATING_WINDOW_GROWTH * steps
+    }
+
+
+    fn is_match(&self, other: &QueuedPlayer) -> bool
+    {
+        self.user_name != other.user_name &&
+            self.variant == other.variant &&
+            self.time_control == other.time_control &&
+            self.is_rated == other.is_rated &&
+            (self.rating - other.rating).abs() <= self.rating_window().max(other.rating_window())
+    }
+}
+
+
 /// `ChatServer` manages chat rooms and responsible for coordinating chat
 /// session. implementation is super primitive
 pub struct ChatServer
 {
     sessions: HashMap<usize, SessionData>,
     rooms: HashMap<String, HashSet<usize>>,
+    games: HashMap<String, GameRoom>,
+    /// Settings of pending invitations by inviting and invited user names
+    invitations: HashMap<(String, String), InvitationSettings>,
+    /// Sessions looking for a game in the order they started to look
+    matchmaking_queue: Vec<QueuedPlayer>,
     rng: ThreadRng,
+    /// Games are saved in the database by the separate actor, so the sessions do not wait for it
+    db: Addr<DbExecutor>,
 }
 
 
-impl Default for ChatServer
+impl ChatServer
 {
-    fn default() -> ChatServer
+    pub fn new(pool: DbPool) -> ChatServer
     {
         // default room
         let mut rooms = HashMap::new();
@@ -150,14 +412,320 @@ impl Default for ChatServer
         {
             sessions: HashMap::new(),
             rooms,
+            games: HashMap::new(),
+            invitations: HashMap::new(),
+            matchmaking_queue: Vec::new(),
             rng: rand::thread_rng(),
+            db: SyncArbiter::start(1, move || DbExecutor::new(pool.clone())),
+        }
+    }
+
+
+    /// Remove the game from the room and save its result,
+    /// the player who left the unfinished game loses it.
+    fn end_game(&mut self, room: &str, left_user_name: Option<&str>) -> Option<GameRoom>
+    {
+        let mut game_room = self.games.remove(room)?;
+        if let Some(color) = left_user_name.and_then(|user_name| game_room.players.get(user_name)).cloned()
+        {
+            let _ = game_room.game.resign(&color);
+        }
+        if let Some(outcome) = &game_room.game.outcome
+        {
+            println!("Game in '{}' is over: {:?}", room, outcome);
+            // ratings of both players are recalculated after every finished rated game
+            let rated_players =
+                match (game_room.player(&PieceColor::White), game_room.player(&PieceColor::Black))
+                {
+                    (Some(white_player), Some(black_player)) if game_room.is_rated =>
+                        Some((white_player.to_owned(), black_player.to_owned(), outcome.score(&PieceColor::White))),
+                    _ => None,
+                };
+            self.db.do_send(FinishGame
+                {
+                    game_id: game_room.game_id.to_owned(),
+                    finished_at: Utc::now().naive_utc(),
+                    result: outcome.as_result().to_owned(),
+                    rated_players,
+                    tournament_game_id: game_room.tournament_game_id,
+                });
+        }
+        // games of the players which waited for this one to end, such as tournament games, start the grace period
+        let waiting_games: Vec<(String, String)> = game_room.players
+            .keys()
+            .flat_map(|user_name| self.games
+                .iter()
+                .filter(move |(_, waiting_room)|
+                    waiting_room.players.contains_key(user_name) && !waiting_room.disconnected_players.contains_key(user_name))
+                .map(move |(waiting_room, _)| (waiting_room.to_owned(), user_name.to_owned())))
+            .filter(|(waiting_room, user_name)| !self.is_user_in_room(waiting_room, user_name))
+            .collect();
+        for (waiting_room, user_name) in waiting_games
+        {
+            self.hold_game(&waiting_room, &user_name);
+        }
+        Some(game_room)
+    }
+
+
+    /// The spectator leaves the game, the rest of the room gets the new number of spectators.
+    fn remove_spectator(&mut self, room: &str, id: usize) -> bool
+    {
+        let spectators_count = match self.games.get_mut(room)
+        {
+            Some(game_room) =>
+                {
+                    if !game_room.spectators.remove(&id)
+                    {
+                        return false;
+                    }
+                    game_room.spectators.len()
+                },
+            None => return false,
+        };
+        self.send_message(room, "spectators_count", &spectators_count.to_string(), 0);
+        true
+    }
+
+
+    /// Ids of all sessions of the user in the room.
+    fn user_sessions(&self, room: &str, user_name: &str) -> Vec<usize>
+    {
+        self.rooms
+            .get(room)
+            .map(|sessions| sessions
+                .iter()
+                .filter(|id| self.sessions.get(id).and_then(|session| session.user_name.as_deref()) == Some(user_name))
+                .copied()
+                .collect())
+            .unwrap_or_default()
+    }
+
+
+    /// Create the game of the accepted invitation or of the paired queued players,
+    /// the given sessions of both players leave the lobby for its room.
+    fn start_game(
+        &mut self, settings: InvitationSettings, inviting_user: &str, inviting_ids: Vec<usize>,
+        accepting_user: &str, accepting_id: usize)
+    {
+        let inviting_color = settings.color_preference.clone()
+            .unwrap_or_else(|| if self.rng.gen::<bool>() { PieceColor::White } else { PieceColor::Black });
+        let (white_player, black_player) =
+            if inviting_color == PieceColor::White { (inviting_user, accepting_user) }
+            else { (accepting_user, inviting_user) };
+
+        let mut player_sessions: Vec<(usize, String)> = inviting_ids
+            .into_iter()
+            .map(|id| (id, inviting_user.to_owned()))
+            .collect();
+        player_sessions.push((accepting_id, accepting_user.to_owned()));
+
+        self.open_game_room(settings, white_player, black_player, player_sessions, None);
+    }
+
+
+    /// Create the game with its room and move the sessions of the players into it,
+    /// every moved session gets its color. Returns the id of the game in the database.
+    fn open_game_room(
+        &mut self, settings: InvitationSettings, white_player: &str, black_player: &str,
+        player_sessions: Vec<(usize, String)>, tournament_game_id: Option<i32>) -> String
+    {
+        let game_id = Uuid::new_v4().to_string();
+        let room = format!("{}{}", GAME_ROOM_PREFIX, game_id);
+        let mut players = HashMap::new();
+        players.insert(white_player.to_owned(), PieceColor::White);
+        players.insert(black_player.to_owned(), PieceColor::Black);
+        self.matchmaking_queue.retain(|queued_player| !players.contains_key(&queued_player.user_name));
+
+        // the position is checked when the invitation is received
+        let game = settings.initial_fen.as_ref()
+            .and_then(|fen| Game::from_fen(settings.variant, fen).ok())
+            .unwrap_or_else(|| Game::new(settings.variant));
+        self.db.do_send(SaveGame(game_models::NewGame
+            {
+                id: game_id.to_owned(),
+                white_player: white_player.to_owned(),
+                black_player: black_player.to_owned(),
+                variant: settings.variant.as_str().to_owned(),
+                started_at: Utc::now().naive_utc(),
+                initial_fen: settings.initial_fen,
+                is_rated: settings.is_rated,
+                time_control: settings.time_control.map(|time_control| time_control.as_key()),
+                is_played_on_server: true,
+            }));
+
+        self.games.insert(
+            room.to_owned(),
+            GameRoom
+            {
+                game_id: game_id.to_owned(),
+                clock: settings.time_control
+                    .map(|time_control| GameClock::new(time_control, game.turn.to_owned())),
+                game,
+                players,
+                ply: 0,
+                is_rated: settings.is_rated,
+                spectators: HashSet::new(),
+                disconnected_players: HashMap::new(),
+                tournament_game_id,
+            });
+
+        for (id, user_name) in player_sessions
+        {
+            self.move_session(id, &room, Some(&user_name));
+            if let Some(game_room) = self.games.get(&room)
+            {
+                let joined_game = JoinedGame
+                    {
+                        game: game_room.live_game(&room),
+                        piece_color: game_room.players[&user_name].to_owned(),
+                        position: None,
+                    };
+                self.send_message_to_session(id, "game_started", &serde_json::to_string(&joined_game).unwrap());
+            }
+        }
+        game_id
+    }
+
+
+    /// Put the session into the unfinished game of its user. When the user plays several games, the held game
+    /// the user left first is chosen, since it is the closest to be lost, then the room with the lowest name.
+    fn rejoin_game(&mut self, id: usize, user_name: &str) -> Option<JoinedGame>
+    {
+        let joined_game = self.games
+            .iter_mut()
+            .filter(|(_, game_room)| game_room.players.contains_key(user_name))
+            .min_by_key(|(room, game_room)|
+                {
+                    let disconnected_at = game_room.disconnected_players.get(user_name).cloned();
+                    (disconnected_at.is_none(), disconnected_at, (*room).to_owned())
+                })
+            .map(|(room, game_room)|
+                {
+                    game_room.disconnected_players.remove(user_name);
+                    JoinedGame
+                    {
+                        game: game_room.live_game(room),
+                        piece_color: game_room.players[user_name].to_owned(),
+                        position: Some(game_room.position_snapshot()),
+                    }
+                });
+
+        if let Some(joined_game) = &joined_game
+        {
+            let room = joined_game.game.room.to_owned();
+            println!("'{}' came back to '{}'", user_name, room);
+            self.move_session(id, &room, Some(user_name));
+            self.send_message(&room, "opponent_reconnected", user_name, id);
+        }
+        joined_game
+    }
+
+
+    /// Whether the user plays some unfinished game.
+    fn is_playing(&self, user_name: &str) -> bool
+    {
+        self.games.values().any(|game_room| game_room.players.contains_key(user_name))
+    }
+
+
+    /// Whether the user is still in the room with some other session.
+    fn is_user_in_room(&self, room: &str, user_name: &str) -> bool
+    {
+        self.rooms.get(room).map_or(false, |sessions| sessions
+            .iter()
+            .filter_map(|id| self.sessions.get(id))
+            .any(|session| session.user_name.as_deref() == Some(user_name)))
+    }
+
+
+    /// The game of the disconnected player is held open for the grace period.
+    fn hold_game(&mut self, room: &str, user_name: &str)
+    {
+        let is_player = match self.games.get_mut(room)
+        {
+            Some(game_room) if game_room.players.contains_key(user_name) =>
+                {
+                    game_room.disconnected_players.insert(user_name.to_owned(), Instant::now());
+                    true
+                },
+            _ => false,
+        };
+        if is_player
+        {
+            self.send_message(room, "opponent_disconnected", user_name, 0);
+        }
+    }
+
+
+    /// Move the session from all its rooms into the room, the rooms it left are told about it.
+    fn move_session(&mut self, id: usize, room: &str, user_name: Option<&str>)
+    {
+        self.matchmaking_queue.retain(|queued_player| queued_player.id != id);
+        let mut rooms = Vec::new();
+        for (name, sessions) in &mut self.rooms
+        {
+            if sessions.remove(&id)
+            {
+                rooms.push(name.to_owned());
+            }
+        }
+        for left_room in rooms
+        {
+            if !self.remove_spectator(&left_room, id) && left_room != room
+            {
+                self.send_message(&left_room, "disconnect", user_name.unwrap_or("Someone disconnected"), 0);
+            }
+        }
+
+        self.rooms
+            .entry(room.to_owned())
+            .or_insert(HashSet::new())
+            .insert(id);
+    }
+
+
+    /// The player left the game, so the game is lost and everyone left in the room gets its outcome.
+    fn leave_game(&mut self, room: &str, user_name: Option<&str>)
+    {
+        if let Some(outcome) = self.end_game(room, user_name).and_then(|game_room| game_room.game.outcome)
+        {
+            self.send_message(room, "game_over", &serde_json::to_string(&outcome).unwrap(), 0);
+        }
+    }
+
+
+    /// The game is already rolled back, stored moves and the clock follow it.
+    /// Both players get the number of taken back plies.
+    fn take_back(&mut self, room: &str, plies: usize)
+    {
+        let clock_data =
+            {
+                let game_room = match self.games.get_mut(room)
+                {
+                    Some(game_room) => game_room,
+                    None => return,
+                };
+                game_room.ply -= plies as i32;
+                self.db.do_send(DeleteMovesAfter { game_id: game_room.game_id.to_owned(), ply: game_room.ply });
+                let turn = &game_room.game.turn;
+                game_room.clock.as_mut().map(|clock|
+                    {
+                        if clock.turn() != turn
+                        {
+                            clock.switch();
+                        }
+                        clock.clock_data()
+                    })
+            };
+        self.send_message(room, "takeback", &plies.to_string(), 0);
+        if let Some(clock_data) = clock_data
+        {
+            self.send_message(room, "clock_update", &serde_json::to_string(&clock_data).unwrap(), 0);
         }
     }
-}
 
 
-impl ChatServer
-{
     /// Send message to all users in the room
     fn send_message(&self, room: &str, action: &str, message: &str, skip_id: usize)
     {
@@ -179,7 +747,19 @@ impl ChatServer
     }
 
 
-    fn process_invitation(&self, room: &str, from_user: &str, to_user: &str, action: &str)
+    /// Send message to the specific session
+    fn send_message_to_session(&self, id: usize, action: &str, message: &str)
+    {
+        if let Some(addr) = self.sessions.get(&id)
+        {
+            let response = WsResponse { action: action.to_owned(), data: message.to_owned() };
+            let m = serde_json::to_string(&response).unwrap();
+            let _ = addr.recipient.do_send(Message(m));
+        }
+    }
+
+
+    fn process_invitation(&self, room: &str, to_user: &str, action: &str, data: &str)
     {
         if let Some(sessions) = self.rooms.get(room)
         {
@@ -191,7 +771,7 @@ impl ChatServer
                     {
                         if user_name == to_user
                         {
-                            let response = WsResponse { action: action.to_owned(), data: from_user.to_owned() };
+                            let response = WsResponse { action: action.to_owned(), data: data.to_owned() };
                             let m = serde_json::to_string(&response).unwrap();
                             let _ = addr.recipient.do_send(Message(m));
                         }
@@ -200,6 +780,133 @@ impl ChatServer
             }
         }
     }
+
+
+    /// Start games of the queued players, the players who wait longer are paired first
+    /// with the opponents of the closest rating.
+    fn match_queued_players(&mut self)
+    {
+        let mut idx = 0;
+        while idx < self.matchmaking_queue.len()
+        {
+            let player = &self.matchmaking_queue[idx];
+            let opponent_idx = self.matchmaking_queue
+                .iter()
+                .enumerate()
+                .skip(idx + 1)
+                .filter(|(_, opponent)| player.is_match(opponent))
+                .min_by(|(_, a), (_, b)| (a.rating - player.rating).abs()
+                    .partial_cmp(&(b.rating - player.rating).abs())
+                    .unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(opponent_idx, _)| opponent_idx);
+            match opponent_idx
+            {
+                Some(opponent_idx) =>
+                    {
+                        let opponent = self.matchmaking_queue.remove(opponent_idx);
+                        let player = self.matchmaking_queue.remove(idx);
+                        println!("'{}' and '{}' are paired by the queue", player.user_name, opponent.user_name);
+                        let settings = InvitationSettings
+                            {
+                                variant: player.variant,
+                                time_control: player.time_control,
+                                initial_fen: None,
+                                is_rated: player.is_rated,
+                                color_preference: None,
+                            };
+                        // only the queued sessions join the game, other tabs of the players stay where they are
+                        self.start_game(settings, &player.user_name, vec![player.id], &opponent.user_name, opponent.id);
+                    },
+                None => idx += 1,
+            }
+        }
+    }
+
+
+    /// Players who did not come back within the grace period lose their games,
+    /// the game left by all players is lost by both of them.
+    fn check_disconnected_players(&mut self)
+    {
+        let is_expired = |disconnected_at: &Instant| disconnected_at.elapsed() >= RECONNECT_GRACE_PERIOD;
+        let mut left_players: Vec<(String, String)> = Vec::new();
+        let mut abandoned_rooms: Vec<String> = Vec::new();
+        for (room, game_room) in &self.games
+        {
+            // nobody is there to win the game while all players are away, it waits for the last of them
+            if game_room.players.keys().all(|user_name| game_room.disconnected_players.contains_key(user_name))
+            {
+                if game_room.disconnected_players.values().all(is_expired)
+                {
+                    abandoned_rooms.push(room.to_owned());
+                }
+                continue;
+            }
+            left_players.extend(game_room.disconnected_players
+                .iter()
+                .filter(|(_, disconnected_at)| is_expired(disconnected_at))
+                .map(|(user_name, _)| (room.to_owned(), user_name.to_owned())));
+        }
+
+        for (room, user_name) in left_players
+        {
+            println!("'{}' did not come back to '{}'", user_name, room);
+            self.leave_game(&room, Some(&user_name));
+            self.send_message(&room, "received_leave_game_message", &user_name, 0);
+        }
+        for room in abandoned_rooms
+        {
+            println!("Nobody came back to '{}'", room);
+            self.forfeit_game(&room);
+        }
+    }
+
+
+    /// None of the players came back to the game, so both of them lose it and the ratings stay the same.
+    fn forfeit_game(&mut self, room: &str)
+    {
+        let game_room = match self.end_game(room, None)
+        {
+            Some(game_room) => game_room,
+            None => return,
+        };
+        self.db.do_send(FinishGame
+            {
+                game_id: game_room.game_id.to_owned(),
+                finished_at: Utc::now().naive_utc(),
+                result: DOUBLE_FORFEIT_RESULT.to_owned(),
+                rated_players: None,
+                tournament_game_id: game_room.tournament_game_id,
+            });
+        for user_name in game_room.players.keys()
+        {
+            self.send_message(room, "received_leave_game_message", user_name, 0);
+        }
+    }
+
+
+    /// Finish games in which the side to move has run out of time
+    fn check_clocks(&mut self)
+    {
+        let rooms: Vec<String> = self.games
+            .iter()
+            .filter(|(_, game_room)| game_room.clock.as_ref().map_or(false, |clock| clock.is_time_over()))
+            .map(|(room, _)| room.to_owned())
+            .collect();
+
+        for room in rooms
+        {
+            if let Some(game_room) = self.games.get_mut(&room)
+            {
+                let color = game_room.clock.as_ref().unwrap().turn().to_owned();
+                let _ = game_room.game.lose_on_time(&color);
+            }
+            println!("Time is over in '{}'", room);
+            if let Some(outcome) = self.end_game(&room, None).and_then(|game_room| game_room.game.outcome)
+            {
+                self.send_message(&room, "game_over", &serde_json::to_string(&outcome).unwrap(), 0);
+            }
+        }
+    }
 }
 
 
@@ -209,6 +916,16 @@ impl Actor for ChatServer
     /// We are going to use simple Context, we just need ability to communicate
     /// with other actors.
     type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context)
+    {
+        ctx.run_interval(CLOCK_CHECK_INTERVAL, |act, _|
+            {
+                act.check_clocks();
+                act.check_disconnected_players();
+                act.match_queued_players();
+            });
+    }
 }
 
 
@@ -254,6 +971,7 @@ impl Handler<Disconnect> for ChatServer
     fn handle(&mut self, msg: Disconnect, _: &mut Context<Self>)
     {
         let mut rooms: Vec<String> = Vec::new();
+        self.matchmaking_queue.retain(|queued_player| queued_player.id != msg.id);
 
         // remove address
         // if self.sessions.remove(&msg.id).is_some()
@@ -270,6 +988,25 @@ impl Handler<Disconnect> for ChatServer
                             rooms.push(name.to_owned());
                         }
                     }
+                    // the game of the disconnected player waits for the player to come back
+                    for room in &rooms
+                    {
+                        if self.remove_spectator(room, msg.id)
+                        {
+                            continue;
+                        }
+                        if let Some(user_name) = &session_data.user_name
+                        {
+                            if !self.is_user_in_room(room, user_name)
+                            {
+                                self.hold_game(room, user_name);
+                            }
+                        }
+                    }
+                    if let Some(user_name) = &session_data.user_name
+                    {
+                        self.invitations.retain(|(from_user, to_user), _| from_user != user_name && to_user != user_name);
+                    }
                     session_data.user_name
                 }
                 else
@@ -324,6 +1061,12 @@ impl Handler<Join> for ChatServer
     fn handle(&mut self, msg: Join, _: &mut Context<Self>)
     {
         let Join { id, name } = msg;
+        // game rooms are entered only by the server, when the game starts or to watch it
+        if name.starts_with(GAME_ROOM_PREFIX)
+        {
+            return;
+        }
+        self.matchmaking_queue.retain(|queued_player| queued_player.id != id);
         let mut rooms = Vec::new();
 
         // remove session from all rooms
@@ -433,13 +1176,319 @@ impl Handler<Invitation> for ChatServer
         {
             if let Some(user_name) = &session.user_name
             {
-                self.process_invitation(&msg.room, &user_name, &msg.to_user, &msg.action);
+                match msg.action.as_str()
+                {
+                    "invitation" =>
+                        {
+                            let variant = msg.variant.unwrap_or_default();
+                            let time_control = msg.time_control;
+                            let initial_fen = msg.initial_fen;
+                            let is_rated = msg.is_rated;
+                            let color_preference = msg.color_preference;
+                            self.invitations.insert(
+                                (user_name.to_owned(), msg.to_user.to_owned()),
+                                InvitationSettings
+                                {
+                                    variant, time_control, initial_fen: initial_fen.clone(), is_rated,
+                                    color_preference: color_preference.clone(),
+                                });
+                            let data = serde_json::to_string(
+                                &InvitationData
+                                {
+                                    user_name: user_name.to_owned(), variant, time_control, initial_fen, is_rated,
+                                    color_preference,
+                                })
+                                .unwrap();
+                            self.process_invitation(&msg.room, &msg.to_user, &msg.action, &data);
+                        },
+                    "accept_invitation" =>
+                        {
+                            let settings = self.invitations.remove(&(msg.to_user.to_owned(), user_name.to_owned()));
+                            let inviting_ids = self.user_sessions(&msg.room, &msg.to_user);
+                            // both users may have left the room or started other games since the invitation
+                            let are_users_idle = !inviting_ids.is_empty() &&
+                                !self.is_playing(&msg.to_user) && !self.is_playing(user_name);
+                            match settings
+                            {
+                                Some(settings) if are_users_idle =>
+                                    {
+                                        // the inviting user learns about the acceptance before leaving the room
+                                        self.process_invitation(&msg.room, &msg.to_user, &msg.action, &user_name);
+                                        self.start_game(settings, &msg.to_user, inviting_ids, user_name, msg.id);
+                                    },
+                                _ =>
+                                    {
+                                        self.process_invitation(&msg.room, &msg.to_user, "decline_invitation", &user_name);
+                                        self.send_message_to_session(msg.id, "cancelled_invitation", &msg.to_user);
+                                    },
+                            }
+                        },
+                    _ =>
+                        {
+                            self.invitations.remove(&(msg.to_user.to_owned(), user_name.to_owned()));
+                            self.process_invitation(&msg.room, &msg.to_user, &msg.action, &user_name);
+                        },
+                }
             }
         }
     }
 }
 
 
+impl Handler<FindGame> for ChatServer
+{
+    type Result = ();
+
+    fn handle(&mut self, msg: FindGame, ctx: &mut Context<Self>)
+    {
+        let user_name = match self.sessions.get(&msg.id).and_then(|session| session.user_name.clone())
+        {
+            Some(user_name) => user_name,
+            None => return,
+        };
+        self.db
+            .send(ExtractRating { user_name: user_name.to_owned() })
+            .into_actor(self)
+            .then(move |res, act, _|
+                {
+                    // the session may be gone or busy with a game while the rating was extracted
+                    let is_idle = act.sessions.contains_key(&msg.id) && !act.is_playing(&user_name);
+                    match res
+                    {
+                        Ok(Some(rating)) if is_idle =>
+                            {
+                                // the user looks for one game at a time
+                                act.matchmaking_queue.retain(|queued_player| queued_player.user_name != user_name);
+                                act.matchmaking_queue.push(QueuedPlayer
+                                    {
+                                        id: msg.id,
+                                        user_name,
+                                        rating,
+                                        variant: msg.variant,
+                                        time_control: msg.time_control,
+                                        is_rated: msg.is_rated,
+                                        queued_at: Instant::now(),
+                                    });
+                                act.match_queued_players();
+                            },
+                        Ok(_) => (),
+                        Err(e) => println!("Error to extract player rating: {}", e),
+                    }
+                    fut::ready(())
+                })
+            .spawn(ctx);
+    }
+}
+
+
+impl Handler<CancelFindGame> for ChatServer
+{
+    type Result = ();
+
+    fn handle(&mut self, msg: CancelFindGame, _: &mut Context<Self>)
+    {
+        self.matchmaking_queue.retain(|queued_player| queued_player.id != msg.id);
+    }
+}
+
+
+impl Handler<StartTournamentRound> for ChatServer
+{
+    type Result = ();
+
+    fn handle(&mut self, msg: StartTournamentRound, _: &mut Context<Self>)
+    {
+        for (tournament_game_id, white_player, black_player) in msg.games
+        {
+            let mut player_sessions = Vec::new();
+            let mut absent_players = Vec::new();
+            for (user_name, opponent, piece_color) in
+                &[(&white_player, &black_player, PieceColor::White), (&black_player, &white_player, PieceColor::Black)]
+            {
+                // players busy with other games join this one when they are over
+                let is_playing = self.is_playing(user_name);
+                let game_ready = TournamentGameReady
+                    {
+                        tournament_id: msg.tournament_id,
+                        tournament_name: msg.tournament_name.to_owned(),
+                        round: msg.round,
+                        opponent: (*opponent).to_owned(),
+                        piece_color: piece_color.to_owned(),
+                    };
+                let data = serde_json::to_string(&game_ready).unwrap();
+                let mut is_online = false;
+                for (id, session) in &self.sessions
+                {
+                    if session.user_name.as_deref() != Some(user_name.as_str())
+                    {
+                        continue;
+                    }
+                    is_online = true;
+                    self.send_message_to_session(*id, "tournament_game_ready", &data);
+                    if !is_playing
+                    {
+                        player_sessions.push((*id, (*user_name).to_owned()));
+                    }
+                }
+                if !is_online
+                {
+                    absent_players.push((*user_name).to_owned());
+                }
+            }
+
+            let settings = InvitationSettings
+                {
+                    variant: msg.variant,
+                    time_control: msg.time_control,
+                    initial_fen: None,
+                    is_rated: true,
+                    color_preference: None,
+                };
+            let game_id = self.open_game_room(
+                settings, &white_player, &black_player, player_sessions, Some(tournament_game_id));
+            self.db.do_send(SetTournamentGameId { tournament_game_id, game_id: game_id.to_owned() });
+
+            // players who are not online have the grace period to come back and play the game
+            let room = format!("{}{}", GAME_ROOM_PREFIX, game_id);
+            for user_name in absent_players
+            {
+                self.hold_game(&room, &user_name);
+            }
+        }
+    }
+}
+
+
+/// Handler for AdjudicateGame message.
+///
+/// The result is already saved by the organizer, so the finished game does not replace it
+/// and the ratings of the players stay the same.
+impl Handler<AdjudicateGame> for ChatServer
+{
+    type Result = ();
+
+    fn handle(&mut self, msg: AdjudicateGame, _: &mut Context<Self>)
+    {
+        let room = match self.games
+            .iter()
+            .find(|(_, game_room)| game_room.tournament_game_id == Some(msg.tournament_game_id))
+            .map(|(room, _)| room.to_owned())
+        {
+            Some(room) => room,
+            None => return,
+        };
+        println!("Game in '{}' is adjudicated: {}", room, msg.result);
+        match GameOutcome::from_result(&msg.result)
+        {
+            Some(outcome) =>
+                {
+                    if let Some(game_room) = self.games.get_mut(&room)
+                    {
+                        game_room.is_rated = false;
+                        game_room.game.outcome = Some(outcome);
+                    }
+                    self.leave_game(&room, None);
+                },
+            None => self.forfeit_game(&room),
+        }
+    }
+}
+
+
+/// Handler for PositionRequest message.
+impl Handler<PositionRequest> for ChatServer
+{
+    type Result = ();
+
+    fn handle(&mut self, msg: PositionRequest, _: &mut Context<Self>)
+    {
+        if let Some(game_room) = self.games.get(&msg.room)
+        {
+            let data = serde_json::to_string(&game_room.position_snapshot()).unwrap();
+            self.send_message_to_session(msg.id, "position_snapshot", &data);
+        }
+    }
+}
+
+
+/// Handler for Reconnect message.
+///
+/// The session joins the game room of the unfinished game its user plays.
+impl Handler<Reconnect> for ChatServer
+{
+    type Result = MessageResult<Reconnect>;
+
+    fn handle(&mut self, msg: Reconnect, _: &mut Context<Self>) -> Self::Result
+    {
+        let user_name = match self.sessions.get(&msg.id).and_then(|session| session.user_name.clone())
+        {
+            Some(user_name) => user_name,
+            None => return MessageResult(None),
+        };
+        MessageResult(self.rejoin_game(msg.id, &user_name))
+    }
+}
+
+
+/// Handler for ListLiveGames message.
+impl Handler<ListLiveGames> for ChatServer
+{
+    type Result = MessageResult<ListLiveGames>;
+
+    fn handle(&mut self, _: ListLiveGames, _: &mut Context<Self>) -> Self::Result
+    {
+        MessageResult(self.games.iter().map(|(room, game_room)| game_room.live_game(room)).collect())
+    }
+}
+
+
+/// Handler for WatchGame message.
+///
+/// The session leaves all its rooms and joins the game room as a spectator.
+impl Handler<WatchGame> for ChatServer
+{
+    type Result = MessageResult<WatchGame>;
+
+    fn handle(&mut self, msg: WatchGame, _: &mut Context<Self>) -> Self::Result
+    {
+        let user_name = self.sessions.get(&msg.id).and_then(|session| session.user_name.clone());
+        match self.games.get(&msg.room)
+        {
+            Some(game_room) if user_name.as_ref().map_or(true, |user_name| !game_room.players.contains_key(user_name)) => (),
+            _ => return MessageResult(None),
+        }
+
+        self.move_session(msg.id, &msg.room, user_name.as_deref());
+
+        let live_game = self.games.get_mut(&msg.room)
+            .map(|game_room|
+                {
+                    game_room.spectators.insert(msg.id);
+                    game_room.live_game(&msg.room)
+                });
+        if let Some(live_game) = &live_game
+        {
+            self.send_message(&msg.room, "spectators_count", &live_game.spectators.to_string(), msg.id);
+        }
+        MessageResult(live_game)
+    }
+}
+
+
+/// Handler for RatedGamePlayer message.
+impl Handler<RatedGamePlayer> for ChatServer
+{
+    type Result = bool;
+
+    fn handle(&mut self, msg: RatedGamePlayer, _: &mut Context<Self>) -> Self::Result
+    {
+        self.games
+            .values()
+            .any(|game_room| game_room.is_rated && game_room.players.contains_key(&msg.user_name))
+    }
+}
+
+
 /// Handler for Game message.
 impl Handler<GameMessage> for ChatServer
 {
@@ -447,7 +1496,171 @@ impl Handler<GameMessage> for ChatServer
 
     fn handle(&mut self, msg: GameMessage, _: &mut Context<Self>)
     {
-        self.send_message(&msg.room, "received_checker_piece_move", msg.msg.as_str(),  msg.id);
+        // the move made after the time is over loses the game
+        self.check_clocks();
+
+        let user_name = match self.sessions.get(&msg.id).and_then(|session| session.user_name.clone())
+        {
+            Some(user_name) => user_name,
+            None => return,
+        };
+
+        let checked_move =
+            {
+                if let Some(game_room) = self.games.get_mut(&msg.room)
+                {
+                    match serde_json::from_str::<GameData>(&msg.msg)
+                    {
+                        Ok(game_data) =>
+                            {
+                                if game_room.players.get(&user_name) != Some(&game_data.opponent_piece_color)
+                                {
+                                    Err("You can move only your own pieces.".to_owned())
+                                }
+                                else
+                                {
+                                    let color = &game_data.opponent_piece_color;
+                                    let is_crowned = |game: &Game, position|
+                                        game.board.find_position(color, position)
+                                            .map_or(false, |idx| game.board.pieces(color)[idx].is_crowned);
+                                    let was_crowned = game_data.path.first()
+                                        .map_or(false, |position| is_crowned(&game_room.game, position));
+                                    game_room.game.apply_move(&game_data)
+                                        .map(|_|
+                                            {
+                                                game_room.ply += 1;
+                                                game_models::NewGameMove
+                                                {
+                                                    game_id: game_room.game_id.to_owned(),
+                                                    ply: game_room.ply,
+                                                    from_square: game_data.path[0].notation(),
+                                                    to_square: game_data.path[game_data.path.len() - 1].notation(),
+                                                    captured_squares: game_data.captured_pieces_positions
+                                                        .iter()
+                                                        .map(|position| position.notation())
+                                                        .collect(),
+                                                    promotion: !was_crowned &&
+                                                        is_crowned(&game_room.game, &game_data.path[game_data.path.len() - 1]),
+                                                    path: game_data.path.iter().map(|position| position.notation()).collect(),
+                                                }
+                                            })
+                                        .map_err(|e| e.to_string())
+                                }
+                            },
+                        Err(_) => Err("Incorrect move data.".to_owned()),
+                    }
+                }
+                else
+                {
+                    Err("Game not found.".to_owned())
+                }
+            };
+
+        match checked_move
+        {
+            Ok(new_move) =>
+                {
+                    self.db.do_send(SaveMove(new_move));
+                    self.send_message(&msg.room, "received_checker_piece_move", msg.msg.as_str(),  msg.id);
+                    let clock_data =
+                        {
+                            if let Some(clock) = self.games.get_mut(&msg.room).and_then(|game_room| game_room.clock.as_mut())
+                            {
+                                clock.switch();
+                                Some(clock.clock_data())
+                            }
+                            else { None }
+                        };
+                    if let Some(clock_data) = clock_data
+                    {
+                        self.send_message(&msg.room, "clock_update", &serde_json::to_string(&clock_data).unwrap(), 0);
+                    }
+                    if let Some(game_room) = self.games.get(&msg.room)
+                    {
+                        if game_room.game.outcome.is_some()
+                        {
+                            self.end_game(&msg.room, None);
+                        }
+                    }
+                },
+            Err(reason) =>
+                {
+                    println!("Rejected move from '{}': {}", user_name, reason);
+                    self.send_message_to_session(msg.id, "rejected_checker_piece_move", &reason);
+                }
+        }
+    }
+}
+
+
+/// Handler for GameDecision message.
+impl Handler<GameDecision> for ChatServer
+{
+    type Result = ();
+
+    fn handle(&mut self, msg: GameDecision, _: &mut Context<Self>)
+    {
+        let user_name = match self.sessions.get(&msg.id).and_then(|session| session.user_name.clone())
+        {
+            Some(user_name) => user_name,
+            None => return,
+        };
+
+        let mut taken_back_plies = 0;
+        let checked_decision =
+            {
+                if let Some(game_room) = self.games.get_mut(&msg.room)
+                {
+                    if let Some(color) = game_room.players.get(&user_name)
+                    {
+                        let game = &mut game_room.game;
+                        match msg.action.as_str()
+                        {
+                            "resign" => game.resign(color).map_err(|e| e.to_string()),
+                            "offer_draw" => game.offer_draw(color).map_err(|e| e.to_string()),
+                            "accept_draw" => game.accept_draw(color).map_err(|e| e.to_string()),
+                            "decline_draw" => game.decline_draw(color).map_err(|e| e.to_string()),
+                            "request_takeback" => game.request_takeback(color).map_err(|e| e.to_string()),
+                            "accept_takeback" => game.accept_takeback(color)
+                                .map(|plies| taken_back_plies = plies)
+                                .map_err(|e| e.to_string()),
+                            "decline_takeback" => game.decline_takeback(color).map_err(|e| e.to_string()),
+                            _ => Err("Unknown game action.".to_owned()),
+                        }
+                    }
+                    else
+                    {
+                        Err("You are not a player of this game.".to_owned())
+                    }
+                }
+                else
+                {
+                    Err("Game not found.".to_owned())
+                }
+            };
+
+        if let Err(reason) = checked_decision
+        {
+            println!("Rejected '{}' from '{}': {}", msg.action, user_name, reason);
+            self.send_message_to_session(msg.id, "rejected_game_decision", &reason);
+            return;
+        }
+
+        match msg.action.as_str()
+        {
+            "offer_draw" => self.send_message(&msg.room, "received_draw_offer", &user_name, msg.id),
+            "decline_draw" => self.send_message(&msg.room, "declined_draw_offer", &user_name, msg.id),
+            "request_takeback" => self.send_message(&msg.room, "received_takeback_request", &user_name, msg.id),
+            "decline_takeback" => self.send_message(&msg.room, "declined_takeback_request", &user_name, msg.id),
+            "accept_takeback" => self.take_back(&msg.room, taken_back_plies),
+            _ =>
+                {
+                    if let Some(outcome) = self.end_game(&msg.room, None).and_then(|game_room| game_room.game.outcome)
+                    {
+                        self.send_message(&msg.room, "game_over", &serde_json::to_string(&outcome).unwrap(), 0);
+                    }
+                },
+        }
     }
 }
 
@@ -461,6 +1674,11 @@ impl Handler<LeaveGameMessage> for ChatServer
     fn handle(&mut self, msg: LeaveGameMessage, _: &mut Context<Self>)
     {
         let LeaveGameMessage { id, name } = msg;
+        // game rooms are entered only by the server, when the game starts or to watch it
+        if name.starts_with(GAME_ROOM_PREFIX)
+        {
+            return;
+        }
         let mut rooms = Vec::new();
 
         // remove session from all rooms
@@ -484,6 +1702,16 @@ impl Handler<LeaveGameMessage> for ChatServer
                 }
             };
 
+        // the game is over as soon as one of the players left it, spectators leave it silently
+        let rooms: Vec<String> = rooms
+            .into_iter()
+            .filter(|room| !self.remove_spectator(room, id))
+            .collect();
+        for room in &rooms
+        {
+            self.leave_game(room, user_name.as_deref());
+        }
+
         match user_name
         {
             Some(user_name) =>
@@ -510,5 +1738,14 @@ impl Handler<LeaveGameMessage> for ChatServer
             .insert(id);
 
         self.send_message(&name, "connect", "Someone connected", id);
+
+        // the player goes on with the game which waited for the left one, such as the next tournament game
+        if let Some(user_name) = self.sessions.get(&id).and_then(|session| session.user_name.clone())
+        {
+            if let Some(joined_game) = self.rejoin_game(id, &user_name)
+            {
+                self.send_message_to_session(id, "game_started", &serde_json::to_string(&joined_game).unwrap());
+            }
+        }
     }
 }
@@ -0,0 +1,540 @@
+use actix::Addr;
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+use chrono::{NaiveDate, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::checkers_game::game::{game_database, game_models};
+use crate::checkers_game::chat::server::{ChatServer, RatedGamePlayer};
+use crate::{DbPool, decode_token};
+
+use checkers_rules::
+    {
+        Game, GameData, GameOutcome, PdnGame, PdnMove, PdnError, Variant, CheckerPosition, PieceColor, Board, EngineLevel,
+        square_number, analyze,
+    };
+
+
+const UNKNOWN_PLAYER: &str = "?";
+/// Number of opponents shown in the statistics of the user
+const FREQUENT_OPPONENTS_NUMBER: usize = 5;
+
+
+#[derive(Deserialize)]
+pub struct GameInfo
+{
+    pub game_id: String,
+}
+
+
+/// Stored moves are written by their first and last squares, captures by every square they land on,
+/// so the capture of the flying king is read back along the same path.
+/// A move through a cell which is not a dark cell of the board can not be written.
+fn to_pdn_game(game: &game_models::GameResponse, moves: &[game_models::GameMoveResponse])
+    -> Result<PdnGame, PdnError>
+{
+    let variant = Variant::from_name(&game.variant).unwrap_or_default();
+    let size = variant.board_size();
+    let square = |notation: &str| CheckerPosition::from_notation(notation)
+        .filter(|position|
+            (1..=size).contains(&position.column) && (1..=size).contains(&position.line) &&
+                (position.column + position.line) % 2 == 0)
+        .map(|position| square_number(&position, size));
+    let result = game.result.to_owned().unwrap_or_else(|| "*".to_owned());
+    let mut tags = vec!
+        [
+            ("Event".to_owned(), format!("Game {}", game.id)),
+            ("Date".to_owned(), game.started_at.format("%Y.%m.%d").to_string()),
+            ("White".to_owned(), game.white_player.to_owned()),
+            ("Black".to_owned(), game.black_player.to_owned()),
+            ("GameType".to_owned(), variant.game_type().to_string()),
+            ("Result".to_owned(), result.to_owned()),
+        ];
+    if let Some(fen) = &game.initial_fen
+    {
+        tags.push(("FEN".to_owned(), fen.to_owned()));
+    }
+    let moves = moves
+        .iter()
+        .map(|game_move|
+            {
+                let is_capture = !game_move.captured_squares.is_empty();
+                // moves saved before the path was stored have no path
+                let cells = if is_capture && game_move.path.len() >= 2 { game_move.path.to_owned() }
+                    else { vec![game_move.from_square.to_owned(), game_move.to_square.to_owned()] };
+                cells
+                    .iter()
+                    .map(|cell| square(cell))
+                    .collect::<Option<Vec<u8>>>()
+                    .map(|squares| PdnMove { squares, is_capture })
+                    .ok_or_else(|| PdnError::IncorrectMove(cells.join(if is_capture { "x" } else { "-" })))
+            })
+        .collect::<Result<Vec<PdnMove>, PdnError>>()?;
+    Ok(PdnGame { tags, moves, result })
+}
+
+
+fn is_crowned(game: &Game, color: &PieceColor, position: &CheckerPosition) -> bool
+{
+    game.board.find_position(color, position).map_or(false, |idx| game.board.pieces(color)[idx].is_crowned)
+}
+
+
+/// Game started from the stored position, stored moves are found among allowable moves
+/// by their path, or by their first and last cells when the path is not stored, and captured pieces.
+/// Replay stops at the first move which does not fit.
+fn replay_stored_game(stored_game: &game_models::GameResponse, moves: &[game_models::GameMoveResponse]) -> Option<Game>
+{
+    let variant = Variant::from_name(&stored_game.variant)?;
+    let mut game = match &stored_game.initial_fen
+    {
+        Some(fen) => Game::from_fen(variant, fen).ok()?,
+        None => Game::new(variant),
+    };
+    for stored_move in moves
+    {
+        let from_position = CheckerPosition::from_notation(&stored_move.from_square)?;
+        let to_position = CheckerPosition::from_notation(&stored_move.to_square);
+        let path = stored_move.path
+            .iter()
+            .map(|notation| CheckerPosition::from_notation(notation))
+            .collect::<Option<Vec<CheckerPosition>>>()?;
+        let captured_pieces_positions = stored_move.captured_squares
+            .iter()
+            .map(|notation| CheckerPosition::from_notation(notation))
+            .collect::<Option<Vec<CheckerPosition>>>()?;
+        let color = game.turn.to_owned();
+        let allowable_move = game.board.allowable_moves(&color, &from_position)?
+            .into_iter()
+            .find(|allowable_move|
+                {
+                    let is_same_path = if path.is_empty() { allowable_move.path.last() == to_position.as_ref() }
+                        else { allowable_move.path == path };
+                    is_same_path && allowable_move.captured_pieces_positions == captured_pieces_positions
+                })?;
+        let game_data = GameData
+            {
+                opponent_piece_color: color,
+                path: allowable_move.path,
+                captured_pieces_positions: allowable_move.captured_pieces_positions,
+            };
+        game.apply_move(&game_data).ok()?;
+    }
+    Some(game)
+}
+
+
+/// Board of the analyzed position, every piece should stand on its own dark cell.
+fn to_board(analysis_request: game_models::AnalysisRequest) -> Option<Board>
+{
+    let variant = analysis_request.variant;
+    let size = variant.board_size();
+    let mut positions: Vec<&CheckerPosition> = Vec::new();
+    for piece in analysis_request.checker_pieces.values().flatten()
+    {
+        let position = &piece.position;
+        if position.column < 1 || position.column > size || position.line < 1 || position.line > size ||
+            (position.column + position.line) % 2 != 0 || positions.contains(&position)
+        {
+            return None;
+        }
+        positions.push(position);
+    }
+    let mut checker_pieces = analysis_request.checker_pieces;
+    checker_pieces.entry(PieceColor::White).or_insert_with(Vec::new);
+    checker_pieces.entry(PieceColor::Black).or_insert_with(Vec::new);
+    Some(Board { checker_pieces, variant, size })
+}
+
+
+/// Moves of the imported game in the form they are stored for played games.
+fn to_new_moves(game_id: &str, mut game: Game, moves: &[GameData]) -> Vec<game_models::NewGameMove>
+{
+    let mut new_moves = Vec::new();
+    for (idx, game_data) in moves.iter().enumerate()
+    {
+        let color = &game_data.opponent_piece_color;
+        let last_position = &game_data.path[game_data.path.len() - 1];
+        let was_crowned = is_crowned(&game, color, &game_data.path[0]);
+        // moves are already checked by the replay of the imported game
+        if game.apply_move(game_data).is_err()
+        {
+            break;
+        }
+        new_moves.push(game_models::NewGameMove
+            {
+                game_id: game_id.to_owned(),
+                ply: idx as i32 + 1,
+                from_square: game_data.path[0].notation(),
+                to_square: last_position.notation(),
+                captured_squares: game_data.captured_pieces_positions
+                    .iter()
+                    .map(|position| position.notation())
+                    .collect(),
+                promotion: !was_crowned && is_crowned(&game, color, last_position),
+                path: game_data.path.iter().map(|position| position.notation()).collect(),
+            });
+    }
+    new_moves
+}
+
+
+/// Statistics of the user by the finished games, games with the same player on both sides are skipped.
+fn user_stats(user_name: String, finished_games: &[(String, String, Option<String>)]) -> game_models::UserStats
+{
+    let mut total = game_models::ResultCounts::default();
+    let mut as_white = game_models::ResultCounts::default();
+    let mut as_black = game_models::ResultCounts::default();
+    let mut opponents: HashMap<&str, game_models::ResultCounts> = HashMap::new();
+    let mut scores = Vec::new();
+    for (white_player, black_player, result) in finished_games
+    {
+        let outcome = match result.as_deref().and_then(GameOutcome::from_result)
+        {
+            Some(outcome) if white_player != black_player => outcome,
+            _ => continue,
+        };
+        let (color, opponent) =
+            if *white_player == user_name { (PieceColor::White, black_player) } else { (PieceColor::Black, white_player) };
+        let score = outcome.score(&color);
+        total.add(score);
+        match color
+        {
+            PieceColor::White => as_white.add(score),
+            PieceColor::Black => as_black.add(score),
+        }
+        if opponent != UNKNOWN_PLAYER
+        {
+            opponents.entry(opponent).or_default().add(score);
+        }
+        scores.push(score);
+    }
+
+    let mut frequent_opponents: Vec<game_models::OpponentStats> = opponents
+        .into_iter()
+        .map(|(opponent, results)| game_models::OpponentStats { user_name: opponent.to_owned(), results })
+        .collect();
+    frequent_opponents.sort_by(|a, b| b.results.games.cmp(&a.results.games).then_with(|| a.user_name.cmp(&b.user_name)));
+    frequent_opponents.truncate(FREQUENT_OPPONENTS_NUMBER);
+
+    let current_streak = scores.last().map(|last_score|
+        {
+            let length = scores.iter().rev().take_while(|score| score == &last_score).count() as u32;
+            let result = if *last_score > 0.5 { "win" } else if *last_score < 0.5 { "loss" } else { "draw" };
+            game_models::Streak { result: result.to_owned(), length }
+        });
+
+    game_models::UserStats { user_name, total, as_white, as_black, frequent_opponents, current_streak }
+}
+
+
+pub async fn show_user_stats(pool: web::Data<DbPool>, info: web::Path<game_models::UserInfo>) -> Result<HttpResponse, Error>
+{
+    let user_name = info.user_name.clone();
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let finished_games = web::block(move || game_database::extract_finished_games(user_name, &conn))
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    Ok(HttpResponse::Ok().json(user_stats(info.user_name.clone(), &finished_games)))
+}
+
+
+pub async fn export_pdn(
+    pool: web::Data<DbPool>, info: web::Path<GameInfo>,
+    _request: HttpRequest) -> Result<HttpResponse, Error>
+{
+    let uid = info.game_id.clone();
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let stored_game = web::block(move || game_database::extract_game(uid, &conn))
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    match stored_game
+    {
+        Some((game, moves)) =>
+            match to_pdn_game(&game, &moves)
+            {
+                Ok(pdn_game) =>
+                    Ok(HttpResponse::Ok()
+                        .content_type("application/x-chess-pdn")
+                        .header("Content-Disposition", format!("attachment; filename=\"{}.pdn\"", game.id))
+                        .body(pdn_game.to_string())),
+                Err(e) =>
+                    {
+                        eprintln!("{}", e);
+                        Ok(HttpResponse::InternalServerError().body(e.to_string()))
+                    },
+            },
+        None => Ok(HttpResponse::NotFound().body("Game not found.")),
+    }
+}
+
+
+pub async fn import_pdn(pool: web::Data<DbPool>, pdn: String) -> Result<HttpResponse, Error>
+{
+    let pdn_game = match PdnGame::parse(&pdn)
+    {
+        Ok(pdn_game) => pdn_game,
+        Err(e) => return Ok(HttpResponse::BadRequest().body(e.to_string())),
+    };
+    let (variant, moves) = match pdn_game.variant().and_then(|variant| pdn_game.replay().map(|(_, moves)| (variant, moves)))
+    {
+        Ok(replayed_game) => replayed_game,
+        Err(e) => return Ok(HttpResponse::BadRequest().body(e.to_string())),
+    };
+    let initial_fen = pdn_game.tag("FEN").map(|fen| fen.to_owned());
+    // the position is already checked by the replay
+    let initial_game = match &initial_fen
+    {
+        Some(fen) => Game::from_fen(variant, fen).unwrap_or_else(|_| Game::new(variant)),
+        None => Game::new(variant),
+    };
+
+    let game_id = Uuid::new_v4().to_string();
+    let started_at = pdn_game.tag("Date")
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y.%m.%d").ok())
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .unwrap_or_else(|| Utc::now().naive_utc());
+    let new_game = game_models::NewGame
+        {
+            id: game_id.to_owned(),
+            white_player: pdn_game.tag("White").unwrap_or(UNKNOWN_PLAYER).to_owned(),
+            black_player: pdn_game.tag("Black").unwrap_or(UNKNOWN_PLAYER).to_owned(),
+            variant: variant.as_str().to_owned(),
+            started_at,
+            initial_fen,
+            is_rated: false,
+            time_control: None,
+            is_played_on_server: false,
+        };
+    let new_moves = to_new_moves(&game_id, initial_game, &moves);
+    let result = pdn_game.result.to_owned();
+
+    let uid = game_id.to_owned();
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    web::block(move ||
+        {
+            game_database::insert_new_game(new_game, &conn);
+            for new_move in new_moves
+            {
+                game_database::insert_new_move(new_move, &conn);
+            }
+            // the finish time of the imported game is unknown, the moment of the import is saved instead
+            if result != "*"
+            {
+                game_database::finish_game(uid, Utc::now().naive_utc(), result, &conn);
+            }
+            Ok::<(), diesel::result::Error>(())
+        })
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    Ok(HttpResponse::Ok().json(game_models::ImportedGame { game_id }))
+}
+
+
+pub async fn load_position(
+    pool: web::Data<DbPool>, info: web::Path<GameInfo>,
+    _request: HttpRequest) -> Result<HttpResponse, Error>
+{
+    let uid = info.game_id.clone();
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let stored_game = web::block(move || game_database::extract_game(uid, &conn))
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    match stored_game.and_then(|(game, moves)| replay_stored_game(&game, &moves).map(|replayed_game| (game, replayed_game)))
+    {
+        Some((game, replayed_game)) =>
+            Ok(HttpResponse::Ok().json(game_models::PositionData
+                {
+                    variant: game.variant,
+                    fen: replayed_game.to_fen(),
+                })),
+        None => Ok(HttpResponse::NotFound().body("Game not found.")),
+    }
+}
+
+
+/// Saves the position as a new game without moves, which can be continued or replayed later.
+pub async fn save_position(
+    pool: web::Data<DbPool>, position_data: web::Json<game_models::PositionData>,
+    request: HttpRequest) -> Result<HttpResponse, Error>
+{
+    let variant = match Variant::from_name(&position_data.variant)
+    {
+        Some(variant) => variant,
+        None => return Ok(HttpResponse::BadRequest().body("Unknown variant.")),
+    };
+    let fen = match Game::from_fen(variant, &position_data.fen)
+    {
+        Ok(game) => game.to_fen(),
+        Err(e) => return Ok(HttpResponse::BadRequest().body(e.to_string())),
+    };
+
+    let player = match request.headers().get("authorization")
+    {
+        Some(received_token) => match decode_token(received_token.to_str().unwrap_or("")).await
+            {
+                Ok(decoded_user) => decoded_user.claims.user_name,
+                Err(_) => UNKNOWN_PLAYER.to_owned(),
+            },
+        None => UNKNOWN_PLAYER.to_owned(),
+    };
+
+    let game_id = Uuid::new_v4().to_string();
+    let new_game = game_models::NewGame
+        {
+            id: game_id.to_owned(),
+            white_player: player.to_owned(),
+            black_player: player,
+            variant: variant.as_str().to_owned(),
+            started_at: Utc::now().naive_utc(),
+            initial_fen: Some(fen),
+            is_rated: false,
+            time_control: None,
+            is_played_on_server: false,
+        };
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    web::block(move ||
+        {
+            game_database::insert_new_game(new_game, &conn);
+            Ok::<(), diesel::result::Error>(())
+        })
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    Ok(HttpResponse::Ok().json(game_models::ImportedGame { game_id }))
+}
+
+
+/// Best move, principal variation and evaluation of the position,
+/// players of unfinished rated games get no hints.
+pub async fn analyze_position(
+    server: web::Data<Addr<ChatServer>>, analysis_request: web::Json<game_models::AnalysisRequest>,
+    request: HttpRequest) -> Result<HttpResponse, Error>
+{
+    let user_name = match request.headers().get("authorization")
+    {
+        Some(received_token) => match decode_token(received_token.to_str().unwrap_or("")).await
+            {
+                Ok(decoded_user) => decoded_user.claims.user_name,
+                Err(e) => return Ok(HttpResponse::Unauthorized().body(e.to_string())),
+            },
+        None => return Ok(HttpResponse::Unauthorized().body("Token is absent.")),
+    };
+    let is_rated_game_player = server
+        .send(RatedGamePlayer { user_name })
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    if is_rated_game_player
+    {
+        return Ok(HttpResponse::Forbidden().body("Hints are turned off in rated games."));
+    }
+
+    let analysis_request = analysis_request.into_inner();
+    let turn = analysis_request.turn.to_owned();
+    let level = analysis_request.level.unwrap_or(EngineLevel::Hard);
+    let board = match to_board(analysis_request)
+    {
+        Some(board) => board,
+        None => return Ok(HttpResponse::BadRequest().body("Incorrect position.")),
+    };
+    let analysis = web::block(move || Ok::<_, ()>(analyze(&board, &turn, level)))
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    Ok(HttpResponse::Ok().json(analysis))
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    fn stored_game(initial_fen: Option<&str>) -> game_models::GameResponse
+    {
+        game_models::GameResponse
+        {
+            id: "game".to_owned(),
+            white_player: "white".to_owned(),
+            black_player: "black".to_owned(),
+            variant: Variant::Russian.as_str().to_owned(),
+            started_at: Utc::now().naive_utc(),
+            finished_at: None,
+            result: None,
+            initial_fen: initial_fen.map(str::to_owned),
+            is_rated: false,
+            time_control: None,
+            is_played_on_server: true,
+        }
+    }
+
+
+    fn stored_move(path: &[&str], captured_squares: &[&str]) -> game_models::GameMoveResponse
+    {
+        game_models::GameMoveResponse
+        {
+            id: 1,
+            game_id: "game".to_owned(),
+            ply: 1,
+            from_square: path[0].to_owned(),
+            to_square: path[path.len() - 1].to_owned(),
+            captured_squares: captured_squares.iter().map(|cell| (*cell).to_owned()).collect(),
+            promotion: false,
+            path: path.iter().map(|cell| (*cell).to_owned()).collect(),
+        }
+    }
+
+
+    #[test]
+    fn flying_king_capture_is_exported_along_its_path()
+    {
+        // the king on b8 captures c7 and f4 landing either on d6 or on e5
+        let game = stored_game(Some("W:WK1:B6,19"));
+        let moves = vec![stored_move(&["b8", "e5", "g3"], &["c7", "f4"])];
+        let pdn_game = to_pdn_game(&game, &moves).unwrap();
+        assert_eq!(pdn_game.moves[0].to_string(), "1x15x24");
+
+        let (_, replayed_moves) = PdnGame::parse(&pdn_game.to_string()).unwrap().replay().unwrap();
+        let path: Vec<String> = replayed_moves[0].path.iter().map(|position| position.notation()).collect();
+        assert_eq!(path, vec!["b8", "e5", "g3"]);
+        assert!(replay_stored_game(&game, &moves).is_some());
+    }
+
+
+    #[test]
+    fn move_without_path_is_exported_by_first_and_last_squares()
+    {
+        let mut old_move = stored_move(&["c3", "d4"], &[]);
+        old_move.path = Vec::new();
+        let pdn_game = to_pdn_game(&stored_game(None), &[old_move]).unwrap();
+        assert_eq!(pdn_game.moves[0].to_string(), "22-18");
+    }
+}
@@ -0,0 +1,93 @@
+use diesel::prelude::*;
+use chrono::NaiveDateTime;
+
+use crate::checkers_game::game::game_models;
+
+
+pub fn insert_new_game(new_game: game_models::NewGame, conn: &PgConnection)
+{
+    use crate::schema::games::dsl::*;
+
+    match diesel::insert_into(games).values(&new_game).execute(conn)
+    {
+        Ok(_) => (),
+        Err(_) => println!("Error to save game in database!!!")
+    }
+}
+
+
+pub fn insert_new_move(new_move: game_models::NewGameMove, conn: &PgConnection)
+{
+    use crate::schema::game_moves::dsl::*;
+
+    match diesel::insert_into(game_moves).values(&new_move).execute(conn)
+    {
+        Ok(_) => (),
+        Err(_) => println!("Error to save move in database!!!")
+    }
+}
+
+
+/// Moves taken back by the players are removed.
+pub fn delete_moves_after(uid: String, last_ply: i32, conn: &PgConnection)
+{
+    use crate::schema::game_moves::dsl::*;
+
+    match diesel::delete(game_moves.filter(game_id.eq(uid)).filter(ply.gt(last_ply))).execute(conn)
+    {
+        Ok(_) => (),
+        Err(_) => println!("Error to delete moves from database!!!")
+    }
+}
+
+
+pub fn finish_game(uid: String, finish_time: NaiveDateTime, game_result: String, conn: &PgConnection)
+{
+    use crate::schema::games::dsl::*;
+
+    match diesel::update(games.find(uid))
+        .set((finished_at.eq(finish_time), result.eq(game_result)))
+        .execute(conn)
+    {
+        Ok(_) => (),
+        Err(_) => println!("Error to save game result in database!!!")
+    }
+}
+
+
+pub fn extract_game(uid: String, conn: &PgConnection)
+    -> Result<Option<(game_models::GameResponse, Vec<game_models::GameMoveResponse>)>, diesel::result::Error>
+{
+    use crate::schema::{games, game_moves};
+
+    let game = games::table.find(uid).first::<game_models::GameResponse>(conn).optional()?;
+    match game
+    {
+        Some(game) =>
+            {
+                let moves = game_moves::table
+                    .filter(game_moves::game_id.eq(&game.id))
+                    .order_by(game_moves::ply.asc())
+                    .load::<game_models::GameMoveResponse>(conn)?;
+                Ok(Some((game, moves)))
+            },
+        None => Ok(None),
+    }
+}
+
+
+/// White player, black player and result of the finished games of the user from the earliest to the latest one,
+/// only games played on the server are counted.
+pub fn extract_finished_games(name: String, conn: &PgConnection)
+    -> Result<Vec<(String, String, Option<String>)>, diesel::result::Error>
+{
+    use crate::schema::games::dsl::*;
+
+    games
+        .filter(white_player.eq(&name).or(black_player.eq(&name)))
+        .filter(is_played_on_server.eq(true))
+        .filter(result.is_not_null())
+        .order_by(finished_at.asc())
+        .select((white_player, black_player, result))
+        .load::<(String, String, Option<String>)>(conn)
+}
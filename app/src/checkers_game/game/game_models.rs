@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+use std::collections::HashMap;
+
+use checkers_rules::{PieceColor, CheckerPiece, Variant, EngineLevel};
+
+use crate::schema::{games, game_moves};
+
+
+#[derive(Insertable)]
+#[table_name="games"]
+pub struct NewGame
+{
+    pub id: String,
+    pub white_player: String,
+    pub black_player: String,
+    pub variant: String,
+    pub started_at: NaiveDateTime,
+    /// Position the game was started from, the initial position of the variant when absent
+    pub initial_fen: Option<String>,
+    /// Hints are turned off in rated games
+    pub is_rated: bool,
+    /// Key of the time control, games without time limits have none
+    pub time_control: Option<String>,
+    /// Imported games and saved positions do not count in statistics of the players
+    pub is_played_on_server: bool,
+}
+
+
+#[derive(Insertable)]
+#[table_name="game_moves"]
+pub struct NewGameMove
+{
+    pub game_id: String,
+    pub ply: i32,
+    pub from_square: String,
+    pub to_square: String,
+    pub captured_squares: Vec<String>,
+    pub promotion: bool,
+    /// Cells of the move from the first to the last one, a capture lands on every cell after the first one
+    pub path: Vec<String>,
+}
+
+
+#[derive(Serialize, Queryable)]
+pub struct GameResponse
+{
+    pub id: String,
+    pub white_player: String,
+    pub black_player: String,
+    pub variant: String,
+    pub started_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+    pub result: Option<String>,
+    pub initial_fen: Option<String>,
+    pub is_rated: bool,
+    pub time_control: Option<String>,
+    pub is_played_on_server: bool,
+}
+
+
+#[derive(Serialize, Queryable)]
+pub struct GameMoveResponse
+{
+    pub id: i32,
+    pub game_id: String,
+    pub ply: i32,
+    pub from_square: String,
+    pub to_square: String,
+    pub captured_squares: Vec<String>,
+    pub promotion: bool,
+    pub path: Vec<String>,
+}
+
+
+#[derive(Serialize)]
+pub struct ImportedGame
+{
+    pub game_id: String,
+}
+
+
+/// Position of the game in the FEN notation.
+#[derive(Serialize, Deserialize)]
+pub struct PositionData
+{
+    pub variant: String,
+    pub fen: String,
+}
+
+
+/// Position to be analyzed in the form it is kept by the board of the client.
+#[derive(Deserialize)]
+pub struct AnalysisRequest
+{
+    pub variant: Variant,
+    pub checker_pieces: HashMap<PieceColor, Vec<CheckerPiece>>,
+    pub turn: PieceColor,
+    /// The deepest search is used when absent
+    #[serde(default)]
+    pub level: Option<EngineLevel>,
+}
+
+
+#[derive(Deserialize)]
+pub struct UserInfo
+{
+    pub user_name: String,
+}
+
+
+#[derive(Serialize, Default, Clone, Copy)]
+pub struct ResultCounts
+{
+    pub games: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+
+impl ResultCounts
+{
+    /// Adds the game with the points of the player, 1 for a win, 0.5 for a draw and 0 for a loss.
+    pub fn add(&mut self, score: f64)
+    {
+        self.games += 1;
+        if score > 0.5
+        {
+            self.wins += 1;
+        }
+        else if score < 0.5
+        {
+            self.losses += 1;
+        }
+        else
+        {
+            self.draws += 1;
+        }
+    }
+}
+
+
+#[derive(Serialize)]
+pub struct OpponentStats
+{
+    pub user_name: String,
+    /// Results of the user against the opponent
+    pub results: ResultCounts,
+}
+
+
+/// Games in a row with the same result, it is one of `win`, `loss` or `draw`.
+#[derive(Serialize)]
+pub struct Streak
+{
+    pub result: String,
+    pub length: u32,
+}
+
+
+/// Results of all finished games of the user.
+#[derive(Serialize)]
+pub struct UserStats
+{
+    pub user_name: String,
+    pub total: ResultCounts,
+    pub as_white: ResultCounts,
+    pub as_black: ResultCounts,
+    /// Opponents the user has played most often, the most frequent first
+    pub frequent_opponents: Vec<OpponentStats>,
+    /// Streak of the latest games, there is none before the first finished game
+    pub current_streak: Option<Streak>,
+}
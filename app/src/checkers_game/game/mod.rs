@@ -0,0 +1,3 @@
+pub mod game_models;
+pub mod game_database;
+pub mod game;
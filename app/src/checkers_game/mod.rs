@@ -1,4 +1,8 @@
 pub mod chat;
+pub mod game;
+pub mod puzzle;
+pub mod rating;
+pub mod tournament;
 
 // pub use chat::chat_route;
 // pub use chat::ChatServer;
@@ -0,0 +1,3 @@
+pub mod puzzle_models;
+pub mod puzzle_database;
+pub mod puzzle;
@@ -0,0 +1,249 @@
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+
+use crate::checkers_game::puzzle::{puzzle_database, puzzle_models};
+use crate::{DbPool, decode_token};
+
+use checkers_rules::{GameData, PdnGame, PdnMove, Variant};
+
+
+/// Maximal number of rating points won or lost in one attempt
+const RATING_K_FACTOR: f64 = 32.0;
+
+
+/// Moves of the player checked against the solution line.
+enum LineCheck
+{
+    /// The move is correct and the opponent replies
+    Continued(GameData),
+    /// The whole line is played, the last reply of the opponent finishes it when there is one
+    Solved(Option<GameData>),
+    Failed,
+}
+
+
+/// Points won by the player by the Elo formula, the puzzle loses the same number of points.
+fn rating_change(player_rating: i32, puzzle_rating: i32, is_solved: bool) -> i32
+{
+    let expected_score = 1.0 / (1.0 + 10f64.powf((puzzle_rating - player_rating) as f64 / 400.0));
+    let score = if is_solved { 1.0 } else { 0.0 };
+    (RATING_K_FACTOR * (score - expected_score)).round() as i32
+}
+
+
+/// Moves of the solution played by the rules from the start position of the puzzle.
+fn solution_moves(puzzle: &puzzle_models::Puzzle) -> Option<(Variant, Vec<GameData>)>
+{
+    let variant = Variant::from_name(&puzzle.variant)?;
+    let moves = puzzle.solution
+        .iter()
+        .map(|notation| PdnMove::parse(notation))
+        .collect::<Result<Vec<PdnMove>, _>>()
+        .ok()?;
+    let pdn_game = PdnGame
+        {
+            tags: vec!
+                [
+                    ("GameType".to_owned(), variant.game_type().to_string()),
+                    ("FEN".to_owned(), puzzle.fen.to_owned()),
+                ],
+            moves,
+            result: "*".to_owned(),
+        };
+    pdn_game.replay().ok().map(|(_, moves)| (variant, moves))
+}
+
+
+async fn extract_user_name(request: &HttpRequest) -> Option<String>
+{
+    let received_token = request.headers().get("authorization")?.to_str().ok()?;
+    decode_token(received_token).await.ok().map(|decoded_user| decoded_user.claims.user_name)
+}
+
+
+pub async fn next_puzzle(pool: web::Data<DbPool>, request: HttpRequest) -> Result<HttpResponse, Error>
+{
+    let user_name = match extract_user_name(&request).await
+    {
+        Some(user_name) => user_name,
+        None => return Ok(HttpResponse::Unauthorized().body("Sign in to solve puzzles.")),
+    };
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let next_puzzle = web::block(move || puzzle_database::extract_next_puzzle(&user_name, &conn))
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    if let Some((puzzle, player_rating)) = next_puzzle
+    {
+        if let Some((variant, _)) = solution_moves(&puzzle)
+        {
+            return Ok(HttpResponse::Ok().json(puzzle_models::PuzzleResponse
+                {
+                    id: puzzle.id,
+                    variant,
+                    fen: puzzle.fen,
+                    rating: puzzle.rating,
+                    player_rating,
+                }));
+        }
+        eprintln!("Solution of the puzzle {} does not fit the rules", puzzle.id);
+    }
+    Ok(HttpResponse::NotFound().body("There are no puzzles yet."))
+}
+
+
+/// The player moves first, so moves of the player are on even places of the solution line
+/// and replies of the opponent are on odd ones.
+fn check_line(solution: &[GameData], moves: &[GameData]) -> LineCheck
+{
+    let player_moves = solution.iter().step_by(2);
+    if moves.is_empty() || moves.len() > player_moves.len() || moves.iter().zip(player_moves).any(|(played, expected)| played != expected)
+    {
+        return LineCheck::Failed;
+    }
+    let reply = solution.get(moves.len() * 2 - 1).cloned();
+    if moves.len() * 2 >= solution.len()
+    {
+        return LineCheck::Solved(reply);
+    }
+    match reply
+    {
+        Some(reply) => LineCheck::Continued(reply),
+        None => LineCheck::Failed,
+    }
+}
+
+
+/// The last move of the player is checked against the solution line, the reply of the opponent is sent back
+/// while the line goes on. The attempt is over at the first wrong move or when the whole line is played,
+/// then the ratings of the player and the puzzle are changed.
+pub async fn play_move(
+    pool: web::Data<DbPool>, attempt: web::Json<puzzle_models::PuzzleAttempt>,
+    request: HttpRequest) -> Result<HttpResponse, Error>
+{
+    let user_name = match extract_user_name(&request).await
+    {
+        Some(user_name) => user_name,
+        None => return Ok(HttpResponse::Unauthorized().body("Sign in to solve puzzles.")),
+    };
+    let attempt = attempt.into_inner();
+    if attempt.moves.is_empty()
+    {
+        return Ok(HttpResponse::BadRequest().body("No moves are played."));
+    }
+    let puzzle_id = attempt.puzzle_id;
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let puzzle = web::block(move || puzzle_database::extract_puzzle(puzzle_id, &conn))
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    let (puzzle, solution) = match puzzle.and_then(|puzzle| solution_moves(&puzzle).map(|(_, solution)| (puzzle, solution)))
+    {
+        Some(puzzle_with_solution) => puzzle_with_solution,
+        None => return Ok(HttpResponse::NotFound().body("Puzzle not found.")),
+    };
+
+    let (is_solved, reply) = match check_line(&solution, &attempt.moves)
+    {
+        LineCheck::Continued(reply) =>
+            return Ok(HttpResponse::Ok().json(puzzle_models::PuzzleMoveResult { reply: Some(reply), result: None })),
+        LineCheck::Solved(reply) => (true, reply),
+        LineCheck::Failed => (false, None),
+    };
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let result = web::block(move ||
+        {
+            let player_rating = puzzle_database::extract_player_rating(&user_name, &conn)?;
+            let change = rating_change(player_rating, puzzle.rating, is_solved);
+            let new_attempt = puzzle_models::NewPuzzleAttempt { puzzle_id, user_name, is_solved };
+            puzzle_database::save_puzzle_attempt(new_attempt, player_rating + change, puzzle.rating - change, &conn)?;
+            Ok::<_, diesel::result::Error>(puzzle_models::PuzzleAttemptResult
+                {
+                    is_solved,
+                    player_rating: player_rating + change,
+                    rating_change: change,
+                })
+        })
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    Ok(HttpResponse::Ok().json(puzzle_models::PuzzleMoveResult { reply, result: Some(result) }))
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use checkers_rules::{CheckerPosition, PieceColor};
+
+
+    fn game_data(color: PieceColor, cells: &[&str]) -> GameData
+    {
+        GameData
+        {
+            opponent_piece_color: color,
+            path: cells.iter().map(|cell| CheckerPosition::from_notation(cell).unwrap()).collect(),
+            captured_pieces_positions: Vec::new(),
+        }
+    }
+
+
+    fn solution() -> Vec<GameData>
+    {
+        vec!
+        [
+            game_data(PieceColor::White, &["c3", "d4"]),
+            game_data(PieceColor::Black, &["f6", "e5"]),
+            game_data(PieceColor::White, &["d4", "f6"]),
+        ]
+    }
+
+
+    #[test]
+    fn correct_move_is_answered_by_reply()
+    {
+        match check_line(&solution(), &solution()[..1])
+        {
+            LineCheck::Continued(reply) => assert_eq!(reply, solution()[1]),
+            _ => panic!("the line goes on"),
+        }
+    }
+
+
+    #[test]
+    fn whole_line_solves_puzzle()
+    {
+        let moves = [solution()[0].clone(), solution()[2].clone()];
+        assert!(matches!(check_line(&solution(), &moves), LineCheck::Solved(None)));
+        assert!(matches!(check_line(&solution()[..2], &moves[..1]), LineCheck::Solved(Some(_))));
+    }
+
+
+    #[test]
+    fn wrong_or_extra_moves_fail_attempt()
+    {
+        let wrong_move = game_data(PieceColor::White, &["c3", "b4"]);
+        assert!(matches!(check_line(&solution(), &[wrong_move]), LineCheck::Failed));
+        // the reply of the opponent can not be sent as the move of the player
+        assert!(matches!(check_line(&solution(), &solution()[..2]), LineCheck::Failed));
+        assert!(matches!(check_line(&solution(), &[]), LineCheck::Failed));
+    }
+
+
+    #[test]
+    fn rating_change_depends_on_expected_result()
+    {
+        assert_eq!(rating_change(1500, 1500, true), 16);
+        assert_eq!(rating_change(1500, 1500, false), -16);
+        assert!(rating_change(1200, 1800, true) > rating_change(1800, 1200, true));
+    }
+}
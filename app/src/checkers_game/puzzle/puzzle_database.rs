@@ -0,0 +1,72 @@
+use diesel::prelude::*;
+
+use crate::checkers_game::puzzle::puzzle_models;
+
+
+/// Rating of the player before the first attempt
+pub const INITIAL_PUZZLE_RATING: i32 = 1500;
+
+
+pub fn extract_player_rating(name: &str, conn: &PgConnection) -> Result<i32, diesel::result::Error>
+{
+    use crate::schema::puzzle_ratings::dsl::*;
+
+    let player_rating = puzzle_ratings.find(name).select(rating).first::<i32>(conn).optional()?;
+    Ok(player_rating.unwrap_or(INITIAL_PUZZLE_RATING))
+}
+
+
+pub fn extract_puzzle(uid: i32, conn: &PgConnection) -> Result<Option<puzzle_models::Puzzle>, diesel::result::Error>
+{
+    use crate::schema::puzzles::dsl::*;
+
+    puzzles.find(uid).first::<puzzle_models::Puzzle>(conn).optional()
+}
+
+
+/// Unsolved puzzle with the rating closest to the rating of the player together with the player's rating,
+/// solved puzzles are offered again when there are no other ones.
+pub fn extract_next_puzzle(name: &str, conn: &PgConnection)
+    -> Result<Option<(puzzle_models::Puzzle, i32)>, diesel::result::Error>
+{
+    use crate::schema::{puzzles, puzzle_attempts};
+
+    let player_rating = extract_player_rating(name, conn)?;
+    let solved_puzzles = puzzle_attempts::table
+        .filter(puzzle_attempts::user_name.eq(name))
+        .filter(puzzle_attempts::is_solved.eq(true))
+        .select(puzzle_attempts::puzzle_id)
+        .load::<i32>(conn)?;
+    let (unsolved, solved): (Vec<_>, Vec<_>) = puzzles::table
+        .load::<puzzle_models::Puzzle>(conn)?
+        .into_iter()
+        .partition(|puzzle| !solved_puzzles.contains(&puzzle.id));
+    let closest = |candidates: Vec<puzzle_models::Puzzle>| candidates
+        .into_iter()
+        .min_by_key(|puzzle| (puzzle.rating - player_rating).abs());
+    Ok(closest(unsolved).or_else(|| closest(solved)).map(|puzzle| (puzzle, player_rating)))
+}
+
+
+/// The attempt and the new ratings of the player and the puzzle are saved together.
+pub fn save_puzzle_attempt(
+    new_attempt: puzzle_models::NewPuzzleAttempt, player_rating: i32, puzzle_rating: i32, conn: &PgConnection)
+    -> Result<(), diesel::result::Error>
+{
+    use crate::schema::{puzzles, puzzle_attempts, puzzle_ratings};
+
+    conn.transaction(||
+        {
+            diesel::insert_into(puzzle_attempts::table).values(&new_attempt).execute(conn)?;
+            diesel::insert_into(puzzle_ratings::table)
+                .values(&puzzle_models::PuzzleRating { user_name: new_attempt.user_name.to_owned(), rating: player_rating })
+                .on_conflict(puzzle_ratings::user_name)
+                .do_update()
+                .set(puzzle_ratings::rating.eq(player_rating))
+                .execute(conn)?;
+            diesel::update(puzzles::table.find(new_attempt.puzzle_id))
+                .set(puzzles::rating.eq(puzzle_rating))
+                .execute(conn)?;
+            Ok(())
+        })
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{puzzle_attempts, puzzle_ratings};
+
+use checkers_rules::{GameData, Variant};
+
+
+#[derive(Queryable)]
+pub struct Puzzle
+{
+    pub id: i32,
+    pub variant: String,
+    /// Start position in the FEN notation
+    pub fen: String,
+    /// Moves of both sides in the PDN notation, the side to move in the start position moves first
+    pub solution: Vec<String>,
+    pub rating: i32,
+}
+
+
+#[derive(Insertable)]
+#[table_name="puzzle_attempts"]
+pub struct NewPuzzleAttempt
+{
+    pub puzzle_id: i32,
+    pub user_name: String,
+    pub is_solved: bool,
+}
+
+
+#[derive(Insertable)]
+#[table_name="puzzle_ratings"]
+pub struct PuzzleRating
+{
+    pub user_name: String,
+    pub rating: i32,
+}
+
+
+/// Puzzle sent to the player, the solution line stays on the server.
+#[derive(Serialize)]
+pub struct PuzzleResponse
+{
+    pub id: i32,
+    pub variant: Variant,
+    pub fen: String,
+    pub rating: i32,
+    /// Puzzle rating of the requesting player
+    pub player_rating: i32,
+}
+
+
+/// Moves of the player played so far without the replies of the opponent, the last one is checked.
+#[derive(Deserialize)]
+pub struct PuzzleAttempt
+{
+    pub puzzle_id: i32,
+    pub moves: Vec<GameData>,
+}
+
+
+#[derive(Serialize)]
+pub struct PuzzleAttemptResult
+{
+    pub is_solved: bool,
+    pub player_rating: i32,
+    pub rating_change: i32,
+}
+
+
+/// Reply of the opponent to the correct move, the result is sent when the attempt is over.
+#[derive(Serialize)]
+pub struct PuzzleMoveResult
+{
+    pub reply: Option<GameData>,
+    pub result: Option<PuzzleAttemptResult>,
+}
@@ -0,0 +1,182 @@
+//! Glicko-2 rating system, every finished game is a separate rating period.
+//! See http://www.glicko.net/glicko/glicko2.pdf
+
+
+use std::f64::consts::PI;
+
+
+pub const INITIAL_RATING: f64 = 1500.0;
+pub const INITIAL_DEVIATION: f64 = 350.0;
+pub const INITIAL_VOLATILITY: f64 = 0.06;
+/// Conversion factor between the Glicko and the Glicko-2 scales
+const SCALE: f64 = 173.7178;
+/// Constrains the change of the volatility over time
+const TAU: f64 = 0.5;
+const CONVERGENCE_TOLERANCE: f64 = 0.000001;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glicko
+{
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+
+impl Default for Glicko
+{
+    fn default() -> Self
+    {
+        Glicko { rating: INITIAL_RATING, deviation: INITIAL_DEVIATION, volatility: INITIAL_VOLATILITY }
+    }
+}
+
+
+fn g(phi: f64) -> f64
+{
+    1.0 / (1.0 + 3.0 * phi.powi(2) / PI.powi(2)).sqrt()
+}
+
+
+fn expected_score(mu: f64, opponent_mu: f64, opponent_phi: f64) -> f64
+{
+    1.0 / (1.0 + (-g(opponent_phi) * (mu - opponent_mu)).exp())
+}
+
+
+/// New volatility found by the Illinois algorithm.
+fn new_volatility(phi: f64, sigma: f64, delta: f64, v: f64) -> f64
+{
+    let a = sigma.powi(2).ln();
+    let f = |x: f64|
+        x.exp() * (delta.powi(2) - phi.powi(2) - v - x.exp()) / (2.0 * (phi.powi(2) + v + x.exp()).powi(2)) -
+            (x - a) / TAU.powi(2);
+
+    let mut big_a = a;
+    let mut big_b =
+        if delta.powi(2) > phi.powi(2) + v
+        {
+            (delta.powi(2) - phi.powi(2) - v).ln()
+        }
+        else
+        {
+            let mut k = 1.0;
+            while f(a - k * TAU) < 0.0
+            {
+                k += 1.0;
+            }
+            a - k * TAU
+        };
+    let mut f_a = f(big_a);
+    let mut f_b = f(big_b);
+    while (big_b - big_a).abs() > CONVERGENCE_TOLERANCE
+    {
+        let big_c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+        let f_c = f(big_c);
+        if f_c * f_b <= 0.0
+        {
+            big_a = big_b;
+            f_a = f_b;
+        }
+        else
+        {
+            f_a /= 2.0;
+        }
+        big_b = big_c;
+        f_b = f_c;
+    }
+    (big_a / 2.0).exp()
+}
+
+
+impl Glicko
+{
+    /// Rating after the games against the opponents, the score is 1 for a win, 0.5 for a draw and 0 for a loss.
+    pub fn rate(&self, results: &[(Glicko, f64)]) -> Glicko
+    {
+        let mu = (self.rating - INITIAL_RATING) / SCALE;
+        let phi = self.deviation / SCALE;
+        if results.is_empty()
+        {
+            let deviation = (phi.powi(2) + self.volatility.powi(2)).sqrt() * SCALE;
+            return Glicko { deviation, ..*self };
+        }
+
+        let opponents: Vec<(f64, f64, f64)> = results
+            .iter()
+            .map(|(opponent, score)|
+                ((opponent.rating - INITIAL_RATING) / SCALE, opponent.deviation / SCALE, *score))
+            .collect();
+        let v = 1.0 / opponents
+            .iter()
+            .map(|(opponent_mu, opponent_phi, _)|
+                {
+                    let e = expected_score(mu, *opponent_mu, *opponent_phi);
+                    g(*opponent_phi).powi(2) * e * (1.0 - e)
+                })
+            .sum::<f64>();
+        let improvement = opponents
+            .iter()
+            .map(|(opponent_mu, opponent_phi, score)|
+                g(*opponent_phi) * (score - expected_score(mu, *opponent_mu, *opponent_phi)))
+            .sum::<f64>();
+
+        let volatility = new_volatility(phi, self.volatility, v * improvement, v);
+        let phi_star = (phi.powi(2) + volatility.powi(2)).sqrt();
+        let new_phi = 1.0 / (1.0 / phi_star.powi(2) + 1.0 / v).sqrt();
+        let new_mu = mu + new_phi.powi(2) * improvement;
+        Glicko
+        {
+            rating: new_mu * SCALE + INITIAL_RATING,
+            deviation: new_phi * SCALE,
+            volatility,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    #[test]
+    fn rating_follows_glicko2_paper_example()
+    {
+        let player = Glicko { rating: 1500.0, deviation: 200.0, volatility: 0.06 };
+        let results =
+            [
+                (Glicko { rating: 1400.0, deviation: 30.0, volatility: 0.06 }, 1.0),
+                (Glicko { rating: 1550.0, deviation: 100.0, volatility: 0.06 }, 0.0),
+                (Glicko { rating: 1700.0, deviation: 300.0, volatility: 0.06 }, 0.0),
+            ];
+        let rated = player.rate(&results);
+        assert!((rated.rating - 1464.06).abs() < 0.01);
+        assert!((rated.deviation - 151.52).abs() < 0.01);
+        assert!((rated.volatility - 0.05999).abs() < 0.00001);
+    }
+
+
+    #[test]
+    fn win_raises_rating_and_lowers_deviation()
+    {
+        let player = Glicko::default();
+        let rated = player.rate(&[(Glicko::default(), 1.0)]);
+        assert!(rated.rating > player.rating);
+        assert!(rated.deviation < player.deviation);
+        let rated = player.rate(&[(Glicko::default(), 0.5)]);
+        assert!((rated.rating - player.rating).abs() < 0.000001);
+    }
+
+
+    #[test]
+    fn deviation_grows_without_games()
+    {
+        let player = Glicko { rating: 1600.0, deviation: 50.0, volatility: 0.06 };
+        let rated = player.rate(&[]);
+        assert_eq!(rated.rating, player.rating);
+        assert!(rated.deviation > player.deviation);
+    }
+}
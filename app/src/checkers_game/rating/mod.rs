@@ -0,0 +1,4 @@
+pub mod glicko;
+pub mod rating_models;
+pub mod rating_database;
+pub mod rating;
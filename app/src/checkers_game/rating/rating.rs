@@ -0,0 +1,141 @@
+use actix_web::{web, Error, HttpResponse};
+use std::collections::HashMap;
+
+use crate::checkers_game::rating::{rating_database, rating_models};
+use crate::DbPool;
+
+use checkers_rules::{GameOutcome, TimeControl, Variant};
+
+
+const LEADERBOARD_PAGE_SIZE: u32 = 20;
+const MAX_LEADERBOARD_PAGE_SIZE: u32 = 100;
+
+
+/// Wins, losses and draws of every player who finished at least one of the games
+/// from the numbers of games of the player by the color and the result.
+fn count_results(result_counts: &[rating_database::ResultCount]) -> HashMap<String, rating_models::GameCounts>
+{
+    let mut game_counts: HashMap<String, rating_models::GameCounts> = HashMap::new();
+    for (player, color, result, games_number) in result_counts
+    {
+        let score = match result.as_deref().and_then(GameOutcome::from_result)
+        {
+            Some(outcome) => outcome.score(color),
+            None => continue,
+        };
+        let games_number = *games_number as u32;
+        let counts = game_counts.entry(player.to_owned()).or_default();
+        if score > 0.5
+        {
+            counts.wins += games_number;
+        }
+        else if score < 0.5
+        {
+            counts.losses += games_number;
+        }
+        else
+        {
+            counts.draws += games_number;
+        }
+    }
+    game_counts
+}
+
+
+/// Players ordered by rating, the counts of games and the threshold take into account
+/// only the rated games of the chosen variant and time control.
+pub async fn leaderboard(
+    pool: web::Data<DbPool>, query: web::Query<rating_models::LeaderboardQuery>) -> Result<HttpResponse, Error>
+{
+    let query = query.into_inner();
+    if query.variant.as_deref().map_or(false, |name| Variant::from_name(name).is_none())
+    {
+        return Ok(HttpResponse::BadRequest().body("Unknown variant."));
+    }
+    if query.time_control.as_deref().map_or(false, |key| TimeControl::from_key(key).is_none())
+    {
+        return Ok(HttpResponse::BadRequest().body("Unknown time control."));
+    }
+    let min_games = query.min_games.unwrap_or(1);
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(LEADERBOARD_PAGE_SIZE).clamp(1, MAX_LEADERBOARD_PAGE_SIZE);
+
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let (game_counts, player_ratings) = web::block(move ||
+        {
+            let result_counts = rating_database::extract_rated_result_counts(query.variant, query.time_control, &conn)?;
+            let game_counts = count_results(&result_counts);
+            let user_names: Vec<String> = game_counts
+                .iter()
+                .filter(|(_, counts)| counts.total() >= min_games)
+                .map(|(user_name, _)| user_name.to_owned())
+                .collect();
+            let player_ratings = rating_database::extract_player_ratings(&user_names, &conn)?;
+            Ok::<_, diesel::result::Error>((game_counts, player_ratings))
+        })
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+
+    let mut player_ratings = player_ratings;
+    player_ratings.sort_by(|a, b| b.rating
+        .partial_cmp(&a.rating)
+        .unwrap_or(std::cmp::Ordering::Equal)
+        .then_with(|| a.user_name.cmp(&b.user_name)));
+    let total = player_ratings.len() as u32;
+    let skipped = page.saturating_sub(1).saturating_mul(per_page);
+    let entries = player_ratings
+        .into_iter()
+        .enumerate()
+        .skip(skipped as usize)
+        .take(per_page as usize)
+        .map(|(idx, player_rating)|
+            {
+                let counts = game_counts.get(&player_rating.user_name).copied().unwrap_or_default();
+                rating_models::LeaderboardEntry
+                {
+                    rank: idx as u32 + 1,
+                    rating: player_rating.rounded_rating(),
+                    user_name: player_rating.user_name,
+                    wins: counts.wins,
+                    losses: counts.losses,
+                    draws: counts.draws,
+                }
+            })
+        .collect();
+    Ok(HttpResponse::Ok().json(rating_models::Leaderboard { entries, page, per_page, total }))
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use checkers_rules::PieceColor;
+
+
+    #[test]
+    fn results_are_counted_for_both_players()
+    {
+        let result_counts = vec!
+            [
+                ("alice".to_owned(), PieceColor::White, Some("1-0".to_owned()), 2),
+                ("alice".to_owned(), PieceColor::Black, Some("1/2-1/2".to_owned()), 1),
+                ("alice".to_owned(), PieceColor::Black, Some("1-0".to_owned()), 1),
+                ("bob".to_owned(), PieceColor::Black, Some("1-0".to_owned()), 2),
+                ("bob".to_owned(), PieceColor::White, Some("1/2-1/2".to_owned()), 1),
+                ("carol".to_owned(), PieceColor::White, Some("1-0".to_owned()), 1),
+                ("carol".to_owned(), PieceColor::White, None, 1),
+            ];
+        let game_counts = count_results(&result_counts);
+        let alice = game_counts["alice"];
+        assert_eq!((alice.wins, alice.losses, alice.draws), (2, 1, 1));
+        assert_eq!(alice.total(), 4);
+        let bob = game_counts["bob"];
+        assert_eq!((bob.wins, bob.losses, bob.draws), (0, 2, 1));
+        assert_eq!(game_counts["carol"].total(), 1);
+    }
+}
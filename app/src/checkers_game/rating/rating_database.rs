@@ -0,0 +1,120 @@
+use diesel::prelude::*;
+
+use crate::checkers_game::rating::rating_models::PlayerRating;
+
+use checkers_rules::PieceColor;
+
+
+/// Player, color of the player, result and number of such games.
+pub type ResultCount = (String, PieceColor, Option<String>, i64);
+
+
+/// Rating of the player, the initial one when the player has not played rated games yet.
+pub fn extract_player_rating(name: &str, conn: &PgConnection) -> Result<PlayerRating, diesel::result::Error>
+{
+    use crate::schema::player_ratings::dsl::*;
+
+    let player_rating = player_ratings.find(name).first::<PlayerRating>(conn).optional()?;
+    Ok(player_rating.unwrap_or_else(|| PlayerRating::new(name)))
+}
+
+
+/// Ratings of the players, the initial ones for players without rated games.
+pub fn extract_player_ratings(names: &[String], conn: &PgConnection)
+    -> Result<Vec<PlayerRating>, diesel::result::Error>
+{
+    use crate::schema::player_ratings::dsl::*;
+
+    let stored_ratings = player_ratings
+        .filter(user_name.eq_any(names))
+        .load::<PlayerRating>(conn)?;
+    Ok(names
+        .iter()
+        .map(|name| stored_ratings
+            .iter()
+            .find(|player_rating| &player_rating.user_name == name)
+            .cloned()
+            .unwrap_or_else(|| PlayerRating::new(name)))
+        .collect())
+}
+
+
+/// Number of finished rated games of every player by the color of the player and the result,
+/// the variant and the time control are not checked when absent.
+pub fn extract_rated_result_counts(
+    variant_name: Option<String>, time_control_key: Option<String>, conn: &PgConnection)
+    -> Result<Vec<ResultCount>, diesel::result::Error>
+{
+    use crate::schema::games::dsl::*;
+    use diesel::dsl::sql;
+    use diesel::sql_types::BigInt;
+
+    let mut white_query = games
+        .filter(is_rated.eq(true))
+        .filter(result.is_not_null())
+        .group_by((white_player, result))
+        // diesel does not mix aggregate and plain columns in the select clause
+        .select((white_player, result, sql::<BigInt>("COUNT(*)")))
+        .into_boxed();
+    let mut black_query = games
+        .filter(is_rated.eq(true))
+        .filter(result.is_not_null())
+        .group_by((black_player, result))
+        .select((black_player, result, sql::<BigInt>("COUNT(*)")))
+        .into_boxed();
+    if let Some(variant_name) = variant_name
+    {
+        white_query = white_query.filter(variant.eq(variant_name.to_owned()));
+        black_query = black_query.filter(variant.eq(variant_name));
+    }
+    if let Some(time_control_key) = time_control_key
+    {
+        white_query = white_query.filter(time_control.eq(time_control_key.to_owned()));
+        black_query = black_query.filter(time_control.eq(time_control_key));
+    }
+    let white_counts = white_query.load::<(String, Option<String>, i64)>(conn)?;
+    let black_counts = black_query.load::<(String, Option<String>, i64)>(conn)?;
+    Ok(white_counts
+        .into_iter()
+        .map(|(player, game_result, games_number)| (player, PieceColor::White, game_result, games_number))
+        .chain(black_counts
+            .into_iter()
+            .map(|(player, game_result, games_number)| (player, PieceColor::Black, game_result, games_number)))
+        .collect())
+}
+
+
+fn save_player_rating(player_rating: &PlayerRating, conn: &PgConnection) -> Result<(), diesel::result::Error>
+{
+    use crate::schema::player_ratings::dsl::*;
+
+    diesel::insert_into(player_ratings)
+        .values(player_rating)
+        .on_conflict(user_name)
+        .do_update()
+        .set((
+            rating.eq(player_rating.rating),
+            deviation.eq(player_rating.deviation),
+            volatility.eq(player_rating.volatility),
+            games_played.eq(player_rating.games_played),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+
+/// Ratings of both players are recalculated from the score of the white player and saved together.
+pub fn update_ratings(white_player: &str, black_player: &str, white_score: f64, conn: &PgConnection)
+    -> Result<(PlayerRating, PlayerRating), diesel::result::Error>
+{
+    conn.transaction(||
+        {
+            let white_rating = extract_player_rating(white_player, conn)?;
+            let black_rating = extract_player_rating(black_player, conn)?;
+            let new_white_rating = white_rating.after_game(&black_rating, white_score);
+            let new_black_rating = black_rating.after_game(&white_rating, 1.0 - white_score);
+            save_player_rating(&new_white_rating, conn)?;
+            save_player_rating(&new_black_rating, conn)?;
+            Ok((new_white_rating, new_black_rating))
+        })
+}
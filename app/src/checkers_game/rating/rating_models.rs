@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+use crate::schema::player_ratings;
+use crate::checkers_game::rating::glicko::Glicko;
+
+
+#[derive(Debug, Clone, Serialize, Queryable, Insertable)]
+#[table_name="player_ratings"]
+pub struct PlayerRating
+{
+    pub user_name: String,
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+    pub games_played: i32,
+}
+
+
+impl PlayerRating
+{
+    /// Rating of the player before the first rated game.
+    pub fn new(user_name: &str) -> Self
+    {
+        let glicko = Glicko::default();
+        PlayerRating
+        {
+            user_name: user_name.to_owned(),
+            rating: glicko.rating,
+            deviation: glicko.deviation,
+            volatility: glicko.volatility,
+            games_played: 0,
+        }
+    }
+
+
+    fn glicko(&self) -> Glicko
+    {
+        Glicko { rating: self.rating, deviation: self.deviation, volatility: self.volatility }
+    }
+
+
+    /// Rating after the game against the opponent, the score is 1 for a win, 0.5 for a draw and 0 for a loss.
+    pub fn after_game(&self, opponent: &PlayerRating, score: f64) -> Self
+    {
+        let glicko = self.glicko().rate(&[(opponent.glicko(), score)]);
+        PlayerRating
+        {
+            user_name: self.user_name.to_owned(),
+            rating: glicko.rating,
+            deviation: glicko.deviation,
+            volatility: glicko.volatility,
+            games_played: self.games_played + 1,
+        }
+    }
+
+
+    /// Rating shown to users.
+    pub fn rounded_rating(&self) -> i32
+    {
+        self.rating.round() as i32
+    }
+}
+
+
+/// Filters and page of the leaderboard, all fields are optional.
+#[derive(Deserialize)]
+pub struct LeaderboardQuery
+{
+    pub variant: Option<String>,
+    /// Key of the time control, e.g. `fischer_300_3`
+    pub time_control: Option<String>,
+    /// Players with fewer finished rated games by the filters are not shown
+    pub min_games: Option<u32>,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+
+/// Finished rated games of the player counted by their results.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GameCounts
+{
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+
+impl GameCounts
+{
+    pub fn total(&self) -> u32
+    {
+        self.wins + self.losses + self.draws
+    }
+}
+
+
+#[derive(Serialize)]
+pub struct LeaderboardEntry
+{
+    pub rank: u32,
+    pub user_name: String,
+    pub rating: i32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+
+#[derive(Serialize)]
+pub struct Leaderboard
+{
+    pub entries: Vec<LeaderboardEntry>,
+    pub page: u32,
+    pub per_page: u32,
+    /// Number of players on all pages
+    pub total: u32,
+}
@@ -0,0 +1,4 @@
+pub mod tournament_models;
+pub mod tournament_database;
+pub mod pairing;
+pub mod tournament;
@@ -0,0 +1,400 @@
+//! Pairings of tournament rounds and standings of the players.
+
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::checkers_game::tournament::tournament_models::{Standing, TournamentGame, DOUBLE_FORFEIT_RESULT};
+
+use checkers_rules::{GameOutcome, PieceColor};
+
+
+/// White player and black player of every game of the round, the player without an opponent gets a bye.
+pub type Pairing = (String, Option<String>);
+
+
+/// How many pairs the search of pairings without rematches tries before it allows rematches
+const REMATCH_SEARCH_LIMIT: usize = 10_000;
+
+
+/// Everyone plays everyone once, one of the players rests every round when the number of players is odd.
+pub fn round_robin_rounds(players_number: usize) -> i32
+{
+    (players_number + players_number % 2) as i32 - 1
+}
+
+
+/// Pairings of the round by the circle method, the first player stays in place while the others rotate.
+pub fn round_robin_pairings(players: &[String], round: i32) -> Vec<Pairing>
+{
+    let mut seats: Vec<Option<&String>> = players.iter().map(Some).collect();
+    if seats.len() % 2 == 1
+    {
+        seats.push(None);
+    }
+    let seats_number = seats.len();
+    if seats_number < 2
+    {
+        return Vec::new();
+    }
+    let shift = (round.max(1) - 1) as usize % (seats_number - 1);
+    seats[1..].rotate_right(shift);
+
+    (0..seats_number / 2)
+        .filter_map(|idx|
+            {
+                let (first, second) =
+                    if (shift + idx) % 2 == 0 { (seats[idx], seats[seats_number - 1 - idx]) }
+                    else { (seats[seats_number - 1 - idx], seats[idx]) };
+                match (first, second)
+                {
+                    (Some(white_player), black_player) => Some((white_player.to_owned(), black_player.cloned())),
+                    (None, Some(player)) => Some((player.to_owned(), None)),
+                    (None, None) => None,
+                }
+            })
+        .collect()
+}
+
+
+/// Number of rounds of the Swiss tournament which is enough to find the single winner.
+pub fn swiss_rounds(players_number: usize) -> i32
+{
+    let mut rounds = 1;
+    while (1 << rounds) < players_number
+    {
+        rounds += 1;
+    }
+    rounds
+}
+
+
+fn have_played(first_player: &str, second_player: &str, games: &[TournamentGame]) -> bool
+{
+    games.iter().any(|game|
+        {
+            let black_player = game.black_player.as_deref();
+            (game.white_player == first_player && black_player == Some(second_player)) ||
+                (game.white_player == second_player && black_player == Some(first_player))
+        })
+}
+
+
+fn white_games(player: &str, games: &[TournamentGame]) -> usize
+{
+    games.iter().filter(|game| game.white_player == player && game.black_player.is_some()).count()
+}
+
+
+/// Players are paired from the top of the list, every player gets the highest placed opponent
+/// who has not been met yet and still leaves a way to pair the rest. The search gives up
+/// when it has tried the given number of pairs.
+fn pair_without_rematches<'a>(players: &[&'a str], games: &[TournamentGame], search_limit: &mut usize)
+    -> Option<Vec<(&'a str, &'a str)>>
+{
+    let first_player = match players.first()
+    {
+        Some(first_player) => *first_player,
+        None => return Some(Vec::new()),
+    };
+    for idx in 1..players.len()
+    {
+        if have_played(first_player, players[idx], games)
+        {
+            continue;
+        }
+        if *search_limit == 0
+        {
+            return None;
+        }
+        *search_limit -= 1;
+        let rest: Vec<&str> = players[1..]
+            .iter()
+            .enumerate()
+            .filter(|(rest_idx, _)| rest_idx + 1 != idx)
+            .map(|(_, player)| *player)
+            .collect();
+        if let Some(mut pairs) = pair_without_rematches(&rest, games, search_limit)
+        {
+            pairs.insert(0, (first_player, players[idx]));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+
+/// Pairings of the next Swiss round, the standings are expected in the order of places.
+/// The lowest placed player without a bye gets it, and the player with fewer games
+/// with white pieces plays white. Rematches are allowed only when they can not be avoided.
+pub fn swiss_pairings(standings: &[Standing], games: &[TournamentGame]) -> Vec<Pairing>
+{
+    let mut players: Vec<&str> = standings.iter().map(|standing| standing.user_name.as_str()).collect();
+    let mut pairings = Vec::new();
+    if players.len() % 2 == 1
+    {
+        let had_bye = |player: &str| games.iter().any(|game| game.white_player == player && game.black_player.is_none());
+        let idx = players.iter().rposition(|player| !had_bye(player)).unwrap_or(players.len() - 1);
+        pairings.push((players.remove(idx).to_owned(), None));
+    }
+
+    let mut search_limit = REMATCH_SEARCH_LIMIT;
+    let pairs = pair_without_rematches(&players, games, &mut search_limit)
+        .unwrap_or_else(|| players.chunks(2).map(|pair| (pair[0], pair[1])).collect());
+    for (first_player, second_player) in pairs
+    {
+        let (white_player, black_player) =
+            if white_games(second_player, games) < white_games(first_player, games) { (second_player, first_player) }
+            else { (first_player, second_player) };
+        pairings.push((white_player.to_owned(), Some(black_player.to_owned())));
+    }
+    pairings
+}
+
+
+/// Standings of the registered players by the finished games.
+pub fn standings(players: &[String], games: &[TournamentGame]) -> Vec<Standing>
+{
+    let mut player_standings: HashMap<&str, Standing> = players
+        .iter()
+        .map(|user_name| (user_name.as_str(), Standing
+            {
+                rank: 0,
+                user_name: user_name.to_owned(),
+                points: 0.0,
+                wins: 0,
+                draws: 0,
+                losses: 0,
+                buchholz: 0.0,
+                sonneborn_berger: 0.0,
+            }))
+        .collect();
+
+    // scores of both players of every finished game, a bye has no opponent
+    let mut results: Vec<(&str, Option<&str>, f64)> = Vec::new();
+    for game in games
+    {
+        match (&game.black_player, game.result.as_deref().and_then(GameOutcome::from_result))
+        {
+            (None, _) => results.push((&game.white_player, None, 1.0)),
+            (Some(black_player), Some(outcome)) =>
+                {
+                    results.push((&game.white_player, Some(black_player), outcome.score(&PieceColor::White)));
+                    results.push((black_player, Some(&game.white_player), outcome.score(&PieceColor::Black)));
+                },
+            // both players lose the game none of them came to
+            (Some(black_player), None) if game.result.as_deref() == Some(DOUBLE_FORFEIT_RESULT) =>
+                {
+                    results.push((&game.white_player, Some(black_player), 0.0));
+                    results.push((black_player, Some(&game.white_player), 0.0));
+                },
+            (Some(_), None) => (),
+        }
+    }
+
+    for (player, opponent, score) in &results
+    {
+        if let Some(standing) = player_standings.get_mut(player)
+        {
+            standing.points += score;
+            if opponent.is_some()
+            {
+                match score.partial_cmp(&0.5)
+                {
+                    Some(Ordering::Greater) => standing.wins += 1,
+                    Some(Ordering::Less) => standing.losses += 1,
+                    _ => standing.draws += 1,
+                }
+            }
+        }
+    }
+
+    let points: HashMap<&str, f64> = player_standings
+        .iter()
+        .map(|(user_name, standing)| (*user_name, standing.points))
+        .collect();
+    for (player, opponent, score) in &results
+    {
+        if let (Some(standing), Some(opponent_points)) =
+            (player_standings.get_mut(player), opponent.and_then(|opponent| points.get(opponent)))
+        {
+            standing.buchholz += opponent_points;
+            standing.sonneborn_berger += score * opponent_points;
+        }
+    }
+
+    let mut standings: Vec<Standing> = player_standings.into_iter().map(|(_, standing)| standing).collect();
+    standings.sort_by(|a, b| b.points.partial_cmp(&a.points).unwrap_or(Ordering::Equal)
+        .then_with(|| b.buchholz.partial_cmp(&a.buchholz).unwrap_or(Ordering::Equal))
+        .then_with(|| b.sonneborn_berger.partial_cmp(&a.sonneborn_berger).unwrap_or(Ordering::Equal))
+        .then_with(|| a.user_name.cmp(&b.user_name)));
+    for (idx, standing) in standings.iter_mut().enumerate()
+    {
+        standing.rank = idx as u32 + 1;
+    }
+    standings
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    fn players(user_names: &[&str]) -> Vec<String>
+    {
+        user_names.iter().map(|user_name| (*user_name).to_owned()).collect()
+    }
+
+
+    fn game(round: i32, white_player: &str, black_player: Option<&str>, result: Option<&str>) -> TournamentGame
+    {
+        TournamentGame
+        {
+            id: 0,
+            tournament_id: 1,
+            round,
+            white_player: white_player.to_owned(),
+            black_player: black_player.map(str::to_owned),
+            game_id: None,
+            result: result.map(str::to_owned),
+        }
+    }
+
+
+    #[test]
+    fn round_robin_pairs_everyone_once()
+    {
+        for players_number in 2..=7
+        {
+            let players: Vec<String> = (0..players_number).map(|idx| format!("player_{}", idx)).collect();
+            let rounds = round_robin_rounds(players_number);
+            let mut met = Vec::new();
+            let mut byes = Vec::new();
+            for round in 1..=rounds
+            {
+                let pairings = round_robin_pairings(&players, round);
+                let mut seated: Vec<&String> = pairings
+                    .iter()
+                    .flat_map(|(white_player, black_player)| Some(white_player).into_iter().chain(black_player))
+                    .collect();
+                seated.sort();
+                seated.dedup();
+                assert_eq!(seated.len(), players_number);
+                for (white_player, black_player) in pairings
+                {
+                    match black_player
+                    {
+                        Some(black_player) =>
+                            {
+                                let mut pair = vec![white_player, black_player];
+                                pair.sort();
+                                met.push(pair);
+                            },
+                        None => byes.push(white_player),
+                    }
+                }
+            }
+            let pairs_number = met.len();
+            met.sort();
+            met.dedup();
+            assert_eq!(met.len(), pairs_number);
+            assert_eq!(pairs_number, players_number * (players_number - 1) / 2);
+            byes.sort();
+            byes.dedup();
+            assert_eq!(byes.len(), if players_number % 2 == 1 { players_number } else { 0 });
+        }
+    }
+
+
+    #[test]
+    fn swiss_rounds_are_enough_for_single_winner()
+    {
+        assert_eq!(swiss_rounds(2), 1);
+        assert_eq!(swiss_rounds(8), 3);
+        assert_eq!(swiss_rounds(9), 4);
+    }
+
+
+    #[test]
+    fn swiss_pairings_avoid_rematches()
+    {
+        let games = vec![game(1, "a", Some("b"), Some("1-0")), game(1, "c", Some("d"), Some("1-0"))];
+        let standings = standings(&players(&["a", "b", "c", "d"]), &games);
+        let pairings = swiss_pairings(&standings, &games);
+        assert_eq!(pairings, vec![
+            ("a".to_owned(), Some("c".to_owned())),
+            ("b".to_owned(), Some("d".to_owned())),
+        ]);
+    }
+
+
+    #[test]
+    fn swiss_pairings_allow_rematch_when_search_is_too_long()
+    {
+        // the last player has met everyone, so every way to pair the others ends with a rematch
+        let others: Vec<String> = (0..21).map(|idx| format!("player_{}", idx)).collect();
+        let games: Vec<TournamentGame> = others
+            .iter()
+            .enumerate()
+            .map(|(idx, player)| game(idx as i32 + 1, "last", Some(player), Some("0-1")))
+            .collect();
+        let mut user_names = others.clone();
+        user_names.push("last".to_owned());
+        let standings = standings(&user_names, &games);
+        let pairings = swiss_pairings(&standings, &games);
+        assert_eq!(pairings.len(), 11);
+        let mut seated: Vec<&String> = pairings
+            .iter()
+            .flat_map(|(white_player, black_player)| Some(white_player).into_iter().chain(black_player))
+            .collect();
+        seated.sort();
+        seated.dedup();
+        assert_eq!(seated.len(), 22);
+    }
+
+
+    #[test]
+    fn swiss_bye_goes_to_lowest_player_without_bye()
+    {
+        let games = vec![game(1, "a", Some("b"), Some("1-0")), game(1, "c", None, Some("1-0"))];
+        let standings = standings(&players(&["a", "b", "c"]), &games);
+        let pairings = swiss_pairings(&standings, &games);
+        // the player with fewer games with white pieces plays white
+        assert_eq!(pairings, vec![("b".to_owned(), None), ("c".to_owned(), Some("a".to_owned()))]);
+    }
+
+
+    #[test]
+    fn standings_are_ordered_by_points_and_tie_breaks()
+    {
+        let games = vec![
+            game(1, "a", Some("b"), Some("1-0")),
+            game(1, "c", Some("d"), Some("1/2-1/2")),
+            game(2, "a", Some("c"), Some("1-0")),
+            game(2, "b", Some("d"), Some(DOUBLE_FORFEIT_RESULT)),
+            game(3, "a", Some("d"), None),
+        ];
+        let standings = standings(&players(&["d", "c", "b", "a"]), &games);
+        let places: Vec<(&str, f64, u32, u32, u32)> = standings
+            .iter()
+            .map(|standing|
+                (standing.user_name.as_str(), standing.points, standing.wins, standing.draws, standing.losses))
+            .collect();
+        // players with equal points are ordered by the points of their opponents
+        assert_eq!(places, vec![("a", 2.0, 2, 0, 0), ("c", 0.5, 0, 1, 1), ("d", 0.5, 0, 1, 1), ("b", 0.0, 0, 0, 2)]);
+        assert_eq!(standings[1].buchholz, 2.5);
+        assert_eq!(standings[1].sonneborn_berger, 0.25);
+        assert_eq!(standings.iter().map(|standing| standing.rank).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    }
+
+
+    #[test]
+    fn bye_counts_as_points_but_not_as_game()
+    {
+        let standings = standings(&players(&["a"]), &[game(1, "a", None, Some("1-0"))]);
+        assert_eq!(standings[0].points, 1.0);
+        assert_eq!(standings[0].wins + standings[0].draws + standings[0].losses, 0);
+    }
+}
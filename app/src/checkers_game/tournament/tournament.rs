@@ -0,0 +1,405 @@
+use actix::Addr;
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+
+use crate::checkers_game::tournament::{pairing, tournament_database, tournament_models};
+use crate::checkers_game::tournament::tournament_models::TournamentSystem;
+use crate::checkers_game::chat::server::{ChatServer, StartTournamentRound, AdjudicateGame};
+use crate::{DbPool, decode_token};
+
+use checkers_rules::{GameOutcome, PieceColor, TimeControl, Variant};
+
+
+/// Result of the request to start the next round.
+enum NextRound
+{
+    NotFound,
+    Rejected(&'static str),
+    Started(tournament_models::Tournament, Vec<tournament_models::TournamentGame>),
+    Finished(tournament_models::Tournament),
+}
+
+
+/// Result of the request to set the result of the game.
+enum Adjudication
+{
+    NotFound,
+    Rejected(&'static str),
+    Saved(tournament_models::TournamentGame),
+}
+
+
+async fn extract_user_name(request: &HttpRequest) -> Option<String>
+{
+    let received_token = request.headers().get("authorization")?.to_str().ok()?;
+    decode_token(received_token).await.ok().map(|decoded_user| decoded_user.claims.user_name)
+}
+
+
+/// Pairings of the round which follows the current one, the number of rounds is fixed
+/// when the first round starts.
+fn next_round(
+    tournament: &tournament_models::Tournament, players: &[String], games: &[tournament_models::TournamentGame])
+    -> (i32, Vec<pairing::Pairing>)
+{
+    let system = TournamentSystem::from_name(&tournament.system).unwrap_or(TournamentSystem::RoundRobin);
+    let rounds = match system
+    {
+        TournamentSystem::RoundRobin => pairing::round_robin_rounds(players.len()),
+        TournamentSystem::Swiss if tournament.rounds > 0 => tournament.rounds,
+        TournamentSystem::Swiss => pairing::swiss_rounds(players.len()),
+    };
+    let round = tournament.current_round + 1;
+    let pairings = match system
+    {
+        TournamentSystem::RoundRobin => pairing::round_robin_pairings(players, round),
+        TournamentSystem::Swiss => pairing::swiss_pairings(&pairing::standings(players, games), games),
+    };
+    (rounds, pairings)
+}
+
+
+fn start_next_round(tournament_id: i32, user_name: &str, conn: &diesel::PgConnection)
+    -> Result<NextRound, diesel::result::Error>
+{
+    let tournament = match tournament_database::extract_tournament(tournament_id, conn)?
+    {
+        Some(tournament) => tournament,
+        None => return Ok(NextRound::NotFound),
+    };
+    if tournament.created_by != user_name
+    {
+        return Ok(NextRound::Rejected("Only the organizer starts rounds."));
+    }
+    if tournament.status == tournament_models::FINISHED_STATUS
+    {
+        return Ok(NextRound::Rejected("Tournament is finished."));
+    }
+    let players = tournament_database::extract_players(tournament_id, conn)?;
+    if players.len() < 2
+    {
+        return Ok(NextRound::Rejected("At least two players are needed."));
+    }
+    let games = tournament_database::extract_games(tournament_id, conn)?;
+    if games.iter().any(|game| game.result.is_none())
+    {
+        return Ok(NextRound::Rejected("Games of the current round are not finished yet."));
+    }
+    if tournament.status != tournament_models::REGISTRATION_STATUS && tournament.current_round >= tournament.rounds
+    {
+        tournament_database::finish_tournament(tournament_id, conn)?;
+        let tournament = tournament_models::Tournament
+            {
+                status: tournament_models::FINISHED_STATUS.to_owned(),
+                ..tournament
+            };
+        return Ok(NextRound::Finished(tournament));
+    }
+
+    let (rounds, pairings) = next_round(&tournament, &players, &games);
+    let round = tournament.current_round + 1;
+    let new_games = pairings
+        .into_iter()
+        .map(|(white_player, black_player)| tournament_models::NewTournamentGame
+            {
+                tournament_id,
+                round,
+                // the player with a bye wins at once
+                result: if black_player.is_none()
+                    { Some(GameOutcome::Win(PieceColor::White).as_result().to_owned()) } else { None },
+                white_player,
+                black_player,
+            })
+        .collect();
+    let round_games = tournament_database::start_round(tournament_id, round, rounds, new_games, conn)?;
+    let tournament = tournament_models::Tournament
+        {
+            rounds,
+            current_round: round,
+            status: tournament_models::IN_PROGRESS_STATUS.to_owned(),
+            ..tournament
+        };
+    Ok(NextRound::Started(tournament, round_games))
+}
+
+
+/// Results of earlier rounds are kept, the pairings were already made by them.
+fn adjudicate_game(
+    tournament_id: i32, game_id: i32, user_name: &str, game_result: String, conn: &diesel::PgConnection)
+    -> Result<Adjudication, diesel::result::Error>
+{
+    let tournament = match tournament_database::extract_tournament(tournament_id, conn)?
+    {
+        Some(tournament) => tournament,
+        None => return Ok(Adjudication::NotFound),
+    };
+    if tournament.created_by != user_name
+    {
+        return Ok(Adjudication::Rejected("Only the organizer sets game results."));
+    }
+    if tournament.status == tournament_models::FINISHED_STATUS
+    {
+        return Ok(Adjudication::Rejected("Tournament is finished."));
+    }
+    let game = match tournament_database::extract_game(game_id, conn)?
+    {
+        Some(game) if game.tournament_id == tournament_id => game,
+        _ => return Ok(Adjudication::NotFound),
+    };
+    if game.black_player.is_none()
+    {
+        return Ok(Adjudication::Rejected("The bye is not played."));
+    }
+    if game.round != tournament.current_round
+    {
+        return Ok(Adjudication::Rejected("Only games of the current round get results."));
+    }
+    Ok(Adjudication::Saved(tournament_database::adjudicate_result(game_id, game_result, conn)?))
+}
+
+
+pub async fn create_tournament(
+    pool: web::Data<DbPool>, tournament_request: web::Json<tournament_models::NewTournamentRequest>,
+    request: HttpRequest) -> Result<HttpResponse, Error>
+{
+    let user_name = match extract_user_name(&request).await
+    {
+        Some(user_name) => user_name,
+        None => return Ok(HttpResponse::Unauthorized().body("Sign in to create tournaments.")),
+    };
+    let tournament_request = tournament_request.into_inner();
+    let name = tournament_request.name.trim().to_owned();
+    if name.is_empty()
+    {
+        return Ok(HttpResponse::BadRequest().body("Tournament name is empty."));
+    }
+    let rounds = match tournament_request.system
+    {
+        TournamentSystem::Swiss => tournament_request.rounds.unwrap_or(0).max(0),
+        TournamentSystem::RoundRobin => 0,
+    };
+    let new_tournament = tournament_models::NewTournament
+        {
+            name,
+            system: tournament_request.system.as_str().to_owned(),
+            variant: tournament_request.variant.as_str().to_owned(),
+            time_control: tournament_request.time_control.map(|time_control| time_control.as_key()),
+            rounds,
+            created_by: user_name,
+        };
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let tournament = web::block(move || tournament_database::insert_new_tournament(new_tournament, &conn))
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    Ok(HttpResponse::Ok().json(tournament))
+}
+
+
+pub async fn list_tournaments(pool: web::Data<DbPool>) -> Result<HttpResponse, Error>
+{
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let tournaments = web::block(move || tournament_database::extract_tournaments(&conn))
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    Ok(HttpResponse::Ok().json(tournaments))
+}
+
+
+/// Tournament with its players and the games of all started rounds.
+pub async fn tournament_details(
+    pool: web::Data<DbPool>, info: web::Path<tournament_models::TournamentInfo>) -> Result<HttpResponse, Error>
+{
+    let tournament_id = info.tournament_id;
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let details = web::block(move ||
+        {
+            match tournament_database::extract_tournament(tournament_id, &conn)?
+            {
+                Some(tournament) =>
+                    {
+                        let players = tournament_database::extract_players(tournament_id, &conn)?;
+                        let games = tournament_database::extract_games(tournament_id, &conn)?;
+                        Ok(Some(tournament_models::TournamentDetails { tournament, players, games }))
+                    },
+                None => Ok::<_, diesel::result::Error>(None),
+            }
+        })
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    match details
+    {
+        Some(details) => Ok(HttpResponse::Ok().json(details)),
+        None => Ok(HttpResponse::NotFound().body("Tournament not found.")),
+    }
+}
+
+
+/// The signed in user joins the tournament, players are registered until the first round starts.
+pub async fn register_player(
+    pool: web::Data<DbPool>, info: web::Path<tournament_models::TournamentInfo>,
+    request: HttpRequest) -> Result<HttpResponse, Error>
+{
+    let user_name = match extract_user_name(&request).await
+    {
+        Some(user_name) => user_name,
+        None => return Ok(HttpResponse::Unauthorized().body("Sign in to play in tournaments.")),
+    };
+    let tournament_id = info.tournament_id;
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let tournament = web::block(move ||
+        {
+            let tournament = tournament_database::extract_tournament(tournament_id, &conn)?;
+            if let Some(tournament) = &tournament
+            {
+                if tournament.status == tournament_models::REGISTRATION_STATUS
+                {
+                    tournament_database::insert_player(
+                        tournament_models::NewTournamentPlayer { tournament_id, user_name }, &conn)?;
+                }
+            }
+            Ok::<_, diesel::result::Error>(tournament)
+        })
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    match tournament
+    {
+        Some(tournament) if tournament.status == tournament_models::REGISTRATION_STATUS =>
+            Ok(HttpResponse::Ok().body("Player is registered.")),
+        Some(_) => Ok(HttpResponse::BadRequest().body("Registration is closed.")),
+        None => Ok(HttpResponse::NotFound().body("Tournament not found.")),
+    }
+}
+
+
+/// The organizer pairs the players of the next round and their games are started,
+/// the tournament is finished when all rounds are played.
+pub async fn start_round(
+    pool: web::Data<DbPool>, server: web::Data<Addr<ChatServer>>,
+    info: web::Path<tournament_models::TournamentInfo>, request: HttpRequest) -> Result<HttpResponse, Error>
+{
+    let user_name = match extract_user_name(&request).await
+    {
+        Some(user_name) => user_name,
+        None => return Ok(HttpResponse::Unauthorized().body("Sign in to run tournaments.")),
+    };
+    let tournament_id = info.tournament_id;
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let next_round = web::block(move || start_next_round(tournament_id, &user_name, &conn))
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    match next_round
+    {
+        NextRound::NotFound => Ok(HttpResponse::NotFound().body("Tournament not found.")),
+        NextRound::Rejected(message) => Ok(HttpResponse::BadRequest().body(message)),
+        NextRound::Finished(tournament) => Ok(HttpResponse::Ok().json(tournament)),
+        NextRound::Started(tournament, round_games) =>
+            {
+                server.do_send(StartTournamentRound
+                    {
+                        tournament_id,
+                        tournament_name: tournament.name.to_owned(),
+                        round: tournament.current_round,
+                        variant: Variant::from_name(&tournament.variant).unwrap_or_default(),
+                        time_control: tournament.time_control.as_deref().and_then(TimeControl::from_key),
+                        games: round_games
+                            .iter()
+                            .filter_map(|game| game.black_player.as_ref().map(|black_player|
+                                (game.id, game.white_player.to_owned(), black_player.to_owned())))
+                            .collect(),
+                    });
+                Ok(HttpResponse::Ok().json(tournament))
+            },
+    }
+}
+
+
+/// The organizer sets the result of the game which was not finished on the server
+/// or corrects it, the next round waits for results of all games. The game is closed
+/// when it is still played.
+pub async fn set_game_result(
+    pool: web::Data<DbPool>, server: web::Data<Addr<ChatServer>>, info: web::Path<tournament_models::TournamentGameInfo>,
+    result_request: web::Json<tournament_models::GameResultRequest>, request: HttpRequest)
+    -> Result<HttpResponse, Error>
+{
+    let user_name = match extract_user_name(&request).await
+    {
+        Some(user_name) => user_name,
+        None => return Ok(HttpResponse::Unauthorized().body("Sign in to run tournaments.")),
+    };
+    let game_result = result_request.into_inner().result;
+    if GameOutcome::from_result(&game_result).is_none() && game_result != tournament_models::DOUBLE_FORFEIT_RESULT
+    {
+        return Ok(HttpResponse::BadRequest().body("Unknown game result."));
+    }
+    let (tournament_id, game_id) = (info.tournament_id, info.game_id);
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let adjudication = web::block(move ||
+        adjudicate_game(tournament_id, game_id, &user_name, game_result, &conn))
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    match adjudication
+    {
+        Adjudication::NotFound => Ok(HttpResponse::NotFound().body("Tournament game not found.")),
+        Adjudication::Rejected(message) => Ok(HttpResponse::BadRequest().body(message)),
+        Adjudication::Saved(game) =>
+            {
+                if let Some(result) = &game.result
+                {
+                    server.do_send(AdjudicateGame { tournament_game_id: game.id, result: result.to_owned() });
+                }
+                Ok(HttpResponse::Ok().json(game))
+            },
+    }
+}
+
+
+pub async fn tournament_standings(
+    pool: web::Data<DbPool>, info: web::Path<tournament_models::TournamentInfo>) -> Result<HttpResponse, Error>
+{
+    let tournament_id = info.tournament_id;
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let standings = web::block(move ||
+        {
+            if tournament_database::extract_tournament(tournament_id, &conn)?.is_none()
+            {
+                return Ok(None);
+            }
+            let players = tournament_database::extract_players(tournament_id, &conn)?;
+            let games = tournament_database::extract_games(tournament_id, &conn)?;
+            Ok::<_, diesel::result::Error>(Some(pairing::standings(&players, &games)))
+        })
+        .await
+        .map_err(|e|
+            {
+                eprintln!("{}", e);
+                HttpResponse::InternalServerError().finish()
+            })?;
+    match standings
+    {
+        Some(standings) => Ok(HttpResponse::Ok().json(standings)),
+        None => Ok(HttpResponse::NotFound().body("Tournament not found.")),
+    }
+}
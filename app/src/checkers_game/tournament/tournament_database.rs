@@ -0,0 +1,155 @@
+use diesel::prelude::*;
+
+use crate::checkers_game::tournament::tournament_models;
+
+
+pub fn insert_new_tournament(new_tournament: tournament_models::NewTournament, conn: &PgConnection)
+    -> Result<tournament_models::Tournament, diesel::result::Error>
+{
+    use crate::schema::tournaments::dsl::*;
+
+    diesel::insert_into(tournaments)
+        .values(&new_tournament)
+        .get_result::<tournament_models::Tournament>(conn)
+}
+
+
+/// Tournaments from the latest to the earliest one.
+pub fn extract_tournaments(conn: &PgConnection) -> Result<Vec<tournament_models::Tournament>, diesel::result::Error>
+{
+    use crate::schema::tournaments::dsl::*;
+
+    tournaments
+        .order_by(created_at.desc())
+        .load::<tournament_models::Tournament>(conn)
+}
+
+
+pub fn extract_tournament(uid: i32, conn: &PgConnection)
+    -> Result<Option<tournament_models::Tournament>, diesel::result::Error>
+{
+    use crate::schema::tournaments::dsl::*;
+
+    tournaments.find(uid).first::<tournament_models::Tournament>(conn).optional()
+}
+
+
+/// User names of the players in the order they were registered.
+pub fn extract_players(uid: i32, conn: &PgConnection) -> Result<Vec<String>, diesel::result::Error>
+{
+    use crate::schema::tournament_players::dsl::*;
+
+    tournament_players
+        .filter(tournament_id.eq(uid))
+        .order_by(id.asc())
+        .select(user_name)
+        .load::<String>(conn)
+}
+
+
+/// The player registered twice stays registered once.
+pub fn insert_player(new_player: tournament_models::NewTournamentPlayer, conn: &PgConnection)
+    -> Result<(), diesel::result::Error>
+{
+    use crate::schema::tournament_players::dsl::*;
+
+    diesel::insert_into(tournament_players)
+        .values(&new_player)
+        .on_conflict((tournament_id, user_name))
+        .do_nothing()
+        .execute(conn)?;
+    Ok(())
+}
+
+
+/// Games of all rounds in the order they were paired.
+pub fn extract_games(uid: i32, conn: &PgConnection)
+    -> Result<Vec<tournament_models::TournamentGame>, diesel::result::Error>
+{
+    use crate::schema::tournament_games::dsl::*;
+
+    tournament_games
+        .filter(tournament_id.eq(uid))
+        .order_by((round.asc(), id.asc()))
+        .load::<tournament_models::TournamentGame>(conn)
+}
+
+
+pub fn extract_game(uid: i32, conn: &PgConnection)
+    -> Result<Option<tournament_models::TournamentGame>, diesel::result::Error>
+{
+    use crate::schema::tournament_games::dsl::*;
+
+    tournament_games.find(uid).first::<tournament_models::TournamentGame>(conn).optional()
+}
+
+
+/// Games of the new round are saved together with the number of the round.
+pub fn start_round(
+    uid: i32, new_round: i32, rounds_number: i32, new_games: Vec<tournament_models::NewTournamentGame>,
+    conn: &PgConnection) -> Result<Vec<tournament_models::TournamentGame>, diesel::result::Error>
+{
+    use crate::schema::{tournaments, tournament_games};
+
+    conn.transaction(||
+        {
+            diesel::update(tournaments::table.find(uid))
+                .set((
+                    tournaments::current_round.eq(new_round),
+                    tournaments::rounds.eq(rounds_number),
+                    tournaments::status.eq(tournament_models::IN_PROGRESS_STATUS),
+                ))
+                .execute(conn)?;
+            diesel::insert_into(tournament_games::table)
+                .values(&new_games)
+                .get_results::<tournament_models::TournamentGame>(conn)
+        })
+}
+
+
+pub fn finish_tournament(uid: i32, conn: &PgConnection) -> Result<(), diesel::result::Error>
+{
+    use crate::schema::tournaments::dsl::*;
+
+    diesel::update(tournaments.find(uid))
+        .set(status.eq(tournament_models::FINISHED_STATUS))
+        .execute(conn)?;
+    Ok(())
+}
+
+
+pub fn set_game_id(uid: i32, played_game_id: String, conn: &PgConnection)
+{
+    use crate::schema::tournament_games::dsl::*;
+
+    match diesel::update(tournament_games.find(uid)).set(game_id.eq(played_game_id)).execute(conn)
+    {
+        Ok(_) => (),
+        Err(_) => println!("Error to save tournament game in database!!!")
+    }
+}
+
+
+/// Result of the played game, the result already set by the organizer stays.
+pub fn save_result(uid: i32, game_result: String, conn: &PgConnection)
+{
+    use crate::schema::tournament_games::dsl::*;
+
+    match diesel::update(tournament_games.find(uid).filter(result.is_null())).set(result.eq(game_result)).execute(conn)
+    {
+        Ok(_) => (),
+        Err(_) => println!("Error to save tournament game result in database!!!")
+    }
+}
+
+
+/// Result set by the organizer replaces the one of the played game.
+pub fn adjudicate_result(uid: i32, game_result: String, conn: &PgConnection)
+    -> Result<tournament_models::TournamentGame, diesel::result::Error>
+{
+    use crate::schema::tournament_games::dsl::*;
+
+    diesel::update(tournament_games.find(uid))
+        .set(result.eq(game_result))
+        .get_result::<tournament_models::TournamentGame>(conn)
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+
+use crate::schema::{tournaments, tournament_players, tournament_games};
+
+use checkers_rules::{Variant, TimeControl};
+
+
+#[derive(Deserialize, Serialize, PartialEq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TournamentSystem
+{
+    /// Everyone plays everyone once
+    RoundRobin,
+    /// Players with equal scores meet each other, nobody meets the same opponent twice
+    Swiss,
+}
+
+
+impl TournamentSystem
+{
+    pub fn as_str(&self) -> &'static str
+    {
+        match self
+        {
+            TournamentSystem::RoundRobin => "round_robin",
+            TournamentSystem::Swiss => "swiss",
+        }
+    }
+
+
+    pub fn from_name(name: &str) -> Option<TournamentSystem>
+    {
+        [TournamentSystem::RoundRobin, TournamentSystem::Swiss]
+            .iter()
+            .find(|system| system.as_str() == name)
+            .copied()
+    }
+}
+
+
+pub const REGISTRATION_STATUS: &str = "registration";
+pub const IN_PROGRESS_STATUS: &str = "in_progress";
+pub const FINISHED_STATUS: &str = "finished";
+/// Result of the game none of the players came to, both of them lose it
+pub const DOUBLE_FORFEIT_RESULT: &str = "0-0";
+
+
+#[derive(Serialize, Queryable, Clone)]
+pub struct Tournament
+{
+    pub id: i32,
+    pub name: String,
+    pub system: String,
+    pub variant: String,
+    /// Key of the time control, games without time limits have none
+    pub time_control: Option<String>,
+    /// Number of rounds, it is known when the first round starts
+    pub rounds: i32,
+    /// Number of the last started round
+    pub current_round: i32,
+    pub status: String,
+    pub created_by: String,
+    pub created_at: NaiveDateTime,
+}
+
+
+#[derive(Insertable)]
+#[table_name="tournaments"]
+pub struct NewTournament
+{
+    pub name: String,
+    pub system: String,
+    pub variant: String,
+    pub time_control: Option<String>,
+    pub rounds: i32,
+    pub created_by: String,
+}
+
+
+#[derive(Insertable)]
+#[table_name="tournament_players"]
+pub struct NewTournamentPlayer
+{
+    pub tournament_id: i32,
+    pub user_name: String,
+}
+
+
+/// Game of the round, the white player has a bye when there is no black player.
+#[derive(Serialize, Queryable, Clone)]
+pub struct TournamentGame
+{
+    pub id: i32,
+    pub tournament_id: i32,
+    pub round: i32,
+    pub white_player: String,
+    pub black_player: Option<String>,
+    /// Id of the played game in the database
+    pub game_id: Option<String>,
+    pub result: Option<String>,
+}
+
+
+#[derive(Insertable)]
+#[table_name="tournament_games"]
+pub struct NewTournamentGame
+{
+    pub tournament_id: i32,
+    pub round: i32,
+    pub white_player: String,
+    pub black_player: Option<String>,
+    pub result: Option<String>,
+}
+
+
+#[derive(Deserialize)]
+pub struct NewTournamentRequest
+{
+    pub name: String,
+    pub system: TournamentSystem,
+    pub variant: Variant,
+    pub time_control: Option<TimeControl>,
+    /// Number of rounds of the Swiss tournament, it depends on the number of players when absent
+    #[serde(default)]
+    pub rounds: Option<i32>,
+}
+
+
+#[derive(Deserialize)]
+pub struct TournamentInfo
+{
+    pub tournament_id: i32,
+}
+
+
+#[derive(Deserialize)]
+pub struct TournamentGameInfo
+{
+    pub tournament_id: i32,
+    pub game_id: i32,
+}
+
+
+/// Result set by the organizer, it is one of `1-0`, `0-1`, `1/2-1/2` or `0-0` for the double forfeit.
+#[derive(Deserialize)]
+pub struct GameResultRequest
+{
+    pub result: String,
+}
+
+
+/// Place of the player in the tournament, players with equal points are ordered
+/// by the Buchholz and then by the Sonneborn-Berger scores.
+#[derive(Serialize, Clone, Debug)]
+pub struct Standing
+{
+    pub rank: u32,
+    pub user_name: String,
+    /// Byes are counted as won points but not as played games
+    pub points: f64,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// Sum of points of all opponents
+    pub buchholz: f64,
+    /// Sum of points of beaten opponents and half of points of opponents the game was drawn with
+    pub sonneborn_berger: f64,
+}
+
+
+#[derive(Serialize)]
+pub struct TournamentDetails
+{
+    pub tournament: Tournament,
+    pub players: Vec<String>,
+    pub games: Vec<TournamentGame>,
+}
@@ -10,6 +10,7 @@ use crypto::digest::Digest;
 use jsonwebtoken::TokenData;
 
 use crate::models;
+use crate::checkers_game::chat::computer_player::ComputerPlayer;
 
 
 #[derive(Fail, Debug)]
@@ -59,6 +60,10 @@ pub fn insert_new_user(user_data: &web::Json<models::UserRegisterData>, conn: &P
 {
     use crate::schema::users_data::dsl::*;
 
+    if ComputerPlayer::is_reserved_name(&user_data.user_name)
+    {
+        return Ok(Err(MyError::Unauthorized { message: "The name is reserved.".to_string() }));
+    }
     match users_data
         .filter(user_name.eq(user_data.user_name.to_string()))
         .or_filter(email.eq(user_data.email.to_string()))
@@ -131,6 +136,10 @@ fn check_edited_user_data_for_collision(edited_user_data: &web::Json<models::Use
     let edited_user_name = edited_user_data.edited_user_name.clone();
     if let Some(edited_user_name) = edited_user_name
     {
+        if ComputerPlayer::is_reserved_name(&edited_user_name)
+        {
+            return Err(MyError::Unauthorized { message: "The name is reserved.".to_string() });
+        }
         if let Ok(_) = users_data.filter(user_name.eq(edited_user_name.to_string())).first::<models::User>(conn)
         {
             return Err(MyError::Unauthorized { message: "The name is already in use.".to_string() });
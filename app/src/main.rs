@@ -23,9 +23,22 @@ mod database;
 mod checkers_game;
 use checkers_game::chat::chat::{chat_route, extract_chat_log};
 use checkers_game::chat::server::ChatServer;
+use checkers_game::chat::computer_player::ComputerPlayer;
+use checkers_game::game::game::{export_pdn, import_pdn, load_position, save_position, analyze_position, show_user_stats};
+use checkers_game::puzzle::puzzle::{next_puzzle, play_move};
+use checkers_game::rating::rating::leaderboard;
+use checkers_game::rating::rating_database;
+use checkers_game::tournament::tournament::
+    {
+        create_tournament, list_tournaments, tournament_details, register_player, start_round, set_game_result,
+        tournament_standings,
+    };
+use checkers_game::rating::rating_models::PlayerRating;
 
 use actix::*;
 
+use checkers_rules::EngineLevel;
+
 mod email;
 
 
@@ -130,6 +143,21 @@ async fn verify_user(user_data: TokenData<models::Claims>, pool: &web::Data<DbPo
 }
 
 
+async fn extract_player_rating(name: String, pool: &web::Data<DbPool>)
+    -> Result<PlayerRating, Error>
+{
+    let conn = pool.get().expect("couldn't get db connection from pool");
+    let player_rating = web::block(move || rating_database::extract_player_rating(&name, &conn))
+    .await
+    .map_err(|e|
+        {
+            eprintln!("{}", e);
+            HttpResponse::InternalServerError().finish()
+        })?;
+    Ok(player_rating)
+}
+
+
 async fn identify_user(pool: web::Data<DbPool>, request: HttpRequest)
     -> Result<Result<HttpResponse, database::MyError>, Error>
 {
@@ -145,12 +173,17 @@ async fn identify_user(pool: web::Data<DbPool>, request: HttpRequest)
 
                         // Ok(Some(user)) => Ok(Ok(HttpResponse::Ok().json(models::AuthorizedUserResponse { user_name: user.user_name } )) ),
 
-                        Ok(Some(user)) => Ok(Ok(HttpResponse::Ok().json(models::AuthorizedUserResponse
+                        Ok(Some(user)) =>
                             {
-                                user_name: user.user_name,
-                                email: user.email, is_superuser: user.is_superuser
-                            }))
-                        ),
+                                let player_rating = extract_player_rating(user.user_name.to_owned(), &pool).await?;
+                                Ok(Ok(HttpResponse::Ok().json(models::AuthorizedUserResponse
+                                    {
+                                        user_name: user.user_name,
+                                        email: user.email, is_superuser: user.is_superuser,
+                                        rating: player_rating.rounded_rating()
+                                    }))
+                                )
+                            },
 
                         Ok(None) => Ok(Err(database::MyError::Unauthorized { message: "Something go wrong.".to_string() })),
                         Err(e) => Err(e)
@@ -403,7 +436,13 @@ async fn main() -> std::io::Result<()>
         .build(manager)
         .expect("Failed to create pool.");
 
-    let server = ChatServer::default().start();
+    let server = ChatServer::new(pool.clone()).start();
+
+    // computer players of every level are waiting for invitations
+    for level in EngineLevel::iterator()
+    {
+        ComputerPlayer::new(*level, server.clone()).start();
+    }
 
     let bind = "0.0.0.0:8080";
     println!("Starting server at: {}", &bind);
@@ -477,6 +516,22 @@ async fn main() -> std::io::Result<()>
                 .service(web::resource("/ws/").to(chat_route))
 
                 .route("/chat/extract_log/{room}", web::get().to(extract_chat_log))
+                .route("/game/export_pdn/{game_id}", web::get().to(export_pdn))
+                .route("/game/import_pdn", web::post().to(import_pdn))
+                .route("/game/load_position/{game_id}", web::get().to(load_position))
+                .route("/game/save_position", web::post().to(save_position))
+                .route("/game/analyze", web::post().to(analyze_position))
+                .route("/game/user_stats/{user_name}", web::get().to(show_user_stats))
+                .route("/puzzle/next", web::get().to(next_puzzle))
+                .route("/puzzle/move", web::post().to(play_move))
+                .route("/leaderboard", web::get().to(leaderboard))
+                .route("/tournament/create", web::post().to(create_tournament))
+                .route("/tournament/list", web::get().to(list_tournaments))
+                .route("/tournament/{tournament_id}", web::get().to(tournament_details))
+                .route("/tournament/{tournament_id}/register", web::post().to(register_player))
+                .route("/tournament/{tournament_id}/next_round", web::post().to(start_round))
+                .route("/tournament/{tournament_id}/games/{game_id}/result", web::post().to(set_game_result))
+                .route("/tournament/{tournament_id}/standings", web::get().to(tournament_standings))
 
                 // .service(Files::new("", "./web_layout/obsolete").index_file("index.html"))
                 .service(Files::new("", "./web_layout").index_file("index.html"))
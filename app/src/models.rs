@@ -62,7 +62,9 @@ pub struct AuthorizedUserResponse
 {
     pub user_name: String,
     pub email: String,
-    pub is_superuser: bool
+    pub is_superuser: bool,
+    /// Rating of the player in rated games
+    pub rating: i32
 }
 
 
@@ -7,6 +7,107 @@ table! {
     }
 }
 
+table! {
+    game_moves (id) {
+        id -> Int4,
+        game_id -> Varchar,
+        ply -> Int4,
+        from_square -> Varchar,
+        to_square -> Varchar,
+        captured_squares -> Array<Varchar>,
+        promotion -> Bool,
+        path -> Array<Varchar>,
+    }
+}
+
+table! {
+    games (id) {
+        id -> Varchar,
+        white_player -> Varchar,
+        black_player -> Varchar,
+        variant -> Varchar,
+        started_at -> Timestamp,
+        finished_at -> Nullable<Timestamp>,
+        result -> Nullable<Varchar>,
+        initial_fen -> Nullable<Varchar>,
+        is_rated -> Bool,
+        time_control -> Nullable<Varchar>,
+        is_played_on_server -> Bool,
+    }
+}
+
+table! {
+    player_ratings (user_name) {
+        user_name -> Varchar,
+        rating -> Float8,
+        deviation -> Float8,
+        volatility -> Float8,
+        games_played -> Int4,
+    }
+}
+
+table! {
+    puzzle_attempts (id) {
+        id -> Int4,
+        puzzle_id -> Int4,
+        user_name -> Varchar,
+        is_solved -> Bool,
+        attempted_at -> Timestamp,
+    }
+}
+
+table! {
+    puzzle_ratings (user_name) {
+        user_name -> Varchar,
+        rating -> Int4,
+    }
+}
+
+table! {
+    puzzles (id) {
+        id -> Int4,
+        variant -> Varchar,
+        fen -> Varchar,
+        solution -> Array<Varchar>,
+        rating -> Int4,
+    }
+}
+
+table! {
+    tournament_games (id) {
+        id -> Int4,
+        tournament_id -> Int4,
+        round -> Int4,
+        white_player -> Varchar,
+        black_player -> Nullable<Varchar>,
+        game_id -> Nullable<Varchar>,
+        result -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    tournament_players (id) {
+        id -> Int4,
+        tournament_id -> Int4,
+        user_name -> Varchar,
+    }
+}
+
+table! {
+    tournaments (id) {
+        id -> Int4,
+        name -> Varchar,
+        system -> Varchar,
+        variant -> Varchar,
+        time_control -> Nullable<Varchar>,
+        rounds -> Int4,
+        current_round -> Int4,
+        status -> Varchar,
+        created_by -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
 table! {
     users_data (id) {
         id -> Varchar,
@@ -18,7 +119,22 @@ table! {
     }
 }
 
+joinable!(game_moves -> games (game_id));
+joinable!(puzzle_attempts -> puzzles (puzzle_id));
+joinable!(tournament_games -> games (game_id));
+joinable!(tournament_games -> tournaments (tournament_id));
+joinable!(tournament_players -> tournaments (tournament_id));
+
 allow_tables_to_appear_in_same_query!(
     checkers_game_chat,
+    game_moves,
+    games,
+    player_ratings,
+    puzzle_attempts,
+    puzzle_ratings,
+    puzzles,
+    tournament_games,
+    tournament_players,
+    tournaments,
     users_data,
 );
@@ -0,0 +1,632 @@
+use std::collections::HashMap;
+
+use crate::types::{PieceColor, CheckerPiece, CheckerPosition, AllowableMove};
+use crate::variant::Variant;
+
+
+const DIRECTIONS: [(i8, i8); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+
+/// Color, column, line and crown of every piece, sorted.
+pub type PositionKey = Vec<(bool, u8, u8, bool)>;
+
+
+fn forward_step(color: &PieceColor) -> i8
+{
+    match color
+    {
+        PieceColor::White => 1,
+        PieceColor::Black => -1,
+    }
+}
+
+
+/// Positions of all pieces on the board and the rules they are moved by.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Board
+{
+    pub checker_pieces: HashMap<PieceColor, Vec<CheckerPiece>>,
+    pub variant: Variant,
+    /// Number of cells in every line and column
+    pub size: u8,
+}
+
+
+impl Board
+{
+    /// Initial position: every dark cell of the nearest lines is occupied,
+    /// three lines on the 8x8 board and four lines on the 10x10 board.
+    pub fn init(variant: Variant) -> Self
+    {
+        let size = variant.board_size();
+        let pieces_lines = variant.pieces_lines();
+        let mut white_pieces = Vec::new();
+        let mut black_pieces = Vec::new();
+        for column in 1..=size
+        {
+            for line in 1..=size
+            {
+                if (column + line) % 2 != 0
+                {
+                    continue;
+                }
+                let position = CheckerPosition { column, line };
+                if line <= pieces_lines
+                {
+                    let id = white_pieces.len() as u8 + 1;
+                    white_pieces.push(CheckerPiece { id, is_crowned: false, position });
+                }
+                else if line > size - pieces_lines
+                {
+                    let id = black_pieces.len() as u8 + 1;
+                    black_pieces.push(CheckerPiece { id, is_crowned: false, position });
+                }
+            }
+        }
+
+        let mut checker_pieces = HashMap::new();
+        checker_pieces.insert(PieceColor::White, white_pieces);
+        checker_pieces.insert(PieceColor::Black, black_pieces);
+        Board { checker_pieces, variant, size }
+    }
+
+
+    pub fn is_allowable_position(&self, position: &CheckerPosition) -> bool
+    {
+        (1 <= position.column) && (position.column <= self.size) && (1 <= position.line) && (position.line <= self.size)
+    }
+
+
+    pub fn is_promotion_line(&self, color: &PieceColor, line: u8) -> bool
+    {
+        match color
+        {
+            PieceColor::White => line == self.size,
+            PieceColor::Black => line == 1,
+        }
+    }
+
+
+    fn shifted_position(&self, position: &CheckerPosition, column_step: i8, line_step: i8) -> Option<CheckerPosition>
+    {
+        let column = position.column as i8 + column_step;
+        let line = position.line as i8 + line_step;
+        if column < 1 || line < 1
+        {
+            return None;
+        }
+        let shifted = CheckerPosition { column: column as u8, line: line as u8 };
+        if self.is_allowable_position(&shifted)
+        {
+            Some(shifted)
+        }
+        else { None }
+    }
+
+
+    pub fn pieces(&self, color: &PieceColor) -> &Vec<CheckerPiece>
+    {
+        &self.checker_pieces[color]
+    }
+
+
+    pub fn find_position(&self, color: &PieceColor, position: &CheckerPosition) -> Option<usize>
+    {
+        self.checker_pieces[color].iter().position(|checker_piece| &checker_piece.position == position)
+    }
+
+
+    pub fn is_empty_cell(&self, position: &CheckerPosition) -> bool
+    {
+        self.find_position(&PieceColor::White, position).is_none() &&
+            self.find_position(&PieceColor::Black, position).is_none()
+    }
+
+
+    /// Single captures of the piece on the given position in the given direction.
+    /// Captures of men reach only the adjacent piece, flying kings move along
+    /// the whole diagonal and may land on any empty cell behind the captured piece.
+    /// Already captured pieces stay on the board until the end of the move,
+    /// they can not be captured twice and block the way.
+    /// Returns pairs of the captured piece position and the landing position.
+    fn single_captures(
+        &self, color: &PieceColor, position: &CheckerPosition, is_crowned: bool,
+        (column_step, line_step): (i8, i8), captured_positions: &[CheckerPosition])
+        -> Vec<(CheckerPosition, CheckerPosition)>
+    {
+        let mut captures = Vec::new();
+        if !is_crowned && !self.variant.men_capture_backwards() && line_step != forward_step(color)
+        {
+            return captures;
+        }
+        let is_flying = is_crowned && self.variant.has_flying_kings();
+
+        let mut first_position = self.shifted_position(position, column_step, line_step);
+        if is_flying
+        {
+            while let Some(position) = &first_position
+            {
+                if !self.is_empty_cell(position)
+                {
+                    break;
+                }
+                first_position = self.shifted_position(position, column_step, line_step);
+            }
+        }
+
+        let first_position = match first_position
+        {
+            Some(position) => position,
+            None => return captures,
+        };
+        if self.find_position(&color.opposite(), &first_position).is_none() ||
+            captured_positions.contains(&first_position)
+        {
+            return captures;
+        }
+
+        let mut second_position = self.shifted_position(&first_position, column_step, line_step);
+        while let Some(position) = second_position
+        {
+            if !self.is_empty_cell(&position)
+            {
+                break;
+            }
+            second_position = self.shifted_position(&position, column_step, line_step);
+            captures.push((first_position.to_owned(), position));
+            if !is_flying
+            {
+                break;
+            }
+        }
+        captures
+    }
+
+
+    /// All complete capturing sequences which continue the given path.
+    /// A man reaching the promotion line during capturing either finishes the move,
+    /// continues it as a crowned piece or continues it as a man, depending on the variant.
+    /// When some of landing cells behind the captured piece allow to continue capturing,
+    /// the piece has to land on one of them.
+    fn capturing_sequences(
+        &self, color: &PieceColor, checker_id: u8, is_crowned: bool,
+        path: Vec<CheckerPosition>, captured_positions: Vec<CheckerPosition>) -> Vec<AllowableMove>
+    {
+        let mut sequences = Vec::new();
+        let position = path.last().unwrap().to_owned();
+        for direction in DIRECTIONS.iter()
+        {
+            let mut direction_sequences = Vec::new();
+            for (captured_position, landing_position) in
+                self.single_captures(color, &position, is_crowned, *direction, &captured_positions)
+            {
+                let is_promoted = !is_crowned && self.is_promotion_line(color, landing_position.line);
+                let mut next_path = path.clone();
+                next_path.push(landing_position);
+                let mut next_captured_positions = captured_positions.clone();
+                next_captured_positions.push(captured_position);
+                if is_promoted && self.variant.is_move_finished_by_promotion()
+                {
+                    direction_sequences.push(
+                        AllowableMove
+                        {
+                            checker_id,
+                            path: next_path,
+                            captured_pieces_positions: next_captured_positions
+                        });
+                    continue;
+                }
+                let is_crowned = is_crowned || (is_promoted && self.variant.is_promoted_during_capture());
+                direction_sequences.extend(
+                    self.capturing_sequences(color, checker_id, is_crowned, next_path, next_captured_positions));
+            }
+            let captures_number = captured_positions.len() + 1;
+            if direction_sequences.iter().any(|sequence| sequence.captured_pieces_positions.len() > captures_number)
+            {
+                direction_sequences.retain(|sequence| sequence.captured_pieces_positions.len() > captures_number);
+            }
+            sequences.extend(direction_sequences);
+        }
+
+        if sequences.is_empty() && !captured_positions.is_empty()
+        {
+            sequences.push(
+                AllowableMove
+                {
+                    checker_id,
+                    path,
+                    captured_pieces_positions: captured_positions
+                });
+        }
+        sequences
+    }
+
+
+    /// Complete capturing sequences of all pieces, every sequence is a single move.
+    /// When the variant obliges to take the maximum, only the longest sequences are kept.
+    pub fn can_capturing_checkers(&self, color: &PieceColor) -> Option<Vec<AllowableMove>>
+    {
+        let mut checkers = Vec::new();
+        for checker in self.pieces(color)
+        {
+            // the moving piece leaves its cell, so the cell may be crossed during the capturing
+            let mut board = self.clone();
+            let idx = board.find_position(color, &checker.position).unwrap();
+            board.checker_pieces.get_mut(color).unwrap().remove(idx);
+            checkers.extend(board.capturing_sequences(
+                color, checker.id, checker.is_crowned, vec![checker.position.to_owned()], Vec::new()));
+        }
+        if self.variant.is_maximum_capture_mandatory()
+        {
+            if let Some(maximum) = checkers.iter().map(|sequence| sequence.captured_pieces_positions.len()).max()
+            {
+                checkers.retain(|sequence| sequence.captured_pieces_positions.len() == maximum);
+            }
+        }
+        if !checkers.is_empty()
+        {
+            Some(checkers)
+        }
+        else { None }
+    }
+
+
+    /// Men step forward to the adjacent cell, crowned pieces move in every direction,
+    /// flying kings along the whole diagonal.
+    pub fn can_moving_checkers(&self, color: &PieceColor) -> Option<Vec<AllowableMove>>
+    {
+        let mut checkers = Vec::new();
+        for checker in self.pieces(color)
+        {
+            for (column_step, line_step) in DIRECTIONS.iter()
+            {
+                if !checker.is_crowned && *line_step != forward_step(color)
+                {
+                    continue;
+                }
+                let is_flying = checker.is_crowned && self.variant.has_flying_kings();
+                let mut second_position = self.shifted_position(&checker.position, *column_step, *line_step);
+                while let Some(position) = second_position
+                {
+                    if !self.is_empty_cell(&position)
+                    {
+                        break;
+                    }
+                    second_position = self.shifted_position(&position, *column_step, *line_step);
+                    checkers.push(
+                        AllowableMove
+                        {
+                            checker_id: checker.id,
+                            path: vec![checker.position.to_owned(), position],
+                            captured_pieces_positions: Vec::new()
+                        });
+                    if !is_flying
+                    {
+                        break;
+                    }
+                }
+            }
+        }
+        if !checkers.is_empty()
+        {
+            Some(checkers)
+        }
+        else { None }
+    }
+
+
+    /// Moves of all pieces of the side. When capturing is mandatory,
+    /// simple moves are allowed only when no piece of this color can capture.
+    pub fn all_allowable_moves(&self, color: &PieceColor) -> Vec<AllowableMove>
+    {
+        match self.can_capturing_checkers(color)
+        {
+            Some(capturing_moves) if self.variant.is_capturing_mandatory() => capturing_moves,
+            Some(mut capturing_moves) =>
+                {
+                    capturing_moves.extend(self.can_moving_checkers(color).unwrap_or_default());
+                    capturing_moves
+                },
+            None => self.can_moving_checkers(color).unwrap_or_default(),
+        }
+    }
+
+
+    /// Moves of the piece on the given position.
+    pub fn allowable_moves(&self, color: &PieceColor, position: &CheckerPosition) -> Option<Vec<AllowableMove>>
+    {
+        let idx = self.find_position(color, position)?;
+        let checker_id = self.pieces(color)[idx].id;
+        let moves: Vec<AllowableMove> = self.all_allowable_moves(color)
+            .into_iter()
+            .filter(|allowable_move| allowable_move.checker_id == checker_id)
+            .collect();
+        if !moves.is_empty()
+        {
+            Some(moves)
+        }
+        else { None }
+    }
+
+
+    /// Moves the piece along the path, crowns it when it reached the promotion line
+    /// and removes all captured pieces at once. Unless the variant crowns men during
+    /// capturing, only the final cell of the path is able to crown the piece.
+    pub fn move_checker_piece(
+        &mut self, color: &PieceColor, path: &[CheckerPosition],
+        captured_pieces_positions: &[CheckerPosition]) -> Option<u8>
+    {
+        let idx = self.find_position(color, path.first()?)?;
+        let is_promoted =
+            {
+                if self.variant.is_promoted_during_capture()
+                {
+                    path.iter().skip(1).any(|position| self.is_promotion_line(color, position.line))
+                }
+                else { self.is_promotion_line(color, path.last()?.line) }
+            };
+        let checker_piece = &mut self.checker_pieces.get_mut(color).unwrap()[idx];
+        checker_piece.position = path.last()?.to_owned();
+        if is_promoted
+        {
+            checker_piece.is_crowned = true;
+        }
+        let checker_id = checker_piece.id;
+
+        for captured_position in captured_pieces_positions
+        {
+            if let Some(idx) = self.find_position(&color.opposite(), captured_position)
+            {
+                self.checker_pieces.get_mut(&color.opposite()).unwrap().remove(idx);
+            }
+        }
+        Some(checker_id)
+    }
+
+
+    /// A side without pieces or with all pieces blocked has no moves.
+    pub fn has_allowable_moves(&self, color: &PieceColor) -> bool
+    {
+        self.can_capturing_checkers(color).is_some() || self.can_moving_checkers(color).is_some()
+    }
+
+
+    /// Cells of all pieces regardless of their order, used to compare positions.
+    pub fn position_key(&self) -> PositionKey
+    {
+        let mut key: PositionKey = self.checker_pieces
+            .iter()
+            .flat_map(|(color, pieces)| pieces
+                .iter()
+                .map(move |piece|
+                    (*color == PieceColor::White, piece.position.column, piece.position.line, piece.is_crowned)))
+            .collect();
+        key.sort_unstable();
+        key
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    fn position(notation: &str) -> CheckerPosition
+    {
+        CheckerPosition::from_notation(notation).unwrap()
+    }
+
+
+    /// Board with the pieces on the given cells, crowned pieces are marked with `K`, e.g. `Kc3`.
+    fn board(variant: Variant, white_cells: &[&str], black_cells: &[&str]) -> Board
+    {
+        let mut checker_pieces = HashMap::new();
+        for (color, cells) in [(PieceColor::White, white_cells), (PieceColor::Black, black_cells)].iter()
+        {
+            let pieces = cells
+                .iter()
+                .enumerate()
+                .map(|(idx, cell)| CheckerPiece
+                    {
+                        id: idx as u8 + 1,
+                        is_crowned: cell.starts_with('K'),
+                        position: position(cell.trim_start_matches('K')),
+                    })
+                .collect();
+            checker_pieces.insert(color.to_owned(), pieces);
+        }
+        Board { checker_pieces, variant, size: variant.board_size() }
+    }
+
+
+    /// Passed cells of every move in the notation, e.g. `c3-e5-g7`.
+    fn paths(moves: &[AllowableMove]) -> Vec<String>
+    {
+        let mut paths: Vec<String> = moves
+            .iter()
+            .map(|allowable_move| allowable_move.path
+                .iter()
+                .map(|position| position.notation())
+                .collect::<Vec<String>>()
+                .join("-"))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+
+    #[test]
+    fn initial_position_occupies_dark_cells_of_three_lines()
+    {
+        let board = Board::init(Variant::Russian);
+        assert_eq!(board.pieces(&PieceColor::White).len(), 12);
+        assert_eq!(board.pieces(&PieceColor::Black).len(), 12);
+        assert!(board.pieces(&PieceColor::White).iter().all(|piece| piece.position.line <= 3));
+        assert!(board.pieces(&PieceColor::Black).iter().all(|piece| piece.position.line >= 6));
+        assert!(board.checker_pieces
+            .values()
+            .flatten()
+            .all(|piece| (piece.position.column + piece.position.line) % 2 == 0));
+    }
+
+
+    #[test]
+    fn men_step_forward_only()
+    {
+        let board = board(Variant::Russian, &["c3"], &["f6"]);
+        assert_eq!(paths(&board.all_allowable_moves(&PieceColor::White)), vec!["c3-b4", "c3-d4"]);
+        assert_eq!(paths(&board.all_allowable_moves(&PieceColor::Black)), vec!["f6-e5", "f6-g5"]);
+    }
+
+
+    #[test]
+    fn capturing_is_mandatory()
+    {
+        let board = board(Variant::Russian, &["c3", "g3"], &["d4"]);
+        assert_eq!(paths(&board.all_allowable_moves(&PieceColor::White)), vec!["c3-e5"]);
+        assert_eq!(board.allowable_moves(&PieceColor::White, &position("g3")), None);
+    }
+
+
+    #[test]
+    fn captured_piece_is_removed()
+    {
+        let mut board = board(Variant::Russian, &["c3"], &["d4", "h8"]);
+        let checker_id = board.move_checker_piece(
+            &PieceColor::White, &[position("c3"), position("e5")], &[position("d4")]);
+        assert_eq!(checker_id, Some(1));
+        assert_eq!(board.find_position(&PieceColor::White, &position("e5")), Some(0));
+        assert!(board.is_empty_cell(&position("d4")));
+        assert_eq!(board.pieces(&PieceColor::Black).len(), 1);
+    }
+
+
+    #[test]
+    fn man_is_crowned_on_promotion_line()
+    {
+        let mut board = board(Variant::Russian, &["c7"], &["a1"]);
+        board.move_checker_piece(&PieceColor::White, &[position("c7"), position("d8")], &[]);
+        assert!(board.pieces(&PieceColor::White)[0].is_crowned);
+    }
+
+
+    #[test]
+    fn blocked_piece_has_no_moves()
+    {
+        let board = board(Variant::Russian, &["a1"], &["b2", "c3"]);
+        assert!(board.all_allowable_moves(&PieceColor::White).is_empty());
+        assert!(!board.has_allowable_moves(&PieceColor::White));
+        assert!(board.has_allowable_moves(&PieceColor::Black));
+    }
+
+
+    #[test]
+    fn flying_king_moves_along_whole_diagonal()
+    {
+        let board = board(Variant::Russian, &["Ka1"], &["h2"]);
+        assert_eq!(
+            paths(&board.all_allowable_moves(&PieceColor::White)),
+            vec!["a1-b2", "a1-c3", "a1-d4", "a1-e5", "a1-f6", "a1-g7", "a1-h8"]);
+    }
+
+
+    #[test]
+    fn king_without_flying_steps_to_adjacent_cell()
+    {
+        let board = board(Variant::English, &["Kc3"], &["h8"]);
+        assert_eq!(
+            paths(&board.all_allowable_moves(&PieceColor::White)),
+            vec!["c3-b2", "c3-b4", "c3-d2", "c3-d4"]);
+    }
+
+
+    #[test]
+    fn flying_king_captures_from_distance_and_lands_on_any_cell_behind()
+    {
+        let board = board(Variant::Russian, &["Ka1"], &["d4"]);
+        assert_eq!(
+            paths(&board.all_allowable_moves(&PieceColor::White)),
+            vec!["a1-e5", "a1-f6", "a1-g7", "a1-h8"]);
+    }
+
+
+    #[test]
+    fn flying_king_lands_where_capturing_continues()
+    {
+        let board = board(Variant::Russian, &["Ka1"], &["c3", "g5"]);
+        assert_eq!(paths(&board.all_allowable_moves(&PieceColor::White)), vec!["a1-f6-h4"]);
+    }
+
+
+    #[test]
+    fn capturing_sequence_is_single_move()
+    {
+        let board = board(Variant::Russian, &["c3"], &["d4", "f6"]);
+        let moves = board.all_allowable_moves(&PieceColor::White);
+        assert_eq!(paths(&moves), vec!["c3-e5-g7"]);
+        assert_eq!(moves[0].captured_pieces_positions, vec![position("d4"), position("f6")]);
+    }
+
+
+    #[test]
+    fn man_promoted_during_capture_continues_as_king()
+    {
+        let mut board = board(Variant::Russian, &["f6"], &["e7", "c7"]);
+        let moves = board.all_allowable_moves(&PieceColor::White);
+        assert_eq!(paths(&moves), vec!["f6-d8-a5", "f6-d8-b6"]);
+        board.move_checker_piece(&PieceColor::White, &moves[0].path, &moves[0].captured_pieces_positions);
+        assert!(board.pieces(&PieceColor::White)[0].is_crowned);
+        assert!(board.pieces(&PieceColor::Black).is_empty());
+    }
+
+
+    #[test]
+    fn english_men_do_not_capture_backwards()
+    {
+        let english_board = board(Variant::English, &["c3"], &["b2"]);
+        assert_eq!(paths(&english_board.all_allowable_moves(&PieceColor::White)), vec!["c3-b4", "c3-d4"]);
+        let russian_board = board(Variant::Russian, &["c3"], &["b2"]);
+        assert_eq!(paths(&russian_board.all_allowable_moves(&PieceColor::White)), vec!["c3-a1"]);
+    }
+
+
+    #[test]
+    fn maximum_capture_is_mandatory_in_brazilian()
+    {
+        let white_cells = ["a1", "e3"];
+        let black_cells = ["b2", "f4", "f6"];
+        let brazilian_board = board(Variant::Brazilian, &white_cells, &black_cells);
+        assert_eq!(paths(&brazilian_board.all_allowable_moves(&PieceColor::White)), vec!["e3-g5-e7"]);
+        let russian_board = board(Variant::Russian, &white_cells, &black_cells);
+        assert_eq!(paths(&russian_board.all_allowable_moves(&PieceColor::White)), vec!["a1-c3", "e3-g5-e7"]);
+    }
+
+
+    #[test]
+    fn promotion_during_capture_depends_on_variant()
+    {
+        let mut brazilian_board = board(Variant::Brazilian, &["f6"], &["e7", "c7"]);
+        let moves = brazilian_board.all_allowable_moves(&PieceColor::White);
+        assert_eq!(paths(&moves), vec!["f6-d8-b6"]);
+        brazilian_board.move_checker_piece(&PieceColor::White, &moves[0].path, &moves[0].captured_pieces_positions);
+        assert!(!brazilian_board.pieces(&PieceColor::White)[0].is_crowned);
+
+        let english_board = board(Variant::English, &["f6"], &["e7", "c7"]);
+        assert_eq!(paths(&english_board.all_allowable_moves(&PieceColor::White)), vec!["f6-d8"]);
+    }
+
+
+    #[test]
+    fn international_board_has_ten_lines()
+    {
+        let board = Board::init(Variant::International);
+        assert_eq!(board.size, 10);
+        assert_eq!(board.pieces(&PieceColor::White).len(), 20);
+        assert_eq!(board.pieces(&PieceColor::Black).len(), 20);
+        assert!(board.pieces(&PieceColor::Black).iter().all(|piece| piece.position.line >= 7));
+        assert!(board.is_allowable_position(&position("j10")));
+        assert!(board.is_promotion_line(&PieceColor::White, 10));
+    }
+}
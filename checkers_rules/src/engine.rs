@@ -0,0 +1,217 @@
+//! Alpha-beta search used by the computer player.
+
+use serde::{Deserialize, Serialize};
+
+use std::slice::Iter;
+
+use crate::board::Board;
+use crate::game::Game;
+use crate::types::{PieceColor, GameData};
+use self::EngineLevel::*;
+
+
+const WIN_SCORE: i32 = 100_000;
+const MAN_VALUE: i32 = 100;
+const KING_VALUE: i32 = 300;
+/// Bonus for every line the man has advanced towards the promotion line
+const ADVANCE_VALUE: i32 = 3;
+
+
+/// Difficulty of the computer player defined by the depth of the search.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum EngineLevel
+{
+    Easy,
+    Medium,
+    Hard,
+}
+
+
+impl EngineLevel
+{
+    pub fn as_str(&self) -> &'static str
+    {
+        match self
+        {
+            EngineLevel::Easy => "easy",
+            EngineLevel::Medium => "medium",
+            EngineLevel::Hard => "hard",
+        }
+    }
+
+
+    pub fn iterator() -> Iter<'static, EngineLevel>
+    {
+        static LEVELS: [EngineLevel; 3] = [Easy, Medium, Hard];
+        LEVELS.iter()
+    }
+
+
+    /// Number of plies searched, captures are searched deeper until the position is quiet.
+    pub fn depth(&self) -> u8
+    {
+        match self
+        {
+            EngineLevel::Easy => 2,
+            EngineLevel::Medium => 4,
+            EngineLevel::Hard => 6,
+        }
+    }
+}
+
+
+/// Result of the search: the best move, its expected continuation and the score of the position.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Analysis
+{
+    pub best_move: Option<GameData>,
+    /// Expected continuation starting with the best move
+    pub principal_variation: Vec<GameData>,
+    /// Score in hundredths of a man, positive values favour white
+    pub evaluation: i32,
+}
+
+
+/// Material of the side minus the material of its opponent.
+fn evaluate(board: &Board, color: &PieceColor) -> i32
+{
+    let material = |color: &PieceColor| -> i32
+        {
+            board.pieces(color)
+                .iter()
+                .map(|piece|
+                    {
+                        if piece.is_crowned
+                        {
+                            KING_VALUE
+                        }
+                        else
+                        {
+                            let advance = match color
+                            {
+                                PieceColor::White => piece.position.line - 1,
+                                PieceColor::Black => board.size - piece.position.line,
+                            };
+                            MAN_VALUE + advance as i32 * ADVANCE_VALUE
+                        }
+                    })
+                .sum()
+        };
+    material(color) - material(&color.opposite())
+}
+
+
+/// Score of the position for the side to move together with the expected continuation,
+/// faster wins are scored higher.
+fn negamax(board: &Board, color: &PieceColor, depth: u8, mut alpha: i32, beta: i32) -> (i32, Vec<GameData>)
+{
+    let moves = board.all_allowable_moves(color);
+    if moves.is_empty()
+    {
+        return (-WIN_SCORE - depth as i32, Vec::new());
+    }
+    let is_capturing = !moves[0].captured_pieces_positions.is_empty();
+    if depth == 0 && !is_capturing
+    {
+        return (evaluate(board, color), Vec::new());
+    }
+    let mut best_line = Vec::new();
+    for allowable_move in moves
+    {
+        let mut next_board = board.clone();
+        next_board.move_checker_piece(color, &allowable_move.path, &allowable_move.captured_pieces_positions);
+        let (score, line) = negamax(&next_board, &color.opposite(), depth.saturating_sub(1), -beta, -alpha);
+        let score = -score;
+        if score >= beta
+        {
+            return (score, Vec::new());
+        }
+        if score > alpha || best_line.is_empty()
+        {
+            alpha = alpha.max(score);
+            best_line = vec![GameData
+                {
+                    opponent_piece_color: color.to_owned(),
+                    path: allowable_move.path,
+                    captured_pieces_positions: allowable_move.captured_pieces_positions,
+                }];
+            best_line.extend(line);
+        }
+    }
+    (alpha, best_line)
+}
+
+
+/// Searches the position with the given side to move.
+pub fn analyze(board: &Board, turn: &PieceColor, level: EngineLevel) -> Analysis
+{
+    let (score, principal_variation) = negamax(board, turn, level.depth(), -WIN_SCORE * 2, WIN_SCORE * 2);
+    Analysis
+    {
+        best_move: principal_variation.first().cloned(),
+        principal_variation,
+        evaluation: if *turn == PieceColor::White { score } else { -score },
+    }
+}
+
+
+/// Move of the side to move chosen by the search, none when the game is over.
+pub fn best_move(game: &Game, level: EngineLevel) -> Option<GameData>
+{
+    if game.outcome.is_some()
+    {
+        return None;
+    }
+    analyze(&game.board, &game.turn, level).best_move
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::types::CheckerPosition;
+    use crate::variant::Variant;
+
+
+    fn cells(game_data: &GameData) -> Vec<String>
+    {
+        game_data.path.iter().map(CheckerPosition::notation).collect()
+    }
+
+
+    #[test]
+    fn capture_of_last_piece_is_scored_as_win()
+    {
+        // white man on c3 captures the only black man on d4
+        let game = Game::from_fen(Variant::Russian, "W:W22:B18").unwrap();
+        let analysis = analyze(&game.board, &game.turn, EngineLevel::Easy);
+        assert_eq!(cells(analysis.best_move.as_ref().unwrap()), vec!["c3", "e5"]);
+        assert!(analysis.evaluation >= WIN_SCORE);
+
+        let game = Game::from_fen(Variant::Russian, "B:W22:B18").unwrap();
+        let analysis = analyze(&game.board, &game.turn, EngineLevel::Easy);
+        assert!(analysis.evaluation <= -WIN_SCORE);
+    }
+
+
+    #[test]
+    fn piece_is_not_given_away()
+    {
+        // the step of the white man on c3 to d4 would be answered by the capture from e5
+        let game = Game::from_fen(Variant::Russian, "W:W22:B15,4").unwrap();
+        for level in EngineLevel::iterator()
+        {
+            assert_eq!(cells(&best_move(&game, *level).unwrap()), vec!["c3", "b4"]);
+        }
+    }
+
+
+    #[test]
+    fn finished_game_has_no_best_move()
+    {
+        let game = Game::from_fen(Variant::Russian, "W:W29:B25,22").unwrap();
+        assert!(game.outcome.is_some());
+        assert_eq!(best_move(&game, EngineLevel::Hard), None);
+    }
+}
@@ -0,0 +1,197 @@
+//! Position strings of draughts like `W:W21,22,K30:B1,2,3`: the side to move
+//! and the squares of white and black pieces, crowned pieces are marked with `K`.
+//! Squares are numbered the same way as in PDN.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::board::Board;
+use crate::pdn::{square_number, square_position};
+use crate::types::{PieceColor, CheckerPiece};
+use crate::variant::Variant;
+
+
+#[derive(Debug, PartialEq)]
+pub enum FenError
+{
+    IncorrectFormat(String),
+    IncorrectSquare(String),
+    OccupiedSquare(u8),
+    /// A man stands on the line it should have been crowned on
+    UncrownedMan(u8),
+}
+
+
+impl fmt::Display for FenError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self
+        {
+            FenError::IncorrectFormat(fen) => write!(f, "Incorrect position: {}", fen),
+            FenError::IncorrectSquare(square) => write!(f, "Incorrect square: {}", square),
+            FenError::OccupiedSquare(square) => write!(f, "Square {} is occupied twice", square),
+            FenError::UncrownedMan(square) => write!(f, "Man on square {} should be crowned", square),
+        }
+    }
+}
+
+
+fn color_letter(color: &PieceColor) -> char
+{
+    match color
+    {
+        PieceColor::White => 'W',
+        PieceColor::Black => 'B',
+    }
+}
+
+
+fn color_from_letter(letter: &str) -> Option<PieceColor>
+{
+    match letter
+    {
+        "W" => Some(PieceColor::White),
+        "B" => Some(PieceColor::Black),
+        _ => None,
+    }
+}
+
+
+/// Squares of the section, a range of men may be written as `1-12`.
+fn parse_squares(section: &str) -> Result<Vec<(u8, bool)>, FenError>
+{
+    let mut squares = Vec::new();
+    for item in section.split(',').map(|item| item.trim()).filter(|item| !item.is_empty())
+    {
+        let is_crowned = item.starts_with('K');
+        let item = item.trim_start_matches('K');
+        let parse = |square: &str| square.parse::<u8>().map_err(|_| FenError::IncorrectSquare(item.to_owned()));
+        let mut range = item.splitn(2, '-');
+        match (range.next(), range.next())
+        {
+            (Some(first), Some(last)) =>
+                {
+                    for square in parse(first)?..=parse(last)?
+                    {
+                        squares.push((square, is_crowned));
+                    }
+                },
+            _ => squares.push((parse(item)?, is_crowned)),
+        }
+    }
+    Ok(squares)
+}
+
+
+impl Board
+{
+    /// Reads the position and the side to move, men are not allowed on their promotion lines.
+    pub fn from_fen(variant: Variant, fen: &str) -> Result<(Board, PieceColor), FenError>
+    {
+        let size = variant.board_size();
+        let fen = fen.trim().trim_end_matches('.');
+        let mut sections = fen.split(':');
+        let turn = sections.next()
+            .and_then(|letter| color_from_letter(letter.trim()))
+            .ok_or_else(|| FenError::IncorrectFormat(fen.to_owned()))?;
+
+        let mut checker_pieces = HashMap::new();
+        checker_pieces.insert(PieceColor::White, Vec::new());
+        checker_pieces.insert(PieceColor::Black, Vec::new());
+        let mut occupied_squares = Vec::new();
+        for section in sections.map(|section| section.trim()).filter(|section| !section.is_empty())
+        {
+            let color = section.get(..1)
+                .and_then(color_from_letter)
+                .ok_or_else(|| FenError::IncorrectFormat(fen.to_owned()))?;
+            for (square, is_crowned) in parse_squares(&section[1..])?
+            {
+                if occupied_squares.contains(&square)
+                {
+                    return Err(FenError::OccupiedSquare(square));
+                }
+                occupied_squares.push(square);
+                let position = square_position(square, size)
+                    .ok_or_else(|| FenError::IncorrectSquare(square.to_string()))?;
+                let promotion_line = if color == PieceColor::White { size } else { 1 };
+                if !is_crowned && position.line == promotion_line
+                {
+                    return Err(FenError::UncrownedMan(square));
+                }
+                let pieces = checker_pieces.get_mut(&color).unwrap();
+                let id = pieces.len() as u8 + 1;
+                pieces.push(CheckerPiece { id, is_crowned, position });
+            }
+        }
+        Ok((Board { checker_pieces, variant, size }, turn))
+    }
+
+
+    pub fn to_fen(&self, turn: &PieceColor) -> String
+    {
+        let mut fen = color_letter(turn).to_string();
+        for color in [PieceColor::White, PieceColor::Black].iter()
+        {
+            let mut squares: Vec<(u8, bool)> = self.pieces(color)
+                .iter()
+                .map(|piece| (square_number(&piece.position, self.size), piece.is_crowned))
+                .collect();
+            squares.sort_unstable();
+            let squares: Vec<String> = squares
+                .iter()
+                .map(|(square, is_crowned)| if *is_crowned { format!("K{}", square) } else { square.to_string() })
+                .collect();
+            fen.push(':');
+            fen.push(color_letter(color));
+            fen.push_str(&squares.join(","));
+        }
+        fen
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::types::CheckerPosition;
+
+
+    #[test]
+    fn initial_position_is_written_by_squares()
+    {
+        let board = Board::init(Variant::Russian);
+        assert_eq!(
+            board.to_fen(&PieceColor::White),
+            "W:W21,22,23,24,25,26,27,28,29,30,31,32:B1,2,3,4,5,6,7,8,9,10,11,12");
+        let (range_board, turn) = Board::from_fen(Variant::Russian, "W:W21-32:B1-12").unwrap();
+        assert_eq!(turn, PieceColor::White);
+        assert_eq!(range_board.position_key(), board.position_key());
+    }
+
+
+    #[test]
+    fn written_position_is_read_back()
+    {
+        let fen = "B:W18,K30:BK3,14";
+        let (board, turn) = Board::from_fen(Variant::Russian, fen).unwrap();
+        assert_eq!(turn, PieceColor::Black);
+        let idx = board.find_position(&PieceColor::White, &CheckerPosition::from_notation("c1").unwrap()).unwrap();
+        assert!(board.pieces(&PieceColor::White)[idx].is_crowned);
+        assert_eq!(board.to_fen(&turn), fen);
+    }
+
+
+    #[test]
+    fn incorrect_positions_are_rejected()
+    {
+        assert_eq!(
+            Board::from_fen(Variant::Russian, "X:W21:B1").err(),
+            Some(FenError::IncorrectFormat("X:W21:B1".to_owned())));
+        assert_eq!(Board::from_fen(Variant::Russian, "W:W33:B1").err(), Some(FenError::IncorrectSquare("33".to_owned())));
+        assert_eq!(Board::from_fen(Variant::Russian, "W:W21:B21").err(), Some(FenError::OccupiedSquare(21)));
+        assert_eq!(Board::from_fen(Variant::Russian, "W:W1:B32").err(), Some(FenError::UncrownedMan(1)));
+        assert!(Board::from_fen(Variant::Russian, "W:WK1:BK32").is_ok());
+    }
+}
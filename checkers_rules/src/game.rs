@@ -0,0 +1,563 @@
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+use crate::board::{Board, PositionKey};
+use crate::fen::FenError;
+use crate::types::{PieceColor, GameData};
+use crate::variant::Variant;
+
+
+/// Number of successive moves of crowned pieces without capturing, counted for both sides,
+/// after which the game is drawn (15 moves of every side).
+const KINGS_MOVES_LIMIT: u8 = 30;
+/// Number of occurrences of the same position with the same side to move which draws the game.
+const REPETITIONS_LIMIT: usize = 3;
+
+
+#[derive(Debug, PartialEq)]
+pub enum MoveError
+{
+    GameIsOver,
+    NotYourTurn,
+    IllegalMove,
+    NoDrawOffer,
+    NoTakebackRequest,
+    NoMoveToTakeBack,
+}
+
+
+impl fmt::Display for MoveError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let message = match self
+        {
+            MoveError::GameIsOver => "The game is over.",
+            MoveError::NotYourTurn => "It is not your turn.",
+            MoveError::IllegalMove => "The move is not allowed.",
+            MoveError::NoDrawOffer => "There is no draw offer from the opponent.",
+            MoveError::NoTakebackRequest => "There is no takeback request from the opponent.",
+            MoveError::NoMoveToTakeBack => "There is no move to take back.",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum GameOutcome
+{
+    Win(PieceColor),
+    Draw,
+}
+
+
+impl GameOutcome
+{
+    /// Result of the game in the usual notation, the score of white comes first.
+    pub fn as_result(&self) -> &'static str
+    {
+        match self
+        {
+            GameOutcome::Win(PieceColor::White) => "1-0",
+            GameOutcome::Win(PieceColor::Black) => "0-1",
+            GameOutcome::Draw => "1/2-1/2",
+        }
+    }
+
+
+    pub fn from_result(result: &str) -> Option<GameOutcome>
+    {
+        [GameOutcome::Win(PieceColor::White), GameOutcome::Win(PieceColor::Black), GameOutcome::Draw]
+            .iter()
+            .find(|outcome| outcome.as_result() == result)
+            .cloned()
+    }
+
+
+    /// Points of the player of the color, 1 for a win, 0.5 for a draw and 0 for a loss.
+    pub fn score(&self, color: &PieceColor) -> f64
+    {
+        match self
+        {
+            GameOutcome::Win(winner) if winner == color => 1.0,
+            GameOutcome::Win(_) => 0.0,
+            GameOutcome::Draw => 0.5,
+        }
+    }
+}
+
+
+/// Board together with the order of steps, used to validate incoming moves.
+#[derive(Clone, Debug)]
+pub struct Game
+{
+    pub board: Board,
+    pub turn: PieceColor,
+    pub outcome: Option<GameOutcome>,
+    /// Side which offered a draw, the offer is valid until the opponent's move
+    pub draw_offer: Option<PieceColor>,
+    /// Side which asked to take back its last move, the request is valid until the next move
+    pub takeback_request: Option<PieceColor>,
+    /// Positions after every move together with the side to move
+    positions: Vec<(bool, PositionKey)>,
+    kings_moves: u8,
+    /// Board and the count of kings moves before every move
+    history: Vec<(Board, u8)>,
+}
+
+
+impl Game
+{
+    pub fn new(variant: Variant) -> Self
+    {
+        Game::from_position(Board::init(variant), variant.first_turn())
+    }
+
+
+    /// Game started from the given position, it is over at once when the side to move has no moves.
+    pub fn from_position(board: Board, turn: PieceColor) -> Self
+    {
+        let positions = vec![(turn == PieceColor::White, board.position_key())];
+        let mut game = Game
+            {
+                board, turn, positions,
+                outcome: None,
+                draw_offer: None,
+                takeback_request: None,
+                kings_moves: 0,
+                history: Vec::new(),
+            };
+        game.outcome = game.define_outcome();
+        game
+    }
+
+
+    pub fn from_fen(variant: Variant, fen: &str) -> Result<Self, FenError>
+    {
+        let (board, turn) = Board::from_fen(variant, fen)?;
+        Ok(Game::from_position(board, turn))
+    }
+
+
+    /// Current position together with the side to move.
+    pub fn to_fen(&self) -> String
+    {
+        self.board.to_fen(&self.turn)
+    }
+
+
+    /// Checks the move against the rules and applies it to the board.
+    pub fn apply_move(&mut self, game_data: &GameData) -> Result<(), MoveError>
+    {
+        self.check_is_not_over()?;
+
+        let color = &game_data.opponent_piece_color;
+        if color != &self.turn
+        {
+            return Err(MoveError::NotYourTurn);
+        }
+
+        let previous_position = game_data.path.first().ok_or(MoveError::IllegalMove)?;
+        let allowable_moves = self.board.allowable_moves(color, previous_position)
+            .ok_or(MoveError::IllegalMove)?;
+        if !allowable_moves
+            .iter()
+            .any(|allowable_move|
+                allowable_move.path == game_data.path &&
+                    allowable_move.captured_pieces_positions == game_data.captured_pieces_positions)
+        {
+            return Err(MoveError::IllegalMove);
+        }
+
+        let idx = self.board.find_position(color, previous_position).ok_or(MoveError::IllegalMove)?;
+        let is_kings_move = self.board.pieces(color)[idx].is_crowned && game_data.captured_pieces_positions.is_empty();
+
+        self.history.push((self.board.clone(), self.kings_moves));
+        self.board.move_checker_piece(color, &game_data.path, &game_data.captured_pieces_positions);
+        // the move declines the draw offer of the opponent, the own one stays valid
+        if self.draw_offer.as_ref() == Some(&color.opposite())
+        {
+            self.draw_offer = None;
+        }
+        // the number of plies to take back is known for the position of the request only
+        self.takeback_request = None;
+        self.turn = self.turn.opposite();
+        self.kings_moves = if is_kings_move { self.kings_moves + 1 } else { 0 };
+        self.positions.push((self.turn == PieceColor::White, self.board.position_key()));
+        self.outcome = self.define_outcome();
+        Ok(())
+    }
+
+
+    fn check_is_not_over(&self) -> Result<(), MoveError>
+    {
+        if self.outcome.is_some()
+        {
+            Err(MoveError::GameIsOver)
+        }
+        else { Ok(()) }
+    }
+
+
+    pub fn resign(&mut self, color: &PieceColor) -> Result<(), MoveError>
+    {
+        self.check_is_not_over()?;
+        self.outcome = Some(GameOutcome::Win(color.opposite()));
+        Ok(())
+    }
+
+
+    /// The player whose time is over loses the game.
+    pub fn lose_on_time(&mut self, color: &PieceColor) -> Result<(), MoveError>
+    {
+        self.resign(color)
+    }
+
+
+    pub fn offer_draw(&mut self, color: &PieceColor) -> Result<(), MoveError>
+    {
+        self.check_is_not_over()?;
+        self.draw_offer = Some(color.to_owned());
+        Ok(())
+    }
+
+
+    /// Only the draw offered by the opponent can be accepted or declined.
+    pub fn accept_draw(&mut self, color: &PieceColor) -> Result<(), MoveError>
+    {
+        self.check_is_not_over()?;
+        if self.draw_offer != Some(color.opposite())
+        {
+            return Err(MoveError::NoDrawOffer);
+        }
+        self.draw_offer = None;
+        self.outcome = Some(GameOutcome::Draw);
+        Ok(())
+    }
+
+
+    pub fn decline_draw(&mut self, color: &PieceColor) -> Result<(), MoveError>
+    {
+        self.check_is_not_over()?;
+        if self.draw_offer != Some(color.opposite())
+        {
+            return Err(MoveError::NoDrawOffer);
+        }
+        self.draw_offer = None;
+        Ok(())
+    }
+
+
+    /// Number of plies taken back for the side: its last move and the opponent's reply when it is made.
+    fn takeback_plies(&self, color: &PieceColor) -> usize
+    {
+        if self.turn == *color { 2 } else { 1 }
+    }
+
+
+    pub fn request_takeback(&mut self, color: &PieceColor) -> Result<(), MoveError>
+    {
+        self.check_is_not_over()?;
+        if self.takeback_plies(color) > self.history.len()
+        {
+            return Err(MoveError::NoMoveToTakeBack);
+        }
+        self.takeback_request = Some(color.to_owned());
+        Ok(())
+    }
+
+
+    /// Only the takeback requested by the opponent can be accepted, the number of taken back plies is returned.
+    pub fn accept_takeback(&mut self, color: &PieceColor) -> Result<usize, MoveError>
+    {
+        self.check_is_not_over()?;
+        if self.takeback_request != Some(color.opposite())
+        {
+            return Err(MoveError::NoTakebackRequest);
+        }
+        let plies = self.takeback_plies(&color.opposite());
+        self.take_back(plies)?;
+        Ok(plies)
+    }
+
+
+    pub fn decline_takeback(&mut self, color: &PieceColor) -> Result<(), MoveError>
+    {
+        self.check_is_not_over()?;
+        if self.takeback_request != Some(color.opposite())
+        {
+            return Err(MoveError::NoTakebackRequest);
+        }
+        self.takeback_request = None;
+        Ok(())
+    }
+
+
+    /// Restores the position before the last plies, offers and requests are dropped.
+    pub fn take_back(&mut self, plies: usize) -> Result<(), MoveError>
+    {
+        if plies > self.history.len()
+        {
+            return Err(MoveError::NoMoveToTakeBack);
+        }
+        for _ in 0..plies
+        {
+            if let Some((board, kings_moves)) = self.history.pop()
+            {
+                self.board = board;
+                self.kings_moves = kings_moves;
+                self.turn = self.turn.opposite();
+                self.positions.pop();
+            }
+        }
+        self.draw_offer = None;
+        self.takeback_request = None;
+        self.outcome = self.define_outcome();
+        Ok(())
+    }
+
+
+    /// The side to move loses when it has no moves, the game is drawn when the same position
+    /// is repeated three times or crowned pieces moved too long without capturing.
+    fn define_outcome(&self) -> Option<GameOutcome>
+    {
+        if !self.board.has_allowable_moves(&self.turn)
+        {
+            return Some(GameOutcome::Win(self.turn.opposite()));
+        }
+        let last_position = self.positions.last()?;
+        let repetitions = self.positions.iter().filter(|position| *position == last_position).count();
+        if repetitions >= REPETITIONS_LIMIT || self.kings_moves >= KINGS_MOVES_LIMIT
+        {
+            Some(GameOutcome::Draw)
+        }
+        else { None }
+    }
+}
+
+
+impl Default for Game
+{
+    fn default() -> Self
+    {
+        Game::new(Variant::default())
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::types::CheckerPosition;
+
+
+    #[test]
+    fn first_move_depends_on_variant()
+    {
+        assert_eq!(Game::new(Variant::English).turn, PieceColor::Black);
+        assert_eq!(Game::new(Variant::Pool).turn, PieceColor::Black);
+        assert_eq!(Game::new(Variant::Russian).turn, PieceColor::White);
+        assert_eq!(Game::new(Variant::International).turn, PieceColor::White);
+        assert_eq!(Game::default().board.variant, Variant::Russian);
+    }
+
+
+    #[test]
+    fn outcome_is_parsed_from_result()
+    {
+        for outcome in &[GameOutcome::Win(PieceColor::White), GameOutcome::Win(PieceColor::Black), GameOutcome::Draw]
+        {
+            assert_eq!(GameOutcome::from_result(outcome.as_result()).as_ref(), Some(outcome));
+        }
+        assert_eq!(GameOutcome::from_result("0-0"), None);
+        assert_eq!(GameOutcome::from_result("*"), None);
+    }
+
+
+    #[test]
+    fn outcome_gives_score_to_each_side()
+    {
+        let white_win = GameOutcome::Win(PieceColor::White);
+        assert_eq!(white_win.score(&PieceColor::White), 1.0);
+        assert_eq!(white_win.score(&PieceColor::Black), 0.0);
+        assert_eq!(GameOutcome::Draw.score(&PieceColor::White), 0.5);
+        assert_eq!(GameOutcome::Draw.score(&PieceColor::Black), 0.5);
+    }
+
+
+    /// Move along the cells in the notation, e.g. `["c3", "e5"]`.
+    fn game_data(color: PieceColor, cells: &[&str], captured_cells: &[&str]) -> GameData
+    {
+        let positions = |cells: &[&str]| cells
+            .iter()
+            .map(|cell| CheckerPosition::from_notation(cell).unwrap())
+            .collect();
+        GameData { opponent_piece_color: color, path: positions(cells), captured_pieces_positions: positions(captured_cells) }
+    }
+
+
+    #[test]
+    fn side_without_moves_loses()
+    {
+        // white man on a1 is blocked by black men on b2 and c3
+        let game = Game::from_fen(Variant::Russian, "W:W29:B25,22").unwrap();
+        assert_eq!(game.outcome, Some(GameOutcome::Win(PieceColor::Black)));
+    }
+
+
+    #[test]
+    fn third_repetition_draws_game()
+    {
+        // white king on a1 and black king on h2
+        let mut game = Game::from_fen(Variant::Russian, "W:WK29:BK28").unwrap();
+        let cycle = [
+            game_data(PieceColor::White, &["a1", "b2"], &[]),
+            game_data(PieceColor::Black, &["h2", "g1"], &[]),
+            game_data(PieceColor::White, &["b2", "a1"], &[]),
+            game_data(PieceColor::Black, &["g1", "h2"], &[]),
+        ];
+        for _ in 0..2
+        {
+            for game_data in cycle.iter()
+            {
+                assert_eq!(game.outcome, None);
+                game.apply_move(game_data).unwrap();
+            }
+        }
+        assert_eq!(game.outcome, Some(GameOutcome::Draw));
+        assert_eq!(game.apply_move(&cycle[0]), Err(MoveError::GameIsOver));
+    }
+
+
+    #[test]
+    fn long_kings_moves_without_capturing_draw_game()
+    {
+        let mut game = Game::from_fen(Variant::Russian, "W:WK29,31:BK28").unwrap();
+        game.kings_moves = KINGS_MOVES_LIMIT - 2;
+        // a move of a man starts the count again
+        game.apply_move(&game_data(PieceColor::White, &["e1", "f2"], &[])).unwrap();
+        game.apply_move(&game_data(PieceColor::Black, &["h2", "g1"], &[])).unwrap();
+        assert_eq!(game.kings_moves, 1);
+
+        game.kings_moves = KINGS_MOVES_LIMIT - 1;
+        game.apply_move(&game_data(PieceColor::White, &["a1", "b2"], &[])).unwrap();
+        assert_eq!(game.outcome, Some(GameOutcome::Draw));
+    }
+
+
+    #[test]
+    fn draw_offer_is_accepted_only_from_opponent()
+    {
+        let mut game = Game::new(Variant::Russian);
+        assert_eq!(game.accept_draw(&PieceColor::White), Err(MoveError::NoDrawOffer));
+        game.offer_draw(&PieceColor::White).unwrap();
+        assert_eq!(game.accept_draw(&PieceColor::White), Err(MoveError::NoDrawOffer));
+        game.accept_draw(&PieceColor::Black).unwrap();
+        assert_eq!(game.outcome, Some(GameOutcome::Draw));
+        assert_eq!(game.offer_draw(&PieceColor::White), Err(MoveError::GameIsOver));
+    }
+
+
+    #[test]
+    fn draw_offer_stays_valid_after_own_move()
+    {
+        let mut game = Game::new(Variant::Russian);
+        game.offer_draw(&PieceColor::White).unwrap();
+        game.apply_move(&game_data(PieceColor::White, &["c3", "d4"], &[])).unwrap();
+        assert_eq!(game.draw_offer, Some(PieceColor::White));
+        game.accept_draw(&PieceColor::Black).unwrap();
+        assert_eq!(game.outcome, Some(GameOutcome::Draw));
+    }
+
+
+    #[test]
+    fn draw_offer_is_declined_by_opponent_move()
+    {
+        let mut game = Game::new(Variant::Russian);
+        game.apply_move(&game_data(PieceColor::White, &["c3", "d4"], &[])).unwrap();
+        game.offer_draw(&PieceColor::White).unwrap();
+        game.apply_move(&game_data(PieceColor::Black, &["f6", "g5"], &[])).unwrap();
+        assert_eq!(game.draw_offer, None);
+        assert_eq!(game.accept_draw(&PieceColor::Black), Err(MoveError::NoDrawOffer));
+    }
+
+
+    #[test]
+    fn resigned_side_loses()
+    {
+        let mut game = Game::new(Variant::Russian);
+        game.resign(&PieceColor::Black).unwrap();
+        assert_eq!(game.outcome, Some(GameOutcome::Win(PieceColor::White)));
+        assert_eq!(game.resign(&PieceColor::White), Err(MoveError::GameIsOver));
+    }
+
+
+    #[test]
+    fn takeback_restores_previous_position()
+    {
+        let mut game = Game::new(Variant::Russian);
+        let initial_fen = game.to_fen();
+        assert_eq!(game.request_takeback(&PieceColor::White), Err(MoveError::NoMoveToTakeBack));
+
+        game.apply_move(&game_data(PieceColor::White, &["c3", "d4"], &[])).unwrap();
+        game.request_takeback(&PieceColor::White).unwrap();
+        assert_eq!(game.accept_takeback(&PieceColor::White), Err(MoveError::NoTakebackRequest));
+        assert_eq!(game.accept_takeback(&PieceColor::Black), Ok(1));
+        assert_eq!(game.to_fen(), initial_fen);
+        assert_eq!(game.turn, PieceColor::White);
+    }
+
+
+    #[test]
+    fn takeback_after_opponent_reply_returns_both_moves()
+    {
+        let mut game = Game::new(Variant::Russian);
+        let initial_fen = game.to_fen();
+        game.apply_move(&game_data(PieceColor::White, &["c3", "d4"], &[])).unwrap();
+        game.apply_move(&game_data(PieceColor::Black, &["f6", "g5"], &[])).unwrap();
+        game.request_takeback(&PieceColor::White).unwrap();
+        assert_eq!(game.accept_takeback(&PieceColor::Black), Ok(2));
+        assert_eq!(game.to_fen(), initial_fen);
+    }
+
+
+    #[test]
+    fn takeback_request_is_declined_by_opponent_move()
+    {
+        let mut game = Game::new(Variant::Russian);
+        game.apply_move(&game_data(PieceColor::White, &["c3", "d4"], &[])).unwrap();
+        game.request_takeback(&PieceColor::White).unwrap();
+        game.apply_move(&game_data(PieceColor::Black, &["f6", "g5"], &[])).unwrap();
+        assert_eq!(game.takeback_request, None);
+        assert_eq!(game.accept_takeback(&PieceColor::Black), Err(MoveError::NoTakebackRequest));
+    }
+
+
+    #[test]
+    fn takeback_request_is_dropped_by_own_move()
+    {
+        let mut game = Game::new(Variant::Russian);
+        game.apply_move(&game_data(PieceColor::White, &["c3", "d4"], &[])).unwrap();
+        game.apply_move(&game_data(PieceColor::Black, &["f6", "g5"], &[])).unwrap();
+        game.request_takeback(&PieceColor::White).unwrap();
+        game.apply_move(&game_data(PieceColor::White, &["g3", "h4"], &[])).unwrap();
+        assert_eq!(game.takeback_request, None);
+        assert_eq!(game.accept_takeback(&PieceColor::Black), Err(MoveError::NoTakebackRequest));
+    }
+
+
+    #[test]
+    fn takeback_of_finishing_move_resumes_game()
+    {
+        // the capture of the last black man finishes the game
+        let mut game = Game::from_fen(Variant::Russian, "W:W22:B18").unwrap();
+        game.apply_move(&game_data(PieceColor::White, &["c3", "e5"], &["d4"])).unwrap();
+        assert_eq!(game.outcome, Some(GameOutcome::Win(PieceColor::White)));
+        game.take_back(1).unwrap();
+        assert_eq!(game.outcome, None);
+        assert_eq!(game.board.pieces(&PieceColor::Black).len(), 1);
+    }
+}
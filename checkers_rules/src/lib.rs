@@ -0,0 +1,20 @@
+//! Checkers rules shared by the game server and the Yew client,
+//! so both sides of the game follow exactly the same rules.
+
+mod types;
+mod variant;
+mod board;
+mod game;
+mod time_control;
+mod pdn;
+mod fen;
+mod engine;
+
+pub use types::{PieceColor, CheckerPosition, CheckerPiece, GameData, AllowableMove};
+pub use variant::Variant;
+pub use board::Board;
+pub use game::{Game, GameOutcome, MoveError};
+pub use time_control::{TimeControl, ClockData};
+pub use pdn::{PdnGame, PdnMove, PdnError, square_number, square_position};
+pub use fen::FenError;
+pub use engine::{EngineLevel, Analysis, analyze, best_move};
@@ -0,0 +1,338 @@
+//! Portable Draughts Notation. Dark cells are numbered line by line
+//! starting from the upper left corner of the board seen by white.
+
+use std::fmt;
+
+use crate::game::Game;
+use crate::types::{CheckerPosition, GameData};
+use crate::variant::Variant;
+
+
+const RESULTS: [&str; 7] = ["1-0", "0-1", "1/2-1/2", "2-0", "0-2", "1-1", "*"];
+/// Rules of international draughts are used when the `GameType` tag is absent
+const DEFAULT_GAME_TYPE: u8 = 20;
+
+
+#[derive(Debug, PartialEq)]
+pub enum PdnError
+{
+    IncorrectTag(String),
+    IncorrectMove(String),
+    IllegalMove(String),
+    UnknownGameType(String),
+    IncorrectPosition(String),
+}
+
+
+impl fmt::Display for PdnError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self
+        {
+            PdnError::IncorrectTag(tag) => write!(f, "Incorrect tag: {}", tag),
+            PdnError::IncorrectMove(pdn_move) => write!(f, "Incorrect move: {}", pdn_move),
+            PdnError::IllegalMove(pdn_move) => write!(f, "The move is not allowed: {}", pdn_move),
+            PdnError::UnknownGameType(game_type) => write!(f, "Unknown game type: {}", game_type),
+            PdnError::IncorrectPosition(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+
+pub fn square_number(position: &CheckerPosition, size: u8) -> u8
+{
+    (size - position.line) * size / 2 + (position.column - 1) / 2 + 1
+}
+
+
+pub fn square_position(number: u8, size: u8) -> Option<CheckerPosition>
+{
+    if number < 1 || number > size * size / 2
+    {
+        return None;
+    }
+    let cells_in_line = size / 2;
+    let line = size - (number - 1) / cells_in_line;
+    let column = (number - 1) % cells_in_line * 2 + if line % 2 == 0 { 2 } else { 1 };
+    Some(CheckerPosition { column, line })
+}
+
+
+/// Squares passed by the piece, captures are written with `x` between the squares.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdnMove
+{
+    pub squares: Vec<u8>,
+    pub is_capture: bool,
+}
+
+
+impl PdnMove
+{
+    pub fn parse(text: &str) -> Result<Self, PdnError>
+    {
+        let is_capture = text.contains('x');
+        let squares = text
+            .split(|c| c == '-' || c == 'x')
+            .map(|square| square.parse::<u8>())
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| PdnError::IncorrectMove(text.to_owned()))?;
+        if squares.len() < 2
+        {
+            return Err(PdnError::IncorrectMove(text.to_owned()));
+        }
+        Ok(PdnMove { squares, is_capture })
+    }
+}
+
+
+impl fmt::Display for PdnMove
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let separator = if self.is_capture { "x" } else { "-" };
+        let squares: Vec<String> = self.squares.iter().map(|square| square.to_string()).collect();
+        write!(f, "{}", squares.join(separator))
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdnGame
+{
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<PdnMove>,
+    pub result: String,
+}
+
+
+impl PdnGame
+{
+    pub fn tag(&self, name: &str) -> Option<&str>
+    {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+
+
+    pub fn variant(&self) -> Result<Variant, PdnError>
+    {
+        match self.tag("GameType")
+        {
+            Some(game_type) =>
+                {
+                    // the type may be followed by the board description, e.g. "20,W,10,10,N2,0"
+                    game_type.split(',').next()
+                        .and_then(|number| number.trim().parse::<u8>().ok())
+                        .and_then(Variant::from_game_type)
+                        .ok_or_else(|| PdnError::UnknownGameType(game_type.to_owned()))
+                },
+            None => Ok(Variant::from_game_type(DEFAULT_GAME_TYPE).unwrap()),
+        }
+    }
+
+
+    /// Reads the first game of the text, comments and move numbers are skipped.
+    pub fn parse(text: &str) -> Result<Self, PdnError>
+    {
+        let mut tags = Vec::new();
+        let mut movetext = String::new();
+        for line in text.lines().map(|line| line.trim())
+        {
+            if line.starts_with('[')
+            {
+                let tag = line.trim_start_matches('[').trim_end_matches(']');
+                let mut parts = tag.splitn(2, ' ');
+                let name = parts.next().filter(|name| !name.is_empty())
+                    .ok_or_else(|| PdnError::IncorrectTag(line.to_owned()))?;
+                let value = parts.next().ok_or_else(|| PdnError::IncorrectTag(line.to_owned()))?;
+                tags.push((name.to_owned(), value.trim().trim_matches('"').to_owned()));
+            }
+            else
+            {
+                movetext.push_str(line);
+                movetext.push(' ');
+            }
+        }
+
+        let mut is_comment = false;
+        let movetext: String = movetext
+            .chars()
+            .filter(|c|
+                {
+                    match c
+                    {
+                        '{' => is_comment = true,
+                        '}' => { is_comment = false; return false; },
+                        _ => (),
+                    }
+                    !is_comment
+                })
+            .collect();
+
+        let mut moves = Vec::new();
+        let mut result = "*".to_owned();
+        for token in movetext.split_whitespace()
+        {
+            if RESULTS.contains(&token)
+            {
+                result = token.to_owned();
+                break;
+            }
+            // move numbers are written as "1." for white and "1..." for black
+            let token = token.rsplit('.').next().unwrap_or("").trim_end_matches(|c| c == '!' || c == '?');
+            if !token.is_empty()
+            {
+                moves.push(PdnMove::parse(token)?);
+            }
+        }
+
+        Ok(PdnGame { tags, moves, result })
+    }
+
+
+    /// Plays all moves by the rules of the game from the initial position or the position
+    /// of the `FEN` tag, every move is found among allowable moves by its first, last and intermediate squares.
+    pub fn replay(&self) -> Result<(Game, Vec<GameData>), PdnError>
+    {
+        let variant = self.variant()?;
+        let mut game = match self.tag("FEN")
+        {
+            Some(fen) => Game::from_fen(variant, fen).map_err(|e| PdnError::IncorrectPosition(e.to_string()))?,
+            None => Game::new(variant),
+        };
+        let mut moves = Vec::new();
+        for pdn_move in &self.moves
+        {
+            let size = game.board.size;
+            let positions = pdn_move.squares
+                .iter()
+                .map(|square| square_position(*square, size))
+                .collect::<Option<Vec<CheckerPosition>>>()
+                .ok_or_else(|| PdnError::IncorrectMove(pdn_move.to_string()))?;
+            let color = game.turn.to_owned();
+            let allowable_move = game.board.allowable_moves(&color, &positions[0])
+                .and_then(|allowable_moves| allowable_moves
+                    .into_iter()
+                    .find(|allowable_move|
+                        {
+                            let mut path = allowable_move.path.iter();
+                            allowable_move.path.last() == positions.last() &&
+                                positions.iter().all(|position| path.any(|passed| passed == position))
+                        }))
+                .ok_or_else(|| PdnError::IllegalMove(pdn_move.to_string()))?;
+            let game_data = GameData
+                {
+                    opponent_piece_color: color,
+                    path: allowable_move.path,
+                    captured_pieces_positions: allowable_move.captured_pieces_positions,
+                };
+            game.apply_move(&game_data).map_err(|_| PdnError::IllegalMove(pdn_move.to_string()))?;
+            moves.push(game_data);
+        }
+        Ok((game, moves))
+    }
+}
+
+
+impl fmt::Display for PdnGame
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        for (name, value) in &self.tags
+        {
+            writeln!(f, "[{} \"{}\"]", name, value)?;
+        }
+        writeln!(f)?;
+        for (idx, pdn_move) in self.moves.iter().enumerate()
+        {
+            if idx % 2 == 0
+            {
+                write!(f, "{}. ", idx / 2 + 1)?;
+            }
+            write!(f, "{} ", pdn_move)?;
+        }
+        writeln!(f, "{}", self.result)
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::types::PieceColor;
+
+
+    #[test]
+    fn squares_are_numbered_from_upper_left_corner()
+    {
+        assert_eq!(square_position(1, 8), CheckerPosition::from_notation("b8"));
+        assert_eq!(square_position(29, 8), CheckerPosition::from_notation("a1"));
+        assert_eq!(square_position(46, 10), CheckerPosition::from_notation("a1"));
+        assert_eq!(square_position(33, 8), None);
+        for size in [8, 10].iter()
+        {
+            for number in 1..=size * size / 2
+            {
+                assert_eq!(square_number(&square_position(number, *size).unwrap(), *size), number);
+            }
+        }
+    }
+
+
+    #[test]
+    fn moves_are_parsed_with_all_squares()
+    {
+        assert_eq!(PdnMove::parse("11-15"), Ok(PdnMove { squares: vec![11, 15], is_capture: false }));
+        assert_eq!(PdnMove::parse("22x15x8"), Ok(PdnMove { squares: vec![22, 15, 8], is_capture: true }));
+        assert_eq!(PdnMove::parse("22x8").unwrap().to_string(), "22x8");
+        assert_eq!(PdnMove::parse("11"), Err(PdnError::IncorrectMove("11".to_owned())));
+        assert_eq!(PdnMove::parse("a-b"), Err(PdnError::IncorrectMove("a-b".to_owned())));
+    }
+
+
+    #[test]
+    fn written_game_is_read_back()
+    {
+        let text = "[White \"alice\"]\n[Black \"bob\"]\n[GameType \"20\"]\n\n\
+            1. 32-28 {opening} 19-23! 2. 28x19 14x23 1-0\n";
+        let pdn_game = PdnGame::parse(text).unwrap();
+        assert_eq!(pdn_game.tag("White"), Some("alice"));
+        assert_eq!(pdn_game.variant(), Ok(Variant::International));
+        assert_eq!(pdn_game.moves.len(), 4);
+        assert_eq!(pdn_game.result, "1-0");
+        assert_eq!(PdnGame::parse(&pdn_game.to_string()), Ok(pdn_game));
+    }
+
+
+    #[test]
+    fn english_game_is_replayed_from_black_move()
+    {
+        let pdn_game = PdnGame::parse("[GameType \"21\"]\n1. 11-15 23-19 *").unwrap();
+        let (game, moves) = pdn_game.replay().unwrap();
+        assert_eq!(moves[0].opponent_piece_color, PieceColor::Black);
+        assert_eq!(game.turn, PieceColor::Black);
+    }
+
+
+    #[test]
+    fn capture_is_replayed_from_position_tag()
+    {
+        let pdn_game = PdnGame::parse("[GameType \"25\"]\n[FEN \"W:W22:B18,11\"]\n1. 22x8 1-0").unwrap();
+        let (game, moves) = pdn_game.replay().unwrap();
+        assert_eq!(moves[0].path.len(), 3);
+        assert_eq!(moves[0].captured_pieces_positions.len(), 2);
+        assert!(game.board.pieces(&PieceColor::Black).is_empty());
+    }
+
+
+    #[test]
+    fn illegal_move_is_rejected()
+    {
+        let pdn_game = PdnGame::parse("[GameType \"25\"]\n1. 22-14 *").unwrap();
+        assert_eq!(pdn_game.replay().err(), Some(PdnError::IllegalMove("22-14".to_owned())));
+        let pdn_game = PdnGame::parse("[GameType \"99\"]\n1. 22-18 *").unwrap();
+        assert_eq!(pdn_game.replay().err(), Some(PdnError::UnknownGameType("99".to_owned())));
+    }
+}
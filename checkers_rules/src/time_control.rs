@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::PieceColor;
+
+
+/// Time limits of the game chosen with the invitation.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum TimeControl
+{
+    /// Base time of every player plus the increment added after every move
+    Fischer { base_seconds: u32, increment_seconds: u32 },
+    /// Fixed time for every move, unused time is not saved
+    PerMove { seconds: u32 },
+}
+
+
+impl TimeControl
+{
+    pub fn as_string(&self) -> String
+    {
+        match self
+        {
+            TimeControl::Fischer { base_seconds, increment_seconds } =>
+                format!("{} min + {} s", base_seconds / 60, increment_seconds),
+            TimeControl::PerMove { seconds } => format!("{} s per move", seconds),
+        }
+    }
+
+
+    /// Short name used in stored games and urls, e.g. `fischer_300_3` or `per_move_30`.
+    pub fn as_key(&self) -> String
+    {
+        match self
+        {
+            TimeControl::Fischer { base_seconds, increment_seconds } =>
+                format!("fischer_{}_{}", base_seconds, increment_seconds),
+            TimeControl::PerMove { seconds } => format!("per_move_{}", seconds),
+        }
+    }
+
+
+    pub fn from_key(key: &str) -> Option<TimeControl>
+    {
+        if let Some(seconds) = key.strip_prefix("per_move_")
+        {
+            return seconds.parse().ok().map(|seconds| TimeControl::PerMove { seconds });
+        }
+        let mut parts = key.strip_prefix("fischer_")?.splitn(2, '_');
+        let base_seconds = parts.next()?.parse().ok()?;
+        let increment_seconds = parts.next()?.parse().ok()?;
+        Some(TimeControl::Fischer { base_seconds, increment_seconds })
+    }
+
+
+    /// Time of every player at the start of the game.
+    pub fn initial_millis(&self) -> u64
+    {
+        match self
+        {
+            TimeControl::Fischer { base_seconds, .. } => *base_seconds as u64 * 1000,
+            TimeControl::PerMove { seconds } => *seconds as u64 * 1000,
+        }
+    }
+
+
+    /// Time left to the player after the move which took the given time.
+    pub fn millis_after_move(&self, remaining_millis: u64, spent_millis: u64) -> u64
+    {
+        match self
+        {
+            TimeControl::Fischer { increment_seconds, .. } =>
+                remaining_millis.saturating_sub(spent_millis) + *increment_seconds as u64 * 1000,
+            TimeControl::PerMove { seconds } => *seconds as u64 * 1000,
+        }
+    }
+}
+
+
+/// Remaining time of both players sent after every move,
+/// the clock of the side to move is running.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ClockData
+{
+    pub white_millis: u64,
+    pub black_millis: u64,
+    pub turn: PieceColor,
+}
+
+
+impl ClockData
+{
+    /// The clock of the side to move in the starting position is running.
+    pub fn new(time_control: &TimeControl, turn: PieceColor) -> Self
+    {
+        ClockData
+        {
+            white_millis: time_control.initial_millis(),
+            black_millis: time_control.initial_millis(),
+            turn,
+        }
+    }
+
+
+    pub fn millis(&self, color: &PieceColor) -> u64
+    {
+        match color
+        {
+            PieceColor::White => self.white_millis,
+            PieceColor::Black => self.black_millis,
+        }
+    }
+
+
+    pub fn set_millis(&mut self, color: &PieceColor, millis: u64)
+    {
+        match color
+        {
+            PieceColor::White => self.white_millis = millis,
+            PieceColor::Black => self.black_millis = millis,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    #[test]
+    fn fischer_increment_is_added_after_move()
+    {
+        let time_control = TimeControl::Fischer { base_seconds: 300, increment_seconds: 3 };
+        assert_eq!(time_control.initial_millis(), 300_000);
+        assert_eq!(time_control.millis_after_move(300_000, 10_000), 293_000);
+        assert_eq!(time_control.millis_after_move(1_000, 5_000), 3_000);
+    }
+
+
+    #[test]
+    fn per_move_time_is_not_saved()
+    {
+        let time_control = TimeControl::PerMove { seconds: 30 };
+        assert_eq!(time_control.initial_millis(), 30_000);
+        assert_eq!(time_control.millis_after_move(30_000, 1_000), 30_000);
+    }
+
+
+    #[test]
+    fn clock_starts_with_initial_time_of_both_players()
+    {
+        let mut clock = ClockData::new(&TimeControl::PerMove { seconds: 30 }, PieceColor::Black);
+        assert_eq!(clock.turn, PieceColor::Black);
+        assert_eq!(clock.millis(&PieceColor::White), 30_000);
+        clock.set_millis(&PieceColor::Black, 12_000);
+        assert_eq!(clock.millis(&PieceColor::Black), 12_000);
+        assert_eq!(clock.millis(&PieceColor::White), 30_000);
+    }
+
+
+    #[test]
+    fn key_is_read_back()
+    {
+        let fischer = TimeControl::Fischer { base_seconds: 300, increment_seconds: 3 };
+        let per_move = TimeControl::PerMove { seconds: 30 };
+        assert_eq!(fischer.as_key(), "fischer_300_3");
+        assert_eq!(per_move.as_key(), "per_move_30");
+        assert_eq!(TimeControl::from_key(&fischer.as_key()), Some(fischer));
+        assert_eq!(TimeControl::from_key(&per_move.as_key()), Some(per_move));
+        assert_eq!(TimeControl::from_key("fischer_300"), None);
+        assert_eq!(TimeControl::from_key("blitz"), None);
+    }
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize, Eq, Hash)]
+pub enum PieceColor
+{
+    White,
+    Black,
+}
+
+
+impl PieceColor
+{
+    pub fn opposite(&self) -> Self
+    {
+        if *self == PieceColor::White
+        {
+            PieceColor::Black
+        }
+        else
+        {
+            PieceColor::White
+        }
+    }
+}
+
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+pub struct CheckerPosition
+{
+    pub column: u8,
+    pub line: u8,
+}
+
+
+impl CheckerPosition
+{
+    /// Name of the cell, the letter of the column followed by the number of the line, e.g. "c3".
+    pub fn notation(&self) -> String
+    {
+        format!("{}{}", (b'a' + self.column - 1) as char, self.line)
+    }
+
+
+    pub fn from_notation(notation: &str) -> Option<Self>
+    {
+        let mut chars = notation.chars();
+        let letter = chars.next()?.to_ascii_lowercase();
+        if !letter.is_ascii_lowercase()
+        {
+            return None;
+        }
+        let line = chars.as_str().parse::<u8>().ok()?;
+        Some(CheckerPosition { column: letter as u8 - b'a' + 1, line })
+    }
+}
+
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct CheckerPiece
+{
+    pub id: u8,
+    pub is_crowned: bool,
+    pub position: CheckerPosition,
+}
+
+
+/// A complete move, capturing sequences are sent as a single move.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GameData
+{
+    pub opponent_piece_color: PieceColor,
+    /// Cells passed by the piece, starting with its previous position
+    pub path: Vec<CheckerPosition>,
+    pub captured_pieces_positions: Vec<CheckerPosition>,
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllowableMove
+{
+    pub checker_id: u8,
+    /// Cells passed by the piece, starting with its current position
+    pub path: Vec<CheckerPosition>,
+    pub captured_pieces_positions: Vec<CheckerPosition>,
+}
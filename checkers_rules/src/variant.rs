@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+
+use std::slice::Iter;
+
+use crate::types::PieceColor;
+use self::Variant::*;
+
+
+/// Rule sets the game can be played by.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize, Eq, Hash)]
+pub enum Variant
+{
+    English,
+    Russian,
+    International,
+    Brazilian,
+    Pool,
+}
+
+
+impl Variant
+{
+    pub fn as_str(&self) -> &'static str
+    {
+        match self
+        {
+            Variant::English => "English",
+            Variant::Russian => "Russian",
+            Variant::International => "International",
+            Variant::Brazilian => "Brazilian",
+            Variant::Pool => "Pool",
+        }
+    }
+
+
+    /// Side which makes the first move of the game, black pieces start in English and pool checkers.
+    pub fn first_turn(&self) -> PieceColor
+    {
+        match self
+        {
+            Variant::English | Variant::Pool => PieceColor::Black,
+            _ => PieceColor::White,
+        }
+    }
+
+
+    pub fn iterator() -> Iter<'static, Variant>
+    {
+        static VARIANTS: [Variant; 5] = [English, Russian, International, Brazilian, Pool];
+        VARIANTS.iter()
+    }
+
+
+    pub fn from_name(name: &str) -> Option<Variant>
+    {
+        Variant::iterator().find(|variant| variant.as_str() == name).copied()
+    }
+
+
+    /// Number of the rule set in the `GameType` tag of PDN.
+    pub fn game_type(&self) -> u8
+    {
+        match self
+        {
+            Variant::International => 20,
+            Variant::English => 21,
+            Variant::Pool => 23,
+            Variant::Russian => 25,
+            Variant::Brazilian => 26,
+        }
+    }
+
+
+    pub fn from_game_type(game_type: u8) -> Option<Variant>
+    {
+        Variant::iterator().find(|variant| variant.game_type() == game_type).copied()
+    }
+
+
+    /// Number of cells in every line and column of the board.
+    pub fn board_size(&self) -> u8
+    {
+        match self
+        {
+            Variant::International => 10,
+            _ => 8,
+        }
+    }
+
+
+    /// Number of lines occupied by pieces of every side in the initial position.
+    pub fn pieces_lines(&self) -> u8
+    {
+        match self
+        {
+            Variant::International => 4,
+            _ => 3,
+        }
+    }
+
+
+    pub fn men_capture_backwards(&self) -> bool
+    {
+        *self != Variant::English
+    }
+
+
+    /// Crowned pieces move and capture along the whole diagonal.
+    pub fn has_flying_kings(&self) -> bool
+    {
+        *self != Variant::English
+    }
+
+
+    /// All supported rule sets oblige to capture, a piece which could capture is never huffed.
+    pub fn is_capturing_mandatory(&self) -> bool
+    {
+        true
+    }
+
+
+    /// The sequence capturing the most pieces has to be chosen.
+    pub fn is_maximum_capture_mandatory(&self) -> bool
+    {
+        matches!(self, Variant::International | Variant::Brazilian)
+    }
+
+
+    /// A man reaching the promotion line during capturing is crowned at once
+    /// and continues capturing as a crowned piece.
+    pub fn is_promoted_during_capture(&self) -> bool
+    {
+        *self == Variant::Russian
+    }
+
+
+    /// A man reaching the promotion line is crowned and the move is finished.
+    pub fn is_move_finished_by_promotion(&self) -> bool
+    {
+        *self == Variant::English
+    }
+}
+
+
+impl Default for Variant
+{
+    fn default() -> Self
+    {
+        Variant::Russian
+    }
+}
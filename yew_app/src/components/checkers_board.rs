@@ -1,16 +1,27 @@
-use std::collections::HashMap;
 use yew::prelude::*;
+use yew::services::interval::{IntervalService, IntervalTask};
+use yew::services::storage::{Area, StorageService};
+use yew::services::fetch::{FetchService, FetchTask, Request, Response};
+use yew::format::Json;
+use anyhow::Error;
 use serde_json;
 
 use std::rc::Rc;
+use std::time::Duration;
 
 
+use checkers_rules::{Game, GameOutcome};
+
 use crate::types::{
-    AuthorizedUserResponse, WsRequest, WsResponse, PieceColor, CheckerPiece, GameData, CheckerPosition,
-    AllowableMove
+    AuthorizedUserResponse, WsRequest, WsResponse, PieceColor, GameData, CheckerPosition,
+    AllowableMove, Variant, TimeControl, ClockData, PositionSnapshot, GameRecord, Board, Analysis, AnalysisRequest,
+    Puzzle
 };
-use crate::pages::{GameAction, GAME_NAME, ChatAction};
+use crate::pages::{GameAction, GAME_NAME, GAME_RECORD_KEY, ChatAction};
+use crate::KEY;
+
 
+const CLOCK_TICK: Duration = Duration::from_secs(1);
 
 
 #[derive(Properties, PartialEq, Clone)]
@@ -18,11 +29,30 @@ pub struct Props
 {
     pub user: Rc<Option<AuthorizedUserResponse>>,
     pub is_in_game: bool,
+    /// The game is watched, moves of both players are only shown
+    #[prop_or_default]
+    pub is_spectator: bool,
     pub piece_color: Option<PieceColor>,
+    pub variant: Variant,
+    pub time_control: Option<TimeControl>,
+    /// Hints are turned off in rated games
+    pub is_rated: bool,
     pub send_websocket_data: Callback<WsRequest>,
     pub reset_websocket_game_response: Callback<()>,
     pub websocket_game_response: Option<WsResponse>,
     pub leave_game: Callback<()>,
+    /// Position shown by the game replay, the board is read-only when it is set
+    #[prop_or_default]
+    pub replay_board: Option<Board>,
+    /// Puzzle played on the board, moves of the player are checked by the server
+    #[prop_or_default]
+    pub puzzle: Option<Puzzle>,
+    /// Move of the player in the puzzle
+    #[prop_or_default]
+    pub puzzle_move: Callback<GameData>,
+    /// Reply of the opponent to the last correct move in the puzzle
+    #[prop_or_default]
+    pub puzzle_reply: Option<GameData>,
 }
 
 
@@ -30,67 +60,101 @@ enum GameResult
 {
     Win,
     Lose,
+    Draw,
 }
 
 
 struct State
 {
     piece_move: Vec<CheckerPosition>,
-    checker_pieces: HashMap<PieceColor, Vec<CheckerPiece>>,
+    game: Game,
     is_steps_order_defined: bool,
     is_my_step: bool,
     allowable_moves: Option<Vec<AllowableMove>>,
     game_result: Option<GameResult>,
+    is_draw_offer_sent: bool,
+    is_draw_offer_received: bool,
+    is_takeback_request_sent: bool,
+    is_takeback_request_received: bool,
+    clock: Option<ClockData>,
+    record: GameRecord,
+    /// Analysis of the current position requested by the player
+    hint: Option<Analysis>,
+    /// Number of sessions watching the game
+    spectators: usize,
+    /// The opponent lost the connection and may come back to the game
+    is_opponent_disconnected: bool,
 }
 
 
 impl State
 {
-    fn init() -> Self
+    fn init(variant: Variant, time_control: &Option<TimeControl>) -> Self
     {
-        let mut checker_pieces = HashMap::new();
-        checker_pieces.insert(
-            PieceColor::White,
-            vec![
-                CheckerPiece { id: 1, is_crowned: false, position: CheckerPosition { column: 1, line: 1 } },
-                CheckerPiece { id: 2, is_crowned: false, position: CheckerPosition { column: 1, line: 3 } },
-                CheckerPiece { id: 3, is_crowned: false, position: CheckerPosition { column: 2, line: 2 } },
-                CheckerPiece { id: 4, is_crowned: false, position: CheckerPosition { column: 3, line: 1 } },
-                CheckerPiece { id: 5, is_crowned: false, position: CheckerPosition{ column: 3, line: 3 } },
-                CheckerPiece { id: 6, is_crowned: false, position: CheckerPosition { column: 4, line: 2 } },
-                CheckerPiece { id: 7, is_crowned: false, position: CheckerPosition { column: 5, line: 1 } },
-                CheckerPiece { id: 8, is_crowned: false, position: CheckerPosition { column: 5, line: 3 } },
-                CheckerPiece { id: 9, is_crowned: false, position: CheckerPosition { column: 6, line: 2 } },
-                CheckerPiece { id: 10, is_crowned: false, position: CheckerPosition { column: 7, line: 1 } },
-                CheckerPiece { id: 11, is_crowned: false, position: CheckerPosition { column: 7, line: 3 } },
-                CheckerPiece { id: 12, is_crowned: false, position: CheckerPosition { column: 8, line: 2 } },
-            ],);
-        checker_pieces.insert(
-            PieceColor::Black,
-            vec![
-                CheckerPiece { id: 1, is_crowned: false, position: CheckerPosition { column: 1, line: 7 } },
-                CheckerPiece { id: 2, is_crowned: false, position: CheckerPosition { column: 2, line: 6 } },
-                CheckerPiece { id: 3, is_crowned: false, position: CheckerPosition { column: 2, line: 8 } },
-                CheckerPiece { id: 4, is_crowned: false, position: CheckerPosition { column: 3, line: 7 } },
-                CheckerPiece { id: 5, is_crowned: false, position: CheckerPosition { column: 4, line: 6 } },
-                CheckerPiece { id: 6, is_crowned: false, position: CheckerPosition { column: 4, line: 8 } },
-                CheckerPiece { id: 7, is_crowned: false, position: CheckerPosition { column: 5, line: 7 } },
-                CheckerPiece { id: 8, is_crowned: false, position: CheckerPosition { column: 6, line: 6 } },
-                CheckerPiece { id: 9, is_crowned: false, position: CheckerPosition { column: 6, line: 8 } },
-                CheckerPiece { id: 10, is_crowned: false, position: CheckerPosition { column: 7, line: 7 } },
-                CheckerPiece { id: 11, is_crowned: false, position: CheckerPosition { column: 8, line: 6 } },
-                CheckerPiece { id: 12, is_crowned: false, position: CheckerPosition { column: 8, line: 8 } },
-                ],);
-
+        let game = Game::new(variant);
+        let clock = time_control.as_ref().map(|time_control| ClockData::new(time_control, game.turn.to_owned()));
         State
         {
             piece_move: Vec::new(),
-            checker_pieces,
+            record: GameRecord { variant, initial_fen: game.to_fen(), moves: Vec::new() },
+            game,
             is_steps_order_defined: false,
             is_my_step: false,
             allowable_moves: None,
             game_result: None,
+            is_draw_offer_sent: false,
+            is_draw_offer_received: false,
+            is_takeback_request_sent: false,
+            is_takeback_request_received: false,
+            clock,
+            hint: None,
+            spectators: 0,
+            is_opponent_disconnected: false,
+        }
+    }
+
+
+    /// The puzzle starts from its position, the player moves first.
+    fn from_puzzle(puzzle: &Puzzle) -> Self
+    {
+        let mut state = State::init(puzzle.variant, &None);
+        if let Ok(game) = Game::from_fen(puzzle.variant, &puzzle.fen)
+        {
+            state.record.initial_fen = game.to_fen();
+            state.game = game;
+        }
+        state.is_my_step = true;
+        state.is_steps_order_defined = true;
+        state
+    }
+
+
+    /// The last plies are removed from the board and from the record of the game.
+    fn take_back(&mut self, plies: usize, piece_color: &Option<PieceColor>, storage: &mut StorageService)
+    {
+        if self.game.take_back(plies).is_ok()
+        {
+            let moves_count = self.record.moves.len().saturating_sub(plies);
+            self.record.moves.truncate(moves_count);
+            storage.store(GAME_RECORD_KEY, Json(&self.record));
         }
+        self.is_my_step = piece_color.as_ref() == Some(&self.game.turn);
+        self.piece_move = Vec::new();
+        self.allowable_moves = None;
+        self.hint = None;
+        self.is_draw_offer_sent = false;
+        self.is_draw_offer_received = false;
+        self.is_takeback_request_sent = false;
+        self.is_takeback_request_received = false;
+    }
+
+
+    /// Every accepted move is recorded, so the game can be replayed after it is over.
+    fn record_move(&mut self, game_data: GameData, storage: &mut StorageService)
+    {
+        self.hint = None;
+        self.record.moves.push(game_data);
+        storage.store(GAME_RECORD_KEY, Json(&self.record));
     }
 }
 
@@ -99,7 +163,10 @@ pub struct CheckersBoard
 {
     link: ComponentLink<Self>,
     props: Props,
-    state: State
+    state: State,
+    clock_task: IntervalTask,
+    storage: StorageService,
+    fetch_task: Option<FetchTask>,
 }
 
 
@@ -107,22 +174,22 @@ pub enum Msg
 {
     MoveCheckerPiece(u8, u8),
     LeaveGame,
+    SendGameDecision(GameAction),
+    ClockTick,
+    RequestHint,
+    HintReceived(Result<Analysis, Error>),
+    HintNotReceived(String),
 }
 
 
-fn find_position(checker_pieces: &Vec<CheckerPiece>, position: &CheckerPosition) -> Option<usize>
-{
-    checker_pieces.iter().position(|checker_piece| &checker_piece.position == position)
-}
-
-
-fn is_allowable_position(position: &CheckerPosition) -> bool
+/// Remaining time in the mm:ss format.
+fn format_clock(millis: u64) -> String
 {
-    (1 <= position.column) && (position.column <= 8) && (1 <= position.line) && (position.line <= 8)
+    let seconds = (millis + 999) / 1000;
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
 }
 
 
-
 impl CheckersBoard
 {
     fn view_checker(&self, color: &str) -> Html
@@ -236,6 +303,150 @@ impl CheckersBoard
 
 
 
+    /// The move is sent to be checked, the player waits for the reply of the opponent.
+    fn send_puzzle_move(&mut self, game_data: GameData)
+    {
+        self.state.is_my_step = false;
+        self.state.record.moves.push(game_data.clone());
+        self.props.puzzle_move.emit(game_data);
+    }
+
+
+    /// The player moves again after the reply unless the puzzle is over.
+    fn play_puzzle_reply(&mut self, reply: GameData)
+    {
+        if self.state.game.apply_move(&reply).is_ok()
+        {
+            self.state.record.moves.push(reply);
+        }
+        self.state.is_my_step = self.props.is_in_game;
+    }
+
+
+    /// The engine is asked for the best move in the current position.
+    fn request_hint(&self, token: &str) -> FetchTask
+    {
+        let callback = self.link.callback(
+            move |response: Response<Json<Result<Analysis, Error>>>|
+                {
+                    let (meta, Json(data)) = response.into_parts();
+                    if meta.status.is_success()
+                    {
+                        Msg::HintReceived(data)
+                    }
+                    else
+                    {
+                        Msg::HintNotReceived(meta.status.to_string())
+                    }
+                },
+            );
+        let analysis_request = AnalysisRequest
+            {
+                variant: self.state.game.board.variant,
+                checker_pieces: self.state.game.board.checker_pieces.clone(),
+                turn: self.state.game.turn.to_owned(),
+            };
+        let request = Request::post("/game/analyze")
+            .header("Content-Type", "application/json")
+            .header(KEY, token)
+            .body(Json(&analysis_request))
+            .unwrap();
+        FetchService::fetch(request, callback).unwrap()
+    }
+
+
+    /// Only the player who already moved can ask to take the move back.
+    fn has_own_move(&self) -> bool
+    {
+        self.state.record.moves
+            .iter()
+            .any(|game_data| Some(&game_data.opponent_piece_color) == self.props.piece_color.as_ref())
+    }
+
+
+    /// Whether the cell is on the path of the suggested move.
+    fn is_hint_cell(&self, column: u8, line: u8) -> bool
+    {
+        self.state.hint
+            .as_ref()
+            .and_then(|analysis| analysis.best_move.as_ref())
+            .map_or(false, |best_move| best_move.path.contains(&CheckerPosition { column, line }))
+    }
+
+
+    fn black_cell_class(&self, column: u8, line: u8) -> &'static str
+    {
+        if self.is_hint_cell(column, line) { "cell black hint_cell" } else { "cell black" }
+    }
+
+
+    fn view_hint(&self) -> Html
+    {
+        if self.props.is_rated || self.props.is_spectator || self.state.game_result.is_some() || !self.props.is_in_game
+        {
+            return html! {};
+        }
+        html!
+        {
+            <div class="checkers_hint">
+                <button class="checker_board_header_button"
+                    disabled=!self.state.is_my_step || self.fetch_task.is_some()
+                    onclick=self.link.callback(|_| Msg::RequestHint)>
+                    { "Hint" }
+                </button>
+                {
+                    if let Some(analysis) = &self.state.hint
+                    {
+                        html!
+                        {
+                            <span>
+                                { format!(" Evaluation: {:+.2}", analysis.evaluation as f64 / 100.0) }
+                            </span>
+                        }
+                    }
+                    else { html! {} }
+                }
+            </div>
+        }
+    }
+
+
+    /// Moves of the selected piece which continue the already chosen landing cells.
+    fn continued_moves(&self) -> Vec<&AllowableMove>
+    {
+        if let Some(allowable_moves) = &self.state.allowable_moves
+        {
+            allowable_moves
+                .iter()
+                .filter(|allowable_move| allowable_move.path.starts_with(&self.state.piece_move))
+                .collect()
+        }
+        else { Vec::new() }
+    }
+
+
+    /// Whether the selected piece is able to land on the cell.
+    fn is_allowable_cell(&self, column: u8, line: u8) -> bool
+    {
+        if self.state.piece_move.is_empty()
+        {
+            return false;
+        }
+        self.continued_moves()
+            .iter()
+            .any(|allowable_move|
+                allowable_move.path.len() > self.state.piece_move.len() &&
+                    allowable_move.path[self.state.piece_move.len()] == CheckerPosition { column, line })
+    }
+
+
+    /// Whether the cell is one of the already chosen landing cells of the capturing sequence.
+    fn is_passed_cell(&self, column: u8, line: u8) -> bool
+    {
+        self.state.piece_move.iter().skip(1).any(|position| position == &CheckerPosition { column, line })
+    }
+
+
     fn view_black_cells(&self, column: u8, line: u8) -> Html
     {
         let white_checker: Html =
@@ -292,19 +503,19 @@ impl CheckersBoard
         let black_crowned_checker_active: Html = html! { <div class="checker_my_black_active">{ self.view_crowned_checker("white") }</div> };
         html!
         {
-            if let Some(idx) = self.state.checker_pieces[&PieceColor::White]
+            if let Some(idx) = self.state.game.board.checker_pieces[&PieceColor::White]
                 .iter()
                 .position(|checker_piece| checker_piece.position == CheckerPosition { column, line })
             {
                 html!
                 {
                     <div
-                        class="cell black"
+                        class=self.black_cell_class(column, line)
                         onclick=self.link.callback(move |_| Msg::MoveCheckerPiece(column, line))>
                         {
                             if !self.state.piece_move.is_empty() && (self.state.piece_move[0] == CheckerPosition { column, line })
                             {
-                                if self.state.checker_pieces[&PieceColor::White][idx].is_crowned
+                                if self.state.game.board.checker_pieces[&PieceColor::White][idx].is_crowned
                                 {
                                     white_crowned_checker_active
                                 }
@@ -315,7 +526,7 @@ impl CheckersBoard
                             }
                             else
                             {
-                                if self.state.checker_pieces[&PieceColor::White][idx].is_crowned
+                                if self.state.game.board.checker_pieces[&PieceColor::White][idx].is_crowned
                                 {
                                     white_crowned_checker
                                 }
@@ -328,19 +539,19 @@ impl CheckersBoard
                     </div>
                 }
             }
-            else if let Some(idx) = self.state.checker_pieces[&PieceColor::Black]
+            else if let Some(idx) = self.state.game.board.checker_pieces[&PieceColor::Black]
                 .iter()
                 .position(|checker_piece| checker_piece.position == CheckerPosition { column, line })
             {
                 html!
                 {
                     <div
-                        class="cell black"
+                        class=self.black_cell_class(column, line)
                         onclick=self.link.callback(move |_| Msg::MoveCheckerPiece(column, line))>
                         {
                             if !self.state.piece_move.is_empty() && (self.state.piece_move[0] == CheckerPosition { column, line })
                             {
-                                if self.state.checker_pieces[&PieceColor::Black][idx].is_crowned
+                                if self.state.game.board.checker_pieces[&PieceColor::Black][idx].is_crowned
                                 {
                                     black_crowned_checker_active
                                 }
@@ -351,7 +562,7 @@ impl CheckersBoard
                             }
                             else
                             {
-                                if self.state.checker_pieces[&PieceColor::Black][idx].is_crowned
+                                if self.state.game.board.checker_pieces[&PieceColor::Black][idx].is_crowned
                                 {
                                     black_crowned_checker
                                 }
@@ -364,12 +575,32 @@ impl CheckersBoard
                     </div>
                 }
             }
+            else if self.is_allowable_cell(column, line)
+            {
+                html!
+                {
+                    <div
+                        class="cell black allowable_cell"
+                        onclick=self.link.callback(move |_| Msg::MoveCheckerPiece(column, line))>
+                    </div>
+                }
+            }
+            else if self.is_passed_cell(column, line)
+            {
+                html!
+                {
+                    <div
+                        class="cell black passed_cell"
+                        onclick=self.link.callback(move |_| Msg::MoveCheckerPiece(column, line))>
+                    </div>
+                }
+            }
             else
             {
                 html!
                 {
                     <div
-                        class="cell black"
+                        class=self.black_cell_class(column, line)
                         onclick=self.link.callback(move |_| Msg::MoveCheckerPiece(column, line))>
                     </div>
                 }
@@ -378,8 +609,93 @@ impl CheckersBoard
     }
 
 
+    /// Starts the opponent's clock until the server sends the exact time.
+    fn switch_clock(&mut self)
+    {
+        if let Some(clock) = &mut self.state.clock
+        {
+            clock.turn = clock.turn.opposite();
+        }
+    }
+
+
+    fn view_clock(&self) -> Html
+    {
+        if let Some(clock) = &self.state.clock
+        {
+            html!
+            {
+                <p class="checkers_clock">
+                    { format!("White {} | Black {}",
+                        format_clock(clock.millis(&PieceColor::White)), format_clock(clock.millis(&PieceColor::Black))) }
+                </p>
+            }
+        }
+        else { html! {} }
+    }
+
+
+    /// The game is over as soon as the rules define its outcome.
+    fn update_game_result(&mut self)
+    {
+        self.state.game_result = match &self.state.game.outcome
+        {
+            Some(GameOutcome::Win(color)) if Some(color) == self.props.piece_color.as_ref() => Some(GameResult::Win),
+            Some(GameOutcome::Win(_)) => Some(GameResult::Lose),
+            Some(GameOutcome::Draw) => Some(GameResult::Draw),
+            None => None,
+        };
+        if self.state.game_result.is_some()
+        {
+            self.state.is_my_step = false;
+            self.state.is_draw_offer_sent = false;
+            self.state.is_draw_offer_received = false;
+            self.state.is_takeback_request_sent = false;
+            self.state.is_takeback_request_received = false;
+        }
+    }
+
+
+    /// Spectators see the side to move or the outcome of the game.
+    fn spectator_header_view(&self) -> Html
+    {
+        let status = match &self.state.game.outcome
+        {
+            Some(GameOutcome::Win(PieceColor::White)) => "White wins",
+            Some(GameOutcome::Win(PieceColor::Black)) => "Black wins",
+            Some(GameOutcome::Draw) => "Draw",
+            None if self.state.game.turn == PieceColor::White => "White to move",
+            None => "Black to move",
+        };
+        html!
+        {
+            <div class="checker_board_header_container">
+                <p>{ status }</p>
+                <button class="checker_board_header_button"
+                    onclick=self.link.callback(|_| Msg::LeaveGame)>
+                    { "Stop watching" }
+                </button>
+            </div>
+        }
+    }
+
+
+    fn view_spectators(&self) -> Html
+    {
+        if self.props.is_in_game && self.state.spectators > 0
+        {
+            html! { <p class="checkers_spectators">{ format!("Watching: {}", self.state.spectators) }</p> }
+        }
+        else { html! {} }
+    }
+
+
     fn checkers_board_header_view(&self) -> Html
     {
+        if self.props.is_spectator
+        {
+            return self.spectator_header_view();
+        }
         if let Some(result) = &self.state.game_result
         {
             match result
@@ -412,6 +728,20 @@ impl CheckersBoard
                             </div>
                         }
                     },
+                GameResult::Draw =>
+                    {
+                        html!
+                        {
+                            <div class="checker_board_header_container">
+                                <p>{ "Draw" }</p>
+                                <button class="checker_board_header_button"
+                                    disabled=!self.props.is_in_game
+                                    onclick=self.link.callback(|_| Msg::LeaveGame)>
+                                    { "Quit Game" }
+                                </button>
+                            </div>
+                        }
+                    },
             }
         }
         else
@@ -445,241 +775,83 @@ impl CheckersBoard
                             html! { <p> { "Opponent's move" } </p> }
                         }
                     }
-                    <button class="checker_board_header_button"
-                        disabled=!self.props.is_in_game
-                        onclick=self.link.callback(|_| Msg::LeaveGame)>
-                        { "Surrender" }
-                    </button>
-                </div>
-            }
-        }
-    }
-
-
-    fn can_capturing_checkers(&self) -> Option<Vec<AllowableMove>>
-    {
-        let mut checkers = Vec::new();
-        if let Some(color) = &self.props.piece_color
-        {
-            for checker in self.state.checker_pieces.get(&color).unwrap()
-            {
-                for opponent_checker in self.state.checker_pieces.get(&color.opposite()).unwrap()
-                {
-                    let first_position = CheckerPosition { column: &checker.position.column + 1,  line: &checker.position.line + 1 };
-                    if opponent_checker.position == first_position
                     {
-                        let second_position = CheckerPosition { column: &checker.position.column + 2,  line: &checker.position.line + 2 };
-                        if !self.state.checker_pieces.get(&color).unwrap().iter().any(|piece| piece.position == second_position) &&
-                            !self.state.checker_pieces.get(&color.opposite()).unwrap().iter().any(|piece| piece.position == second_position) &&
-                            is_allowable_position(&second_position)
+                        if self.state.is_opponent_disconnected
                         {
-                            checkers.push(
-                                AllowableMove
-                                {
-                                    checker_id: checker.id,
-                                    captured_piece_position: Some(first_position),
-                                    next_position: second_position
-                                })
+                            html! { <p>{ "Opponent is disconnected, waiting for reconnection" }</p> }
                         }
+                        else { html! {} }
                     }
-                    let first_position = CheckerPosition { column: &checker.position.column - 1,  line: &checker.position.line + 1 };
-                    if opponent_checker.position == first_position
                     {
-                        let second_position = CheckerPosition { column: &checker.position.column - 2,  line: &checker.position.line + 2 };
-                        if !self.state.checker_pieces.get(&color).unwrap().iter().any(|piece| piece.position == second_position) &&
-                            !self.state.checker_pieces.get(&color.opposite()).unwrap().iter().any(|piece| piece.position == second_position) &&
-                            is_allowable_position(&second_position)
+                        if self.state.is_draw_offer_received
                         {
-                            checkers.push(
-                                AllowableMove
-                                {
-                                    checker_id: checker.id,
-                                    captured_piece_position: Some(first_position),
-                                    next_position: second_position
-                                })
+                            html!
+                            {
+                                <>
+                                    <p>{ "Opponent offers a draw" }</p>
+                                    <button class="checker_board_header_button"
+                                        onclick=self.link.callback(|_| Msg::SendGameDecision(GameAction::AcceptDraw))>
+                                        { "Accept" }
+                                    </button>
+                                    <button class="checker_board_header_button"
+                                        onclick=self.link.callback(|_| Msg::SendGameDecision(GameAction::DeclineDraw))>
+                                        { "Decline" }
+                                    </button>
+                                </>
+                            }
                         }
-                    }
-                    let first_position = CheckerPosition { column: &checker.position.column - 1,  line: &checker.position.line - 1 };
-                    if opponent_checker.position == first_position
-                    {
-                        let second_position = CheckerPosition { column: &checker.position.column - 2,  line: &checker.position.line - 2 };
-                        if !self.state.checker_pieces.get(&color).unwrap().iter().any(|piece| piece.position == second_position) &&
-                            !self.state.checker_pieces.get(&color.opposite()).unwrap().iter().any(|piece| piece.position == second_position) &&
-                            is_allowable_position(&second_position)
+                        else
                         {
-                            checkers.push(
-                                AllowableMove
-                                {
-                                    checker_id: checker.id,
-                                    captured_piece_position: Some(first_position),
-                                    next_position: second_position
-                                })
+                            html!
+                            {
+                                <button class="checker_board_header_button"
+                                    disabled=!self.props.is_in_game || self.state.is_draw_offer_sent
+                                    onclick=self.link.callback(|_| Msg::SendGameDecision(GameAction::OfferDraw))>
+                                    { "Offer Draw" }
+                                </button>
+                            }
                         }
                     }
-                    let first_position = CheckerPosition { column: &checker.position.column + 1,  line: &checker.position.line - 1 };
-                    if opponent_checker.position == first_position
                     {
-                        let second_position = CheckerPosition { column: &checker.position.column + 2,  line: &checker.position.line - 2 };
-                        if !self.state.checker_pieces.get(&color).unwrap().iter().any(|piece| piece.position == second_position) &&
-                            !self.state.checker_pieces.get(&color.opposite()).unwrap().iter().any(|piece| piece.position == second_position) &&
-                            is_allowable_position(&second_position)
+                        if self.state.is_takeback_request_received
                         {
-                            checkers.push(
-                                AllowableMove
-                                {
-                                    checker_id: checker.id,
-                                    captured_piece_position: Some(first_position),
-                                    next_position: second_position
-                                })
-                        }
-                    }
-                }
-
-            }
-            if !checkers.is_empty()
-            {
-                Some(checkers)
-            }
-            else { None }
-        }
-        else { None }
-    }
-
-
-    fn can_moving_checkers(&self) -> Option<Vec<AllowableMove>>
-    {
-        let mut checkers = Vec::new();
-        if let Some(color) = &self.props.piece_color
-        {
-            for checker in self.state.checker_pieces.get(&color).unwrap()
-            {
-                if checker.is_crowned
-                {
-                    let second_position = CheckerPosition { column: &checker.position.column + 1,  line: &checker.position.line + 1 };
-                    if !self.state.checker_pieces.get(&color).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        !self.state.checker_pieces.get(&color.opposite()).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        is_allowable_position(&second_position)
-                    {
-                        checkers.push(
-                            AllowableMove
-                            {
-                                checker_id: checker.id,
-                                captured_piece_position: None,
-                                next_position: second_position
-                            })
-                    }
-
-                    let second_position = CheckerPosition { column: &checker.position.column - 1,  line: &checker.position.line + 1 };
-                    if !self.state.checker_pieces.get(&color).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        !self.state.checker_pieces.get(&color.opposite()).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        is_allowable_position(&second_position)
-                    {
-                        checkers.push(
-                            AllowableMove
+                            html!
                             {
-                                checker_id: checker.id,
-                                captured_piece_position: None,
-                                next_position: second_position
-                            })
-                    }
-
-
-                    let second_position = CheckerPosition { column: &checker.position.column - 1,  line: &checker.position.line - 1 };
-                    if !self.state.checker_pieces.get(&color).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        !self.state.checker_pieces.get(&color.opposite()).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        is_allowable_position(&second_position)
-                    {
-                        checkers.push(
-                            AllowableMove
-                            {
-                                checker_id: checker.id,
-                                captured_piece_position: None,
-                                next_position: second_position
-                            })
-                    }
-                    let second_position = CheckerPosition { column: &checker.position.column + 1,  line: &checker.position.line - 1 };
-                    if !self.state.checker_pieces.get(&color).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        !self.state.checker_pieces.get(&color.opposite()).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        is_allowable_position(&second_position)
-                    {
-                        checkers.push(
-                            AllowableMove
-                            {
-                                checker_id: checker.id,
-                                captured_piece_position: None,
-                                next_position: second_position
-                            })
-                    }
-                }
-                else if color == &PieceColor::White
-                {
-                    let second_position = CheckerPosition { column: &checker.position.column + 1,  line: &checker.position.line + 1 };
-                    if !self.state.checker_pieces.get(&color).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        !self.state.checker_pieces.get(&color.opposite()).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        is_allowable_position(&second_position)
-                    {
-                        checkers.push(
-                            AllowableMove
-                            {
-                                checker_id: checker.id,
-                                captured_piece_position: None,
-                                next_position: second_position
-                            })
-                    }
-                    let second_position = CheckerPosition { column: &checker.position.column - 1,  line: &checker.position.line + 1 };
-                    if !self.state.checker_pieces.get(&color).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        !self.state.checker_pieces.get(&color.opposite()).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        is_allowable_position(&second_position)
-                    {
-                        checkers.push(
-                            AllowableMove
-                            {
-                                checker_id: checker.id,
-                                captured_piece_position: None,
-                                next_position: second_position
-                            })
-                    }
-                }
-                else
-                {
-                    let second_position = CheckerPosition { column: &checker.position.column + 1,  line: &checker.position.line - 1 };
-                    if !self.state.checker_pieces.get(&color).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        !self.state.checker_pieces.get(&color.opposite()).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        is_allowable_position(&second_position)
-                    {
-                        checkers.push(
-                            AllowableMove
-                            {
-                                checker_id: checker.id,
-                                captured_piece_position: None,
-                                next_position: second_position
-                            })
-                    }
-                    let second_position = CheckerPosition { column: &checker.position.column - 1,  line: &checker.position.line - 1 };
-                    if !self.state.checker_pieces.get(&color).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        !self.state.checker_pieces.get(&color.opposite()).unwrap().iter().any(|piece| piece.position == second_position) &&
-                        is_allowable_position(&second_position)
-                    {
-                        checkers.push(
-                            AllowableMove
+                                <>
+                                    <p>{ "Opponent asks to take back a move" }</p>
+                                    <button class="checker_board_header_button"
+                                        onclick=self.link.callback(|_| Msg::SendGameDecision(GameAction::AcceptTakeback))>
+                                        { "Accept" }
+                                    </button>
+                                    <button class="checker_board_header_button"
+                                        onclick=self.link.callback(|_| Msg::SendGameDecision(GameAction::DeclineTakeback))>
+                                        { "Decline" }
+                                    </button>
+                                </>
+                            }
+                        }
+                        else
+                        {
+                            html!
                             {
-                                checker_id: checker.id,
-                                captured_piece_position: None,
-                                next_position: second_position
-                            })
+                                <button class="checker_board_header_button"
+                                    disabled=!self.props.is_in_game || self.state.is_takeback_request_sent ||
+                                        !self.has_own_move()
+                                    onclick=self.link.callback(|_| Msg::SendGameDecision(GameAction::RequestTakeback))>
+                                    { "Takeback" }
+                                </button>
+                            }
+                        }
                     }
-                }
-            }
-            if !checkers.is_empty()
-            {
-                Some(checkers)
+                    <button class="checker_board_header_button"
+                        disabled=!self.props.is_in_game
+                        onclick=self.link.callback(|_| Msg::SendGameDecision(GameAction::Resign))>
+                        { "Surrender" }
+                    </button>
+                </div>
             }
-            else { None }
         }
-        else { None }
     }
-
-
 }
 
 
@@ -691,11 +863,25 @@ impl Component for CheckersBoard
 
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self
     {
+        let mut state = match &props.puzzle
+        {
+            Some(puzzle) => State::from_puzzle(puzzle),
+            None => State::init(props.variant, &props.time_control),
+        };
+        if let Some(board) = &props.replay_board
+        {
+            state.game.board = board.clone();
+        }
+        let clock_task = IntervalService::spawn(CLOCK_TICK, link.callback(|_| Msg::ClockTick));
+        let storage = StorageService::new(Area::Local).expect("storage was disabled by the user");
         Self
         {
             props,
             link,
-            state: State::init(),
+            state,
+            clock_task,
+            storage,
+            fetch_task: None,
         }
     }
 
@@ -706,10 +892,9 @@ impl Component for CheckersBoard
             {
                 if !&self.state.is_steps_order_defined
                 {
-                    match color
+                    if *color == self.state.game.turn
                     {
-                        PieceColor::White => self.state.is_my_step = true,
-                        _ => (),
+                        self.state.is_my_step = true;
                     }
                     self.state.is_steps_order_defined = true;
                 }
@@ -719,165 +904,72 @@ impl Component for CheckersBoard
         {
             Msg::MoveCheckerPiece(column, line) =>
                 {
-                    if self.state.is_my_step
+                    if self.state.is_my_step && self.props.replay_board.is_none()
                     {
                         match &self.props.piece_color
                         {
                             Some(color) =>
                                 {
-                                    if self.state.piece_move.len() == 1
+                                    if !self.state.piece_move.is_empty()
                                     {
-                                        if self.state.piece_move[0] == (CheckerPosition { column, line })
+                                        if self.is_allowable_cell(column, line)
                                         {
-                                            self.state.piece_move = Vec::new();
-                                        }
-                                        else
-                                        {
-                                            if let Some(allowable_moves) = &self.state.allowable_moves
+                                            self.state.piece_move.push(CheckerPosition { column, line });
+                                            let completed_move = self.continued_moves()
+                                                .into_iter()
+                                                .find(|allowable_move| allowable_move.path == self.state.piece_move)
+                                                .cloned();
+                                            if let Some(completed_move) = completed_move
                                             {
-                                                if let Some(allow_idx) = allowable_moves
-                                                    .iter()
-                                                    .position(|allowable_move| allowable_move.next_position ==
-                                                        CheckerPosition { column, line })
-                                                {
-                                                    self.state.piece_move.push(CheckerPosition { column, line });
-
-                                                    if let Some(idx) = find_position(
-                                                        &self.state.checker_pieces[color],
-                                                        &self.state.piece_move[0] )
+                                                let game_data = GameData
                                                     {
-
-                                                        self.state.checker_pieces.get_mut(color).unwrap()[idx].position = self.state.piece_move[1].to_owned();
-                                                        match color
-                                                        {
-                                                            PieceColor::White =>
-                                                                {
-                                                                    if self.state.piece_move[1].line == 8
-                                                                    {
-                                                                        self.state.checker_pieces.get_mut(color).unwrap()[idx].is_crowned = true;
-                                                                    }
-                                                                },
-                                                            PieceColor::Black =>
-                                                                {
-                                                                    if self.state.piece_move[1].line == 1
-                                                                    {
-                                                                        self.state.checker_pieces.get_mut(color).unwrap()[idx].is_crowned = true;
-                                                                    }
-                                                                }
-                                                        }
-                                                    }
-                                                    if let Some(captured_piece_position) = &allowable_moves[allow_idx].captured_piece_position
-                                                    {
-                                                        if let Some(idx) = find_position(
-                                                            &self.state.checker_pieces[&color.opposite()],
-                                                            captured_piece_position )
-                                                        {
-                                                            self.state.checker_pieces.get_mut(&color.opposite()).unwrap().remove(idx);
-                                                        }
-
-                                                    }
-                                                    self.state.is_my_step =
-                                                        {
-                                                            if let Some(capturing_moves) = self.can_capturing_checkers()
-                                                            {
-                                                                if let Some(idx) = capturing_moves
-                                                                    .iter()
-                                                                    .position(|allowable_move| allowable_move.checker_id == allowable_moves[allow_idx].checker_id)
-                                                                {
-                                                                    if let Some(allowable_moves) = &self.state.allowable_moves
-                                                                    {
-                                                                        if allowable_moves
-                                                                            .iter()
-                                                                            .all(|allowable_move|
-                                                                                {
-                                                                                    if let Some(_) = allowable_move.captured_piece_position
-                                                                                    {
-                                                                                        true
-                                                                                    }
-                                                                                    else { false }
-                                                                                })
-                                                                        {
-                                                                            true
-                                                                        }
-                                                                        else { false }
-                                                                    }
-                                                                    else { false }
-                                                                }
-                                                                else { false }
-                                                            }
-                                                            else { false }
-                                                        };
-
-                                                    let data =
-                                                        {
-                                                            serde_json::to_string(
-                                                                &GameData
-                                                                {
-                                                                    opponent_piece_color: color.clone(),
-                                                                    piece_previous_position: self.state.piece_move[0].to_owned(),
-                                                                    piece_new_position: self.state.piece_move[1].to_owned(),
-                                                                    captured_piece_position: allowable_moves[allow_idx].captured_piece_position.to_owned(),
-                                                                    is_opponent_step: self.state.is_my_step,
-                                                                }).unwrap()
-                                                        };
-                                                    let request = WsRequest
-                                                    {
-                                                        action: GameAction::SendCheckerPieceMove.as_str(),
-                                                        data
+                                                        opponent_piece_color: color.clone(),
+                                                        path: completed_move.path,
+                                                        captured_pieces_positions: completed_move.captured_pieces_positions,
                                                     };
-                                                    self.props.send_websocket_data.emit(request);
-                                                    self.state.piece_move = Vec::new();
-                                                    if self.state.checker_pieces[&color.opposite()].is_empty()
-                                                    {
-                                                        self.state.game_result = Some(GameResult::Win);
-                                                    }
+                                                if self.state.game.apply_move(&game_data).is_err()
+                                                {
+                                                    return false;
                                                 }
-                                                else { return false; }
+                                                self.state.piece_move = Vec::new();
+                                                self.state.allowable_moves = None;
+                                                if self.props.puzzle.is_some()
+                                                {
+                                                    self.send_puzzle_move(game_data);
+                                                    return true;
+                                                }
+                                                self.state.is_my_step = false;
+                                                self.switch_clock();
+
+                                                let data = serde_json::to_string(&game_data).unwrap();
+                                                self.state.record_move(game_data, &mut self.storage);
+                                                let request = WsRequest
+                                                {
+                                                    action: GameAction::SendCheckerPieceMove.as_str(),
+                                                    data
+                                                };
+                                                self.props.send_websocket_data.emit(request);
+                                                // the draw offer and the takeback request are declined by the move
+                                                self.state.is_draw_offer_received = false;
+                                                self.state.is_takeback_request_received = false;
+                                                self.update_game_result();
                                             }
-                                            else { return false; }
                                         }
+                                        else if self.state.piece_move[0] == (CheckerPosition { column, line })
+                                        {
+                                            self.state.piece_move = Vec::new();
+                                            self.state.allowable_moves = None;
+                                        }
+                                        else { return false; }
                                     }
-                                    else if let Some(idx) = find_position(
-                                        &self.state.checker_pieces[color],
-                                        &CheckerPosition { column, line })
+                                    else if self.state.game.board.find_position(color, &CheckerPosition { column, line }).is_some()
                                     {
-                                        match self.can_capturing_checkers()
+                                        if let Some(moves) = self.state.game.board.allowable_moves(color, &CheckerPosition { column, line })
                                         {
-                                            Some(capturing_moves) =>
-                                                {
-                                                    let moves: Vec<AllowableMove> =  capturing_moves
-                                                            .into_iter()
-                                                            .filter(|allowable_move|
-                                                                allowable_move.checker_id == self.state.checker_pieces
-                                                                    .get(color).unwrap()[idx].id).collect();
-                                                    if !moves.is_empty()
-                                                    {
-                                                        self.state.allowable_moves = Some(moves);
-                                                        self.state.piece_move.push(CheckerPosition { column, line });
-                                                    }
-                                                    else { return false; }
-                                                },
-                                            None =>
-                                                {
-                                                    match self.can_moving_checkers()
-                                                    {
-                                                        Some(simple_moves) =>
-                                                            {
-                                                                let moves: Vec<AllowableMove> =  simple_moves
-                                                                    .into_iter()
-                                                                    .filter(|allowable_move|
-                                                                        allowable_move.checker_id == self.state.checker_pieces
-                                                                            .get(color).unwrap()[idx].id).collect();
-                                                                if !moves.is_empty()
-                                                                {
-                                                                    self.state.allowable_moves = Some(moves);
-                                                                    self.state.piece_move.push(CheckerPosition { column, line });
-                                                                }
-                                                            },
-                                                        None => ()
-                                                    }
-                                                }
+                                            self.state.allowable_moves = Some(moves);
+                                            self.state.piece_move.push(CheckerPosition { column, line });
                                         }
+                                        else { return false; }
                                     }
                                     else { return false; }
                                 },
@@ -886,9 +978,59 @@ impl Component for CheckersBoard
                     }
                     else { return false; }
                 },
+            Msg::ClockTick =>
+                {
+                    if !self.props.is_in_game || self.state.game_result.is_some()
+                    {
+                        return false;
+                    }
+                    if let Some(clock) = &mut self.state.clock
+                    {
+                        let color = clock.turn.to_owned();
+                        clock.set_millis(&color, clock.millis(&color).saturating_sub(CLOCK_TICK.as_millis() as u64));
+                    }
+                    else { return false; }
+                },
+            Msg::SendGameDecision(action) =>
+                {
+                    match action
+                    {
+                        GameAction::OfferDraw => self.state.is_draw_offer_sent = true,
+                        GameAction::AcceptDraw | GameAction::DeclineDraw => self.state.is_draw_offer_received = false,
+                        GameAction::RequestTakeback => self.state.is_takeback_request_sent = true,
+                        GameAction::AcceptTakeback | GameAction::DeclineTakeback =>
+                            self.state.is_takeback_request_received = false,
+                        _ => (),
+                    }
+                    let request = WsRequest { action: action.as_str(), data: String::new() };
+                    self.props.send_websocket_data.emit(request);
+                },
+            Msg::RequestHint =>
+                {
+                    if self.props.is_rated || !self.state.is_my_step
+                    {
+                        return false;
+                    }
+                    if let Ok(token) = self.storage.restore::<Result<String, Error>>(KEY)
+                    {
+                        let task = self.request_hint(&token);
+                        self.fetch_task = Some(task);
+                    }
+                    else { return false; }
+                },
+            Msg::HintReceived(response) =>
+                {
+                    self.fetch_task = None;
+                    self.state.hint = response.ok();
+                },
+            Msg::HintNotReceived(status) =>
+                {
+                    self.fetch_task = None;
+                    yew::services::dialog::DialogService::alert(&format!("Hint is not available: {}", status));
+                },
             Msg::LeaveGame =>
                 {
-                    self.state = State::init();
+                    self.state = State::init(self.props.variant, &self.props.time_control);
                     self.props.leave_game.emit(());
                     let request = WsRequest
                         {
@@ -915,55 +1057,145 @@ impl Component for CheckersBoard
     {
         if self.props != props
         {
+            if self.props.variant != props.variant || self.props.time_control != props.time_control
+            {
+                self.state = State::init(props.variant, &props.time_control);
+            }
+            if self.props.puzzle != props.puzzle
+            {
+                if let Some(puzzle) = &props.puzzle
+                {
+                    self.state = State::from_puzzle(puzzle);
+                }
+            }
+            if let Some(board) = &props.replay_board
+            {
+                self.state.game.board = board.clone();
+            }
+            let puzzle_reply = if self.props.puzzle_reply != props.puzzle_reply { props.puzzle_reply.clone() } else { None };
             self.props = props;
+            if let Some(reply) = puzzle_reply
+            {
+                self.play_puzzle_reply(reply);
+            }
             if let Some(response) = &self.props.websocket_game_response
             {
                 if response.action == GameAction::ReceivedCheckerPieceMove.as_str()
                 {
                     self.props.reset_websocket_game_response.emit(());
                     let game_data: GameData = serde_json::from_str(&response.data).unwrap();
-                    self.state.is_my_step = !game_data.is_opponent_step;
-                    let color =  &game_data.opponent_piece_color;
-                    if let Some(idx) = find_position(
-                        &self.state.checker_pieces[color],
-                        &game_data.piece_previous_position)
+                    self.state.is_my_step = !self.props.is_spectator;
+                    self.state.is_draw_offer_sent = false;
+                    self.state.is_takeback_request_sent = false;
+                    if self.state.game.apply_move(&game_data).is_ok()
                     {
-                        self.state.checker_pieces.get_mut(color).unwrap()[idx].position = game_data.piece_new_position.clone();
-                        match color
+                        self.state.record_move(game_data, &mut self.storage);
+                    }
+                    self.switch_clock();
+                    self.update_game_result();
+                }
+                else if response.action == GameAction::ClockUpdate.as_str()
+                {
+                    self.props.reset_websocket_game_response.emit(());
+                    if let Ok(clock_data) = serde_json::from_str::<ClockData>(&response.data)
+                    {
+                        self.state.clock = Some(clock_data);
+                    }
+                }
+                else if response.action == GameAction::PositionSnapshot.as_str()
+                {
+                    self.props.reset_websocket_game_response.emit(());
+                    if let Ok(snapshot) = serde_json::from_str::<PositionSnapshot>(&response.data)
+                    {
+                        if let Ok(game) = Game::from_fen(self.props.variant, &snapshot.fen)
                         {
-                            PieceColor::White =>
-                                {
-                                    if game_data.piece_new_position.line == 8
-                                    {
-                                        self.state.checker_pieces.get_mut(color).unwrap()[idx].is_crowned = true;
-                                    }
-                                },
-                            PieceColor::Black =>
+                            self.state.is_my_step = self.props.piece_color.as_ref() == Some(&game.turn);
+                            self.state.is_steps_order_defined = true;
+                            self.state.record = GameRecord
                                 {
-                                    if game_data.piece_new_position.line == 1
-                                    {
-                                        self.state.checker_pieces.get_mut(color).unwrap()[idx].is_crowned = true;
-                                    }
-                                }
+                                    variant: self.props.variant,
+                                    initial_fen: game.to_fen(),
+                                    moves: Vec::new(),
+                                };
+                            self.state.game = game;
+                            self.state.hint = None;
+                            self.state.piece_move = Vec::new();
+                            self.state.allowable_moves = None;
+                            self.update_game_result();
                         }
-                    }
-                    if let Some(captured_position) = game_data.captured_piece_position
-                    {
-                        if let Some(idx) = find_position(
-                            &self.state.checker_pieces[&color.opposite()],
-                            &captured_position)
+                        if snapshot.clock.is_some()
                         {
-                            self.state.checker_pieces.get_mut(&color.opposite()).unwrap().remove(idx);
+                            self.state.clock = snapshot.clock;
                         }
+                        self.state.spectators = snapshot.spectators;
+                    }
+                }
+                else if response.action == GameAction::OpponentDisconnected.as_str()
+                {
+                    self.props.reset_websocket_game_response.emit(());
+                    self.state.is_opponent_disconnected = !self.props.is_spectator;
+                }
+                else if response.action == GameAction::OpponentReconnected.as_str()
+                {
+                    self.props.reset_websocket_game_response.emit(());
+                    self.state.is_opponent_disconnected = false;
+                }
+                else if response.action == GameAction::SpectatorsCount.as_str()
+                {
+                    self.props.reset_websocket_game_response.emit(());
+                    self.state.spectators = response.data.parse().unwrap_or_default();
+                }
+                else if response.action == GameAction::ReceivedDrawOffer.as_str()
+                {
+                    self.props.reset_websocket_game_response.emit(());
+                    self.state.is_draw_offer_received = true;
+                }
+                else if response.action == GameAction::DeclinedDrawOffer.as_str()
+                {
+                    self.props.reset_websocket_game_response.emit(());
+                    self.state.is_draw_offer_sent = false;
+                }
+                else if response.action == GameAction::ReceivedTakebackRequest.as_str()
+                {
+                    self.props.reset_websocket_game_response.emit(());
+                    self.state.is_takeback_request_received = true;
+                }
+                else if response.action == GameAction::DeclinedTakebackRequest.as_str()
+                {
+                    self.props.reset_websocket_game_response.emit(());
+                    self.state.is_takeback_request_sent = false;
+                }
+                else if response.action == GameAction::Takeback.as_str()
+                {
+                    self.props.reset_websocket_game_response.emit(());
+                    if let Ok(plies) = response.data.parse::<usize>()
+                    {
+                        self.state.take_back(plies, &self.props.piece_color, &mut self.storage);
                     }
-                    if self.state.checker_pieces[&color.opposite()].is_empty()
+                }
+                else if response.action == GameAction::GameOver.as_str()
+                {
+                    self.props.reset_websocket_game_response.emit(());
+                    if let Ok(outcome) = serde_json::from_str::<GameOutcome>(&response.data)
                     {
-                        self.state.game_result = Some(GameResult::Lose);
+                        self.state.game.outcome = Some(outcome);
+                        self.update_game_result();
                     }
                 }
+                else if response.action == GameAction::RejectedGameDecision.as_str()
+                {
+                    self.props.reset_websocket_game_response.emit(());
+                    yew::services::dialog::DialogService::alert(&format!("Request was rejected: {}", response.data));
+                }
+                else if response.action == GameAction::RejectedCheckerPieceMove.as_str()
+                {
+                    self.props.reset_websocket_game_response.emit(());
+                    yew::services::dialog::DialogService::alert(&format!("Move was rejected: {}", response.data));
+                }
                 else if response.action == GameAction::ReceivedLeaveGameMessage.as_str()
                 {
-                    if let None = &self.state.game_result
+                    // spectators get the outcome of the left game from the server
+                    if self.state.game_result.is_none() && !self.props.is_spectator
                     {
                         self.state.game_result = Some(GameResult::Win);
                     }
@@ -981,18 +1213,18 @@ impl Component for CheckersBoard
 
     fn view(&self) -> Html
     {
-        let letters = ["A", "B", "C", "D", "E", "F", "G", "H"];
+        let size = self.state.game.board.size;
         let (number_sequence, letter_sequence): (Vec<u8>, Vec<u8>) =
             {
                 if let Some(color) = &self.props.piece_color
                 {
                     match color
                     {
-                        PieceColor::White => ((1..=8).into_iter().rev().collect(), (1..=8).into_iter().collect()),
-                        PieceColor::Black => ((1..=8).into_iter().collect(), (1..=8).into_iter().rev().collect())
+                        PieceColor::White => ((1..=size).into_iter().rev().collect(), (1..=size).into_iter().collect()),
+                        PieceColor::Black => ((1..=size).into_iter().collect(), (1..=size).into_iter().rev().collect())
                     }
                 }
-                else { ((1..=8).into_iter().rev().collect(), (1..=8).into_iter().collect()) }
+                else { ((1..=size).into_iter().rev().collect(), (1..=size).into_iter().collect()) }
             };
 
 
@@ -1002,7 +1234,7 @@ impl Component for CheckersBoard
                 {
                     html!
                     {
-                        <div class="cell_alpha">{ letters[i as usize - 1] }</div>
+                        <div class="cell_alpha">{ (b'A' + i - 1) as char }</div>
                     }
                 }).collect()
             };
@@ -1012,7 +1244,13 @@ impl Component for CheckersBoard
         {
             <div class="checkers_board_container">
 
-                { self.checkers_board_header_view() }
+                {
+                    if self.props.replay_board.is_none() && self.props.puzzle.is_none()
+                    {
+                        html! { <>{ self.checkers_board_header_view() }{ self.view_spectators() }{ self.view_clock() }{ self.view_hint() }</> }
+                    }
+                    else { html! {} }
+                }
 
                 <div class="line">
                     <div class="cell_num">   </div>
@@ -1034,7 +1272,7 @@ impl Component for CheckersBoard
                                         }
                                         else
                                         {
-                                            if self.props.is_in_game
+                                            if self.props.is_in_game || self.props.replay_board.is_some()
                                             {
                                                 { self.view_black_cells(j, i) }
                                             }
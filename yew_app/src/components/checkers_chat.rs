@@ -6,6 +6,7 @@ use yew::services::timeout::{TimeoutService, TimeoutTask};
 use std::time::Duration;
 
 use web_sys;
+use serde_json;
 use std::collections::HashSet;
 
 use std::rc::Rc;
@@ -13,13 +14,22 @@ use std::rc::Rc;
 use crate::types::
 {
     AuthorizedUserResponse, WsRequest, ChatMessage, OnlineUser, SentInvitation, ChatMessageResponse,
-    WsResponse, ReceivedInvitation
+    WsResponse, ReceivedInvitation, InvitationData, Variant, TimeControl, LiveGame, PieceColor, GameSearch,
+    TournamentGameReady,
 };
-use crate::pages::ChatAction;
+use crate::pages::{ChatAction, GameAction};
 
 
 const INVITATION_WAITING_TIME: Duration = Duration::from_secs(30);
 const CHAT_LOG_URL: &str = "/chat/extract_log/checkers_game";
+pub const TIME_CONTROLS: [Option<TimeControl>; 5] =
+    [
+        None,
+        Some(TimeControl::Fischer { base_seconds: 180, increment_seconds: 2 }),
+        Some(TimeControl::Fischer { base_seconds: 300, increment_seconds: 3 }),
+        Some(TimeControl::Fischer { base_seconds: 600, increment_seconds: 5 }),
+        Some(TimeControl::PerMove { seconds: 30 }),
+    ];
 
 
 pub type FetchResponse<T> = Response<Json<Result<T, Error>>>;
@@ -37,9 +47,10 @@ pub struct Props
     pub reset_websocket_chat_response: Callback<()>,
     pub websocket_chat_response: Option<WsResponse>,
     pub is_in_game: bool,
-    pub start_game: Callback<()>,
-    pub choose_white_color: Callback<()>,
-    pub choose_black_color: Callback<()>,
+    pub watch_game: Callback<()>,
+    pub choose_variant: Callback<Variant>,
+    pub choose_time_control: Callback<Option<TimeControl>>,
+    pub choose_rated: Callback<bool>,
 }
 
 
@@ -51,6 +62,14 @@ struct State
     online_users: HashSet<OnlineUser>,
     sent_invitations: Vec<SentInvitation>,
     received_invitations: Vec<ReceivedInvitation>,
+    variant: Variant,
+    time_control: Option<TimeControl>,
+    initial_fen: Option<String>,
+    is_rated: bool,
+    color_preference: Option<PieceColor>,
+    live_games: Vec<LiveGame>,
+    /// The session waits in the matchmaking queue
+    is_searching_game: bool,
 }
 
 
@@ -84,6 +103,36 @@ pub enum Msg
     SendInvitation(String),
     DeclineInvitation(String),
     AcceptInvitation(String),
+    ChooseVariant(String),
+    ChooseTimeControl(String),
+    UpdateInitialFen(String),
+    ToggleRated,
+    ChooseColor(String),
+    RequestLiveGames,
+    WatchGame(String),
+    FindGame,
+    CancelFindGame,
+}
+
+
+fn color_preference_name(color_preference: &Option<PieceColor>) -> &'static str
+{
+    match color_preference
+    {
+        Some(PieceColor::White) => "White",
+        Some(PieceColor::Black) => "Black",
+        None => "Random",
+    }
+}
+
+
+fn time_control_name(time_control: &Option<TimeControl>) -> String
+{
+    if let Some(time_control) = time_control
+    {
+        time_control.as_string()
+    }
+    else { "No limit".to_owned() }
 }
 
 
@@ -178,6 +227,12 @@ impl CheckersChat
     }
 
 
+    /// The game may start from a custom position, so the board is set by the server.
+    fn request_position_snapshot(&self)
+    {
+        let request = WsRequest { action: GameAction::RequestPositionSnapshot.as_str(), data: String::new() };
+        self.props.send_websocket_data.emit(request);
+    }
 }
 
 
@@ -197,6 +252,8 @@ impl Component for CheckersChat
             {
                 message: None, chat_messages: Vec::new(), online_users: HashSet::new(),
                 sent_invitations: Vec::new(), received_invitations: Vec::new(),
+                variant: Variant::default(), time_control: None, initial_fen: None,
+                is_rated: false, color_preference: None, live_games: Vec::new(), is_searching_game: false,
             },
             fetch_task: None, timeout_tasks: Vec::new(),
         }
@@ -219,6 +276,7 @@ impl Component for CheckersChat
                     self.timeout_tasks = Vec::new();
                     self.state.sent_invitations = Vec::new();
                     self.state.received_invitations = Vec::new();
+                    self.state.is_searching_game = false;
 
                     self.props.disconnect.emit(());
                 },
@@ -263,8 +321,16 @@ impl Component for CheckersChat
             Msg::ChatLogNotReceived => return false,
             Msg::SendInvitation(to_user) =>
                 {
-                    self.state.sent_invitations.push(SentInvitation { to_user: to_user.clone() });
-                    let request = WsRequest { action: ChatAction::Invitation.as_str(), data: to_user };
+                    let variant = self.state.variant;
+                    let time_control = self.state.time_control;
+                    let initial_fen = self.state.initial_fen.clone();
+                    let is_rated = self.state.is_rated;
+                    let color_preference = self.state.color_preference.clone();
+                    self.state.sent_invitations.push(SentInvitation { to_user: to_user.clone(), variant, time_control, is_rated });
+                    let data = serde_json::to_string(
+                        &InvitationData { user_name: to_user, variant, time_control, initial_fen, is_rated, color_preference })
+                        .unwrap();
+                    let request = WsRequest { action: ChatAction::Invitation.as_str(), data };
                     self.props.send_websocket_data.emit(request);
                 },
             Msg::DeclineInvitation(to_user) =>
@@ -286,20 +352,76 @@ impl Component for CheckersChat
                 },
             Msg::AcceptInvitation(to_user) =>
                 {
+                    // the server starts the game and tells the color of the pieces
                     self.decline_invitations(&to_user);
-                    let request = WsRequest { action: ChatAction::AcceptInvitation.as_str(), data: to_user.clone() };
+                    let request = WsRequest { action: ChatAction::AcceptInvitation.as_str(), data: to_user };
                     self.props.send_websocket_data.emit(request);
-                    if let Some(user) = &*self.props.user
+                },
+            Msg::ChooseVariant(name) =>
+                {
+                    if let Some(variant) = Variant::from_name(&name)
                     {
-                        let join_to_room_request = WsRequest
-                        {
-                            action: ChatAction::JoinToRoom.as_str(),
-                            data: format!("checkers_game_{}_{}", user.user_name, to_user),
-                        };
-                        self.props.send_websocket_data.emit(join_to_room_request);
+                        self.state.variant = variant;
+                    }
+                    else { return false; }
+                },
+            Msg::ChooseTimeControl(idx) =>
+                {
+                    if let Some(time_control) = idx.parse::<usize>().ok().and_then(|idx| TIME_CONTROLS.get(idx))
+                    {
+                        self.state.time_control = *time_control;
                     }
-                    self.props.start_game.emit(());
-                    self.props.choose_white_color.emit(());
+                    else { return false; }
+                },
+            Msg::UpdateInitialFen(fen) =>
+                {
+                    let fen = fen.trim().to_owned();
+                    self.state.initial_fen = if fen.is_empty() { None } else { Some(fen) };
+                    return false;
+                },
+            Msg::ToggleRated => self.state.is_rated = !self.state.is_rated,
+            Msg::ChooseColor(name) =>
+                {
+                    self.state.color_preference = match name.as_str()
+                    {
+                        "White" => Some(PieceColor::White),
+                        "Black" => Some(PieceColor::Black),
+                        _ => None,
+                    };
+                },
+            Msg::RequestLiveGames =>
+                {
+                    let request = WsRequest { action: ChatAction::RequestLiveGames.as_str(), data: String::new() };
+                    self.props.send_websocket_data.emit(request);
+                    return false;
+                },
+            Msg::WatchGame(room) =>
+                {
+                    self.decline_invitations("");
+                    let request = WsRequest { action: ChatAction::WatchGame.as_str(), data: room };
+                    self.props.send_websocket_data.emit(request);
+                    return false;
+                },
+            Msg::FindGame =>
+                {
+                    // the server starts the game as soon as the opponent is found
+                    let data = serde_json::to_string(
+                        &GameSearch
+                        {
+                            variant: self.state.variant,
+                            time_control: self.state.time_control,
+                            is_rated: self.state.is_rated,
+                        })
+                        .unwrap();
+                    let request = WsRequest { action: ChatAction::FindGame.as_str(), data };
+                    self.props.send_websocket_data.emit(request);
+                    self.state.is_searching_game = true;
+                },
+            Msg::CancelFindGame =>
+                {
+                    let request = WsRequest { action: ChatAction::CancelFindGame.as_str(), data: String::new() };
+                    self.props.send_websocket_data.emit(request);
+                    self.state.is_searching_game = false;
                 },
         }
         true
@@ -311,6 +433,10 @@ impl Component for CheckersChat
         if self.props != props
         {
             self.props = props;
+            if self.props.is_in_game || !self.props.is_connected
+            {
+                self.state.is_searching_game = false;
+            }
             if let Some(response) = self.props.websocket_chat_response.clone()
             {
                 if response.action == ChatAction::ReceivedMessage.as_str()
@@ -341,7 +467,10 @@ impl Component for CheckersChat
                     self.props.reset_websocket_chat_response.emit(());
                     if !self.props.is_in_game
                     {
-                        self.state.online_users.insert(OnlineUser(response.data));
+                        if let Ok(online_user) = serde_json::from_str::<OnlineUser>(&response.data)
+                        {
+                            self.state.online_users.insert(online_user);
+                        }
                     }
                 }
                 else if response.action == ChatAction::SomeoneConnected.as_str() // && response.data == "Someone connected"
@@ -354,13 +483,31 @@ impl Component for CheckersChat
                 else if response.action == ChatAction::Invitation.as_str()
                 {
                     self.props.reset_websocket_chat_response.emit(());
-                    self.state.received_invitations.push(ReceivedInvitation { from_user: response.data.clone() });
-                    let task = self.auto_decline_invitation(response.data.clone());
+                    let invitation_data: InvitationData = serde_json::from_str(&response.data).unwrap();
+                    self.state.received_invitations.push(
+                        ReceivedInvitation
+                            {
+                                from_user: invitation_data.user_name.clone(),
+                                variant: invitation_data.variant,
+                                time_control: invitation_data.time_control,
+                                initial_fen: invitation_data.initial_fen.clone(),
+                                is_rated: invitation_data.is_rated,
+                                color_preference: invitation_data.color_preference.clone(),
+                            });
+                    let task = self.auto_decline_invitation(invitation_data.user_name.clone());
                     self.timeout_tasks.push(
                         TimeoutTaskData
                             {
                                 timeout_task: task,
-                                received_invitation: ReceivedInvitation { from_user: response.data }
+                                received_invitation: ReceivedInvitation
+                                    {
+                                        from_user: invitation_data.user_name,
+                                        variant: invitation_data.variant,
+                                        time_control: invitation_data.time_control,
+                                        initial_fen: invitation_data.initial_fen,
+                                        is_rated: invitation_data.is_rated,
+                                        color_preference: invitation_data.color_preference,
+                                    }
                             }
                     );
                 }
@@ -376,19 +523,49 @@ impl Component for CheckersChat
                 }
                 else if response.action == ChatAction::AcceptInvitation.as_str()
                 {
+                    // the game is started by the server
                     self.props.reset_websocket_chat_response.emit(());
                     self.decline_invitations(&response.data);
-                    if let Some(user) = &*self.props.user
+                }
+                else if response.action == ChatAction::CancelledInvitation.as_str()
+                {
+                    self.props.reset_websocket_chat_response.emit(());
+                    self.state.chat_messages.push(ChatMessage(format!(
+                        "{} is not available, the invitation is cancelled", response.data)));
+                }
+                else if response.action == ChatAction::ResponseLiveGames.as_str()
+                {
+                    self.props.reset_websocket_chat_response.emit(());
+                    self.state.live_games = serde_json::from_str(&response.data).unwrap_or_default();
+                }
+                else if response.action == ChatAction::WatchGame.as_str()
+                {
+                    self.props.reset_websocket_chat_response.emit(());
+                    if let Ok(live_game) = serde_json::from_str::<LiveGame>(&response.data)
                     {
-                        let join_to_room_request = WsRequest
-                        {
-                            action: ChatAction::JoinToRoom.as_str(),
-                            data: format!("checkers_game_{}_{}", &response.data, user.user_name),
-                        };
-                        self.props.send_websocket_data.emit(join_to_room_request);
+                        self.props.choose_variant.emit(live_game.variant);
+                        self.props.choose_time_control.emit(live_game.time_control);
+                        self.props.choose_rated.emit(live_game.is_rated);
+                        self.props.watch_game.emit(());
+                        self.request_position_snapshot();
+                    }
+                }
+                else if response.action == ChatAction::RejectedWatchGame.as_str()
+                {
+                    self.props.reset_websocket_chat_response.emit(());
+                    yew::services::dialog::DialogService::alert(&response.data);
+                    self.link.send_message(Msg::RequestLiveGames);
+                }
+                else if response.action == ChatAction::TournamentGameReady.as_str()
+                {
+                    self.props.reset_websocket_chat_response.emit(());
+                    if let Ok(game_ready) = serde_json::from_str::<TournamentGameReady>(&response.data)
+                    {
+                        let piece_color = if game_ready.piece_color == PieceColor::White { "white" } else { "black" };
+                        self.state.chat_messages.push(ChatMessage(format!(
+                            "{}, round {}: your next game against {} is ready, you play {}",
+                            game_ready.tournament_name, game_ready.round, game_ready.opponent, piece_color)));
                     }
-                    self.props.start_game.emit(());
-                    self.props.choose_black_color.emit(());
                 }
                 else { return false; }
             }
@@ -467,6 +644,104 @@ impl Component for CheckersChat
                 }
 
                 <h3>{ "Users online" }</h3>
+                <div>
+                    { "Rules: " }
+                    <select
+                        disabled=(self.props.is_in_game || self.state.is_searching_game)
+                        onchange=self.link.callback(|e: ChangeData|
+                            match e
+                            {
+                                ChangeData::Select(select) => Msg::ChooseVariant(select.value()),
+                                _ => Msg::ChooseVariant(String::new()),
+                            })>
+                        {
+                            for Variant::iterator().map(|variant: &Variant|
+                            html!
+                            {
+                                <option value=variant.as_str() selected=*variant == self.state.variant>
+                                    { variant.as_str() }
+                                </option>
+                            })
+                        }
+                    </select>
+                    { " Time: " }
+                    <select
+                        disabled=(self.props.is_in_game || self.state.is_searching_game)
+                        onchange=self.link.callback(|e: ChangeData|
+                            match e
+                            {
+                                ChangeData::Select(select) => Msg::ChooseTimeControl(select.value()),
+                                _ => Msg::ChooseTimeControl(String::new()),
+                            })>
+                        {
+                            for TIME_CONTROLS.iter().enumerate().map(|(idx, time_control)|
+                            html!
+                            {
+                                <option value=idx.to_string() selected=*time_control == self.state.time_control>
+                                    { time_control_name(time_control) }
+                                </option>
+                            })
+                        }
+                    </select>
+                    { " Position: " }
+                    <input
+                        placeholder="FEN, e.g. W:W21,22,K30:B1,2,3"
+                        disabled=self.props.is_in_game
+                        oninput=self.link.callback(|d: InputData| Msg::UpdateInitialFen(d.value))
+                    />
+                    <label>
+                        <input
+                            type="checkbox"
+                            checked=self.state.is_rated
+                            disabled=(self.props.is_in_game || self.state.is_searching_game)
+                            onclick=self.link.callback(|_| Msg::ToggleRated)
+                        />
+                        { " Rated" }
+                    </label>
+                    { " Color: " }
+                    <select
+                        disabled=self.props.is_in_game
+                        onchange=self.link.callback(|e: ChangeData|
+                            match e
+                            {
+                                ChangeData::Select(select) => Msg::ChooseColor(select.value()),
+                                _ => Msg::ChooseColor(String::new()),
+                            })>
+                        {
+                            for [None, Some(PieceColor::White), Some(PieceColor::Black)].iter().map(|color_preference|
+                            html!
+                            {
+                                <option
+                                    value=color_preference_name(color_preference)
+                                    selected=*color_preference == self.state.color_preference>
+                                    { color_preference_name(color_preference) }
+                                </option>
+                            })
+                        }
+                    </select>
+                    {
+                        if self.state.is_searching_game
+                        {
+                            html!
+                            {
+                                <button onclick=self.link.callback(|_| Msg::CancelFindGame)>
+                                    { "Cancel search" }
+                                </button>
+                            }
+                        }
+                        else
+                        {
+                            html!
+                            {
+                                <button
+                                    disabled=(!self.props.is_connected || self.props.is_in_game || self.props.user.is_none())
+                                    onclick=self.link.callback(|_| Msg::FindGame)>
+                                    { "Find game" }
+                                </button>
+                            }
+                        }
+                    }
+                </div>
                 <div class="checkers_game_online_users">
                     {
                         if !self.props.is_in_game
@@ -485,12 +760,12 @@ impl Component for CheckersChat
                                         html!
                                         {
                                             <tr>
-                                                <td>{ &online_user.0 }</td>
+                                                <td>{ format!("{} ({})", online_user.user_name, online_user.rating) }</td>
                                                 <td>
                                                     {
                                                         if true
                                                         {
-                                                            let user_name = online_user.0.clone();
+                                                            let user_name = online_user.user_name.clone();
                                                             html!
                                                             {
                                                                 <button
@@ -521,6 +796,42 @@ impl Component for CheckersChat
 
                 </div>
 
+                <h3>{ "Live games" }</h3>
+                <div class="checkers_game_live_games">
+                    <button
+                        disabled=!self.props.is_connected
+                        onclick=self.link.callback(|_| Msg::RequestLiveGames)>
+                        { "Refresh" }
+                    </button>
+                    <table>
+                        <tbody>
+                        {
+                            for self.state.live_games.iter().map(|live_game: &LiveGame|
+                            {
+                                let room = live_game.room.clone();
+                                html!
+                                {
+                                    <tr>
+                                        <td>{ format!("{} - {}", live_game.white_player, live_game.black_player) }</td>
+                                        <td>{ live_game.variant.as_str() }</td>
+                                        <td>{ time_control_name(&live_game.time_control) }</td>
+                                        <td>{ if live_game.is_rated { "rated" } else { "casual" } }</td>
+                                        <td>{ format!("watching: {}", live_game.spectators) }</td>
+                                        <td>
+                                            <button
+                                                disabled=!self.props.is_connected || self.props.is_in_game
+                                                onclick=self.link.callback(move |_| Msg::WatchGame(room.clone()))>
+                                                { "Watch" }
+                                            </button>
+                                        </td>
+                                    </tr>
+                                }
+                            })
+                        }
+                        </tbody>
+                    </table>
+                </div>
+
                 <h3>{ "Invitations" }</h3>
                 <div class="checkers_game_invitation">
                     <table>
@@ -531,6 +842,16 @@ impl Component for CheckersChat
                             {
                                 <tr>
                                     <td>{ &invitation.from_user }</td>
+                                    <td>{ invitation.variant.as_str() }</td>
+                                    <td>{ time_control_name(&invitation.time_control) }</td>
+                                    <td>{ if invitation.initial_fen.is_some() { "custom position" } else { "" } }</td>
+                                    <td>{ if invitation.is_rated { "rated" } else { "casual" } }</td>
+                                    <td>
+                                        {
+                                            format!("you play: {}",
+                                                color_preference_name(&invitation.color_preference.as_ref().map(PieceColor::opposite)))
+                                        }
+                                    </td>
                                     <td>
                                         {
                                             if true
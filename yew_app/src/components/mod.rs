@@ -6,4 +6,4 @@ mod checkers_chat;
 pub use navbar::NavBar;
 pub use all_users::AllUsers;
 pub use checkers_board::CheckersBoard;
-pub use checkers_chat::CheckersChat;
+pub use checkers_chat::{CheckersChat, TIME_CONTROLS};
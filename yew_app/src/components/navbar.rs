@@ -68,7 +68,7 @@ impl NavBar
                     // <a href="/" class="anchor_button" onclick=self.link.callback(|_| Msg::SignOut)>{ "Sign out" }</a>
                     <button class="button" onclick=self.link.callback(|_| Msg::SignOut)>{ "Sign out" }</button>
                     <Button route=AppRoute::UserInfo classes="button">
-                      { user.user_name.to_string() }
+                      { format!("{} ({})", user.user_name, user.rating) }
                     </Button>
                   </>
                 }
@@ -154,6 +154,15 @@ impl Component for NavBar
                             <li class="header_list_item">
                                 <Anchor route=AppRoute::CheckersGame>{ "Checkers" }</Anchor>
                             </li>
+                            <li class="header_list_item">
+                                <Anchor route=AppRoute::GameReplay>{ "Replay" }</Anchor>
+                            </li>
+                            <li class="header_list_item">
+                                <Anchor route=AppRoute::Puzzles>{ "Puzzles" }</Anchor>
+                            </li>
+                            <li class="header_list_item">
+                                <Anchor route=AppRoute::Leaderboard>{ "Leaderboard" }</Anchor>
+                            </li>
                         </ul>
                     </nav>
 
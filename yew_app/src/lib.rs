@@ -24,7 +24,7 @@ mod pages;
 mod types;
 
 use components::NavBar;
-use pages::{HomePage, SignInUser, RegisterUser, UserInfo, CheckersGame};
+use pages::{HomePage, SignInUser, RegisterUser, UserInfo, CheckersGame, GameReplay, Puzzles, Leaderboard};
 use route::AppRoute;
 use types::AuthorizedUserResponse;
 
@@ -184,7 +184,10 @@ impl Component for Model
                                             identify_user=handle_identify_user.clone() /> },
             AppRoute::RegisterUser => html! { <RegisterUser /> },
             AppRoute::UserInfo => html! { <UserInfo user=Rc::clone(&user), token=Rc::clone(&token), sign_out=handle_sign_out.clone() /> },
-            AppRoute::CheckersGame => html! { <CheckersGame user=Rc::clone(&user) /> },
+            AppRoute::CheckersGame => html! { <CheckersGame user=Rc::clone(&user), token=Rc::clone(&token) /> },
+            AppRoute::GameReplay => html! { <GameReplay user=Rc::clone(&user) /> },
+            AppRoute::Puzzles => html! { <Puzzles user=Rc::clone(&user), token=Rc::clone(&token) /> },
+            AppRoute::Leaderboard => html! { <Leaderboard /> },
             AppRoute::HomePage => html! { <HomePage /> },
         });
 
@@ -2,10 +2,12 @@ use yew::prelude::*;
 use anyhow::Error;
 use yew::format::Json;
 use yew::services::websocket::{WebSocketService, WebSocketStatus, WebSocketTask};
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+use std::time::Duration;
 
 use std::rc::Rc;
 
-use crate::types::{AuthorizedUserResponse, WsRequest, WsResponse, PieceColor};
+use crate::types::{AuthorizedUserResponse, WsRequest, WsResponse, PieceColor, Variant, TimeControl, JoinedGame};
 use crate::components::CheckersBoard;
 use crate::components::CheckersChat;
 
@@ -18,13 +20,16 @@ use dotenv_codegen::dotenv;
 
 pub const GAME_NAME: &str = "checkers_game";
 pub const WEBSOCKET_URL: &str = dotenv!("WEBSOCKET_URL");
+/// Key of the local storage under which the moves of the last game are recorded
+pub const GAME_RECORD_KEY: &str = "checkers_game_record";
+/// How long to wait before the lost connection of the unfinished game is restored
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
 
 
 pub enum ChatAction
 {
     RequestOnlineUsers,
     JoinToRoom,
-    SetName,
     SendMessage,
     Invitation,
     AcceptInvitation,
@@ -33,6 +38,14 @@ pub enum ChatAction
     SomeoneDisconnected,
     SomeoneConnected,
     ReceivedMessage,
+    RequestLiveGames,
+    ResponseLiveGames,
+    WatchGame,
+    RejectedWatchGame,
+    FindGame,
+    CancelFindGame,
+    TournamentGameReady,
+    CancelledInvitation,
 }
 
 
@@ -44,7 +57,6 @@ impl ChatAction
         {
             ChatAction::RequestOnlineUsers => String::from("request_online_users"),
             ChatAction::JoinToRoom => String::from("join_to_room"),
-            ChatAction::SetName => String::from("set_name"),
             ChatAction::SendMessage => String::from("send_message"),
             ChatAction::Invitation => String::from("invitation"),
             ChatAction::AcceptInvitation => String::from("accept_invitation"),
@@ -53,16 +65,26 @@ impl ChatAction
             ChatAction::SomeoneDisconnected => String::from("disconnect"),
             ChatAction::SomeoneConnected => String::from("connect"),
             ChatAction::ReceivedMessage => String::from("received_message"),
+            ChatAction::RequestLiveGames => String::from("request_live_games"),
+            ChatAction::ResponseLiveGames => String::from("response_live_games"),
+            ChatAction::WatchGame => String::from("watch_game"),
+            ChatAction::RejectedWatchGame => String::from("rejected_watch_game"),
+            ChatAction::FindGame => String::from("find_game"),
+            ChatAction::CancelFindGame => String::from("cancel_find_game"),
+            ChatAction::TournamentGameReady => String::from("tournament_game_ready"),
+            ChatAction::CancelledInvitation => String::from("cancelled_invitation"),
         }
     }
 
     pub fn iterator() -> Iter<'static, ChatAction>
      {
-        static ACTIONS: [ChatAction; 11] =
+        static ACTIONS: [ChatAction; 18] =
             [
-                RequestOnlineUsers, JoinToRoom, SetName, SendMessage, Invitation,
+                RequestOnlineUsers, JoinToRoom, SendMessage, Invitation,
                 AcceptInvitation, DeclineInvitation, ResponseOnlineUsers,
-                SomeoneDisconnected, SomeoneConnected, ReceivedMessage
+                SomeoneDisconnected, SomeoneConnected, ReceivedMessage,
+                RequestLiveGames, ResponseLiveGames, WatchGame, RejectedWatchGame,
+                FindGame, CancelFindGame, TournamentGameReady, CancelledInvitation
             ];
         ACTIONS.iter()
     }
@@ -75,6 +97,29 @@ pub enum GameAction
     ReceivedCheckerPieceMove,
     SendLeaveGameMessage,
     ReceivedLeaveGameMessage,
+    RejectedCheckerPieceMove,
+    Resign,
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+    ReceivedDrawOffer,
+    DeclinedDrawOffer,
+    GameOver,
+    RejectedGameDecision,
+    ClockUpdate,
+    RequestPositionSnapshot,
+    PositionSnapshot,
+    RequestTakeback,
+    AcceptTakeback,
+    DeclineTakeback,
+    ReceivedTakebackRequest,
+    DeclinedTakebackRequest,
+    Takeback,
+    SpectatorsCount,
+    GameStarted,
+    ReconnectGame,
+    OpponentDisconnected,
+    OpponentReconnected,
 }
 
 
@@ -88,15 +133,43 @@ impl GameAction
             GameAction::ReceivedCheckerPieceMove => String::from("received_checker_piece_move"),
             GameAction::SendLeaveGameMessage => String::from("send_leave_game_message"),
             GameAction::ReceivedLeaveGameMessage => String::from("received_leave_game_message"),
+            GameAction::RejectedCheckerPieceMove => String::from("rejected_checker_piece_move"),
+            GameAction::Resign => String::from("resign"),
+            GameAction::OfferDraw => String::from("offer_draw"),
+            GameAction::AcceptDraw => String::from("accept_draw"),
+            GameAction::DeclineDraw => String::from("decline_draw"),
+            GameAction::ReceivedDrawOffer => String::from("received_draw_offer"),
+            GameAction::DeclinedDrawOffer => String::from("declined_draw_offer"),
+            GameAction::GameOver => String::from("game_over"),
+            GameAction::RejectedGameDecision => String::from("rejected_game_decision"),
+            GameAction::ClockUpdate => String::from("clock_update"),
+            GameAction::RequestPositionSnapshot => String::from("request_position_snapshot"),
+            GameAction::PositionSnapshot => String::from("position_snapshot"),
+            GameAction::RequestTakeback => String::from("request_takeback"),
+            GameAction::AcceptTakeback => String::from("accept_takeback"),
+            GameAction::DeclineTakeback => String::from("decline_takeback"),
+            GameAction::ReceivedTakebackRequest => String::from("received_takeback_request"),
+            GameAction::DeclinedTakebackRequest => String::from("declined_takeback_request"),
+            GameAction::Takeback => String::from("takeback"),
+            GameAction::SpectatorsCount => String::from("spectators_count"),
+            GameAction::GameStarted => String::from("game_started"),
+            GameAction::ReconnectGame => String::from("reconnect_game"),
+            GameAction::OpponentDisconnected => String::from("opponent_disconnected"),
+            GameAction::OpponentReconnected => String::from("opponent_reconnected"),
         }
     }
 
     pub fn iterator() -> Iter<'static, GameAction>
      {
-        static ACTIONS: [GameAction; 4] =
+        static ACTIONS: [GameAction; 27] =
             [
                 SendCheckerPieceMove, ReceivedCheckerPieceMove, SendLeaveGameMessage,
-                ReceivedLeaveGameMessage
+                ReceivedLeaveGameMessage, RejectedCheckerPieceMove, Resign, OfferDraw,
+                AcceptDraw, DeclineDraw, ReceivedDrawOffer, DeclinedDrawOffer, GameOver,
+                RejectedGameDecision, ClockUpdate, RequestPositionSnapshot, PositionSnapshot,
+                RequestTakeback, AcceptTakeback, DeclineTakeback, ReceivedTakebackRequest,
+                DeclinedTakebackRequest, Takeback, SpectatorsCount, GameStarted, ReconnectGame,
+                OpponentDisconnected, OpponentReconnected
             ];
         ACTIONS.iter()
     }
@@ -107,6 +180,8 @@ impl GameAction
 pub struct Props
 {
     pub user: Rc<Option<AuthorizedUserResponse>>,
+    /// The server identifies the user of the websocket connection by the token
+    pub token: Rc<Option<String>>,
 }
 
 
@@ -116,7 +191,12 @@ struct State
     is_chat_room_defined: bool,
     websocket_chat_response: Option<WsResponse>,
     is_in_game: bool,
+    /// The game is watched, moves are only shown on the board
+    is_spectator: bool,
     piece_color: Option<PieceColor>,
+    variant: Variant,
+    time_control: Option<TimeControl>,
+    is_rated: bool,
     websocket_game_response: Option<WsResponse>,
 }
 
@@ -127,6 +207,7 @@ pub struct CheckersGame
     props: Props,
     state: State,
     websocket_task: Option<WebSocketTask>,
+    reconnect_task: Option<TimeoutTask>,
 }
 
 
@@ -137,9 +218,10 @@ pub enum WsAction
     ResetWebsocketChatResponse,
     Disconnect,
     Lost,
-    StartGame,
-    ChooseWhiteColor,
-    ChooseBlackColor,
+    WatchGame,
+    ChooseVariant(Variant),
+    ChooseTimeControl(Option<TimeControl>),
+    ChooseRated(bool),
     ResetWebsocketGameResponse,
     LeaveGame,
 }
@@ -162,6 +244,35 @@ impl From<WsAction> for Msg
 }
 
 
+impl CheckersGame
+{
+    /// The game is set up by the server, the current position comes with the game the player is back to
+    /// or it is requested from the server.
+    fn join_game(&mut self, joined_game: JoinedGame)
+    {
+        self.state.variant = joined_game.game.variant;
+        self.state.time_control = joined_game.game.time_control;
+        self.state.is_rated = joined_game.game.is_rated;
+        self.state.piece_color = Some(joined_game.piece_color);
+        self.state.is_in_game = true;
+        self.state.is_spectator = false;
+        if let Some(position) = joined_game.position
+        {
+            self.state.websocket_game_response = Some(WsResponse
+                {
+                    action: GameAction::PositionSnapshot.as_str(),
+                    data: serde_json::to_string(&position).unwrap(),
+                });
+        }
+        else if let Some(websocket_task) = self.websocket_task.as_mut()
+        {
+            let request = WsRequest { action: GameAction::RequestPositionSnapshot.as_str(), data: String::new() };
+            websocket_task.send(Json(&request));
+        }
+    }
+}
+
+
 impl Component for CheckersGame
 {
     type Message = Msg;
@@ -179,10 +290,15 @@ impl Component for CheckersGame
                     is_chat_room_defined: false,
                     websocket_chat_response: None,
                     is_in_game: false,
+                    is_spectator: false,
                     piece_color: None,
+                    variant: Variant::default(),
+                    time_control: None,
+                    is_rated: false,
                     websocket_game_response: None,
                 },
             websocket_task: None,
+            reconnect_task: None,
         }
     }
 
@@ -197,11 +313,11 @@ impl Component for CheckersGame
                 self.websocket_task.as_mut().unwrap().send(Json(&join_to_room_request));
                 if let Some(user) = &*self.props.user
                 {
-                    let set_name_request = WsRequest { action: ChatAction::SetName.as_str(), data: user.user_name.to_owned() };
-                    self.websocket_task.as_mut().unwrap().send(Json(&set_name_request));
                     let request_online_users = WsRequest { action: ChatAction::RequestOnlineUsers.as_str(), data: user.user_name.to_owned() };
                     self.websocket_task.as_mut().unwrap().send(Json(&request_online_users));
                 }
+                let request_live_games = WsRequest { action: ChatAction::RequestLiveGames.as_str(), data: String::new() };
+                self.websocket_task.as_mut().unwrap().send(Json(&request_live_games));
                 self.state.is_chat_room_defined = true;
             }
         }
@@ -221,10 +337,17 @@ impl Component for CheckersGame
                                     WebSocketStatus::Opened => Msg::Ignore,
                                     WebSocketStatus::Closed | WebSocketStatus::Error => WsAction::Lost.into(),
                                 });
+                                // browsers do not send headers with websocket requests, so the token goes in the query
+                                let url = match &*self.props.token
+                                {
+                                    Some(token) => format!("{}?token={}", WEBSOCKET_URL, token),
+                                    None => WEBSOCKET_URL.to_owned(),
+                                };
                                 let task =
-                                    WebSocketService::connect(WEBSOCKET_URL, callback, notification)
+                                    WebSocketService::connect(&url, callback, notification)
                                         .unwrap();
                                 self.websocket_task = Some(task);
+                                self.reconnect_task = None;
                                 self.state.is_connected = true;
                                 self.state.is_chat_room_defined = false;
                             },
@@ -244,15 +367,32 @@ impl Component for CheckersGame
                                 self.websocket_task.take();
                                 self.state.is_connected = false;
                                 self.state.is_in_game = false;
+                                self.state.is_spectator = false;
                                 self.state.piece_color = None;
                             },
-                        WsAction::Lost => self.websocket_task = None,
-                        WsAction::StartGame => self.state.is_in_game = true,
-                        WsAction::ChooseWhiteColor => self.state.piece_color = Some(PieceColor::White),
-                        WsAction::ChooseBlackColor => self.state.piece_color = Some(PieceColor::Black),
+                        WsAction::Lost =>
+                            {
+                                self.websocket_task = None;
+                                // the server holds the unfinished game for a while, so the player is able to come back
+                                if self.state.is_in_game && !self.state.is_spectator
+                                {
+                                    let callback = self.link.callback(|_| WsAction::Connect);
+                                    self.reconnect_task = Some(TimeoutService::spawn(RECONNECT_DELAY, callback));
+                                }
+                            },
+                        WsAction::WatchGame =>
+                            {
+                                self.state.is_in_game = true;
+                                self.state.is_spectator = true;
+                                self.state.piece_color = None;
+                            },
+                        WsAction::ChooseVariant(variant) => self.state.variant = variant,
+                        WsAction::ChooseTimeControl(time_control) => self.state.time_control = time_control,
+                        WsAction::ChooseRated(is_rated) => self.state.is_rated = is_rated,
                         WsAction::LeaveGame =>
                             {
                                 self.state.is_in_game = false;
+                                self.state.is_spectator = false;
                                 self.state.piece_color = None;
                                 self.state.websocket_game_response = None;
                             },
@@ -270,6 +410,14 @@ impl Component for CheckersGame
                             self.state.websocket_chat_response = Some(received_data);
                         }
 
+                        else if received_data.action == GameAction::GameStarted.as_str() ||
+                            received_data.action == GameAction::ReconnectGame.as_str()
+                        {
+                            if let Ok(joined_game) = serde_json::from_str::<JoinedGame>(&received_data.data)
+                            {
+                                self.join_game(joined_game);
+                            }
+                        }
                         else if let Some(_) = GameAction::iterator()
                             .position(|action| action.as_str() == received_data.action)
                         {
@@ -285,7 +433,7 @@ impl Component for CheckersGame
 
     fn change(&mut self, props: Self::Properties) -> ShouldRender
     {
-        if !Rc::ptr_eq(&self.props.user, &props.user)
+        if !Rc::ptr_eq(&self.props.user, &props.user) || !Rc::ptr_eq(&self.props.token, &props.token)
         {
             self.props = props;
             true
@@ -315,9 +463,10 @@ impl Component for CheckersGame
                                 websocket_chat_response=&self.state.websocket_chat_response,
                                 reset_websocket_chat_response=self.link.callback(|_| Msg::WsAction(WsAction::ResetWebsocketChatResponse)),
                                 is_in_game=&self.state.is_in_game,
-                                start_game=self.link.callback(|_| Msg::WsAction(WsAction::StartGame)),
-                                choose_white_color=self.link.callback(|_| Msg::WsAction(WsAction::ChooseWhiteColor)),
-                                choose_black_color=self.link.callback(|_| Msg::WsAction(WsAction::ChooseBlackColor)),
+                                watch_game=self.link.callback(|_| Msg::WsAction(WsAction::WatchGame)),
+                                choose_variant=self.link.callback(|variant| Msg::WsAction(WsAction::ChooseVariant(variant))),
+                                choose_time_control=self.link.callback(|time_control| Msg::WsAction(WsAction::ChooseTimeControl(time_control))),
+                                choose_rated=self.link.callback(|is_rated| Msg::WsAction(WsAction::ChooseRated(is_rated))),
                              />
                         </div>
 
@@ -327,8 +476,12 @@ impl Component for CheckersGame
                                 <CheckersBoard
                                     user=Rc::clone(&self.props.user),
                                     is_in_game=&self.state.is_in_game,
+                                    is_spectator=self.state.is_spectator,
                                     send_websocket_data=self.link.callback(|request| Msg::WsAction(WsAction::SendWebSocketData(request))),
                                     piece_color=&self.state.piece_color,
+                                    variant=&self.state.variant,
+                                    time_control=&self.state.time_control,
+                                    is_rated=self.state.is_rated,
                                     websocket_game_response=&self.state.websocket_game_response,
                                     reset_websocket_game_response=self.link.callback(|_| Msg::WsAction(WsAction::ResetWebsocketGameResponse)),
                                     leave_game=self.link.callback(|_| Msg::WsAction(WsAction::LeaveGame)),
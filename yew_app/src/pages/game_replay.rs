@@ -0,0 +1,271 @@
+use yew::prelude::*;
+use yew::services::interval::{IntervalService, IntervalTask};
+use yew::services::storage::{Area, StorageService};
+use yew::format::Json;
+use anyhow::Error;
+
+use std::rc::Rc;
+use std::time::Duration;
+
+use checkers_rules::{Game, PdnMove, square_number};
+
+use crate::types::{AuthorizedUserResponse, GameRecord, Board};
+use crate::components::CheckersBoard;
+use crate::pages::GAME_RECORD_KEY;
+
+
+const AUTOPLAY_INTERVAL: Duration = Duration::from_millis(1500);
+
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct Props
+{
+    pub user: Rc<Option<AuthorizedUserResponse>>,
+}
+
+
+/// Positions after every ply of the recorded game, the first one is the starting position.
+struct ReplayedGame
+{
+    record: GameRecord,
+    positions: Vec<Board>,
+    notations: Vec<String>,
+}
+
+
+impl ReplayedGame
+{
+    /// Moves are replayed until the first one which does not fit the rules.
+    fn new(record: GameRecord) -> Option<Self>
+    {
+        let mut game = Game::from_fen(record.variant, &record.initial_fen).ok()?;
+        let mut positions = vec![game.board.clone()];
+        let mut notations = Vec::new();
+        for game_data in &record.moves
+        {
+            if game.apply_move(game_data).is_err()
+            {
+                break;
+            }
+            let size = game.board.size;
+            let pdn_move = PdnMove
+                {
+                    squares: game_data.path.iter().map(|position| square_number(position, size)).collect(),
+                    is_capture: !game_data.captured_pieces_positions.is_empty(),
+                };
+            positions.push(game.board.clone());
+            notations.push(pdn_move.to_string());
+        }
+        Some(ReplayedGame { record, positions, notations })
+    }
+}
+
+
+struct State
+{
+    replayed_game: Option<ReplayedGame>,
+    /// Number of played moves in the shown position
+    ply: usize,
+}
+
+
+pub struct GameReplay
+{
+    link: ComponentLink<Self>,
+    props: Props,
+    state: State,
+    autoplay_task: Option<IntervalTask>,
+}
+
+
+pub enum Msg
+{
+    ToStart,
+    StepBack,
+    StepForward,
+    ToEnd,
+    GoTo(usize),
+    ToggleAutoplay,
+    AutoplayTick,
+}
+
+
+impl GameReplay
+{
+    fn last_ply(&self) -> usize
+    {
+        self.state.replayed_game.as_ref().map_or(0, |replayed_game| replayed_game.notations.len())
+    }
+
+
+    fn view_move_list(&self, replayed_game: &ReplayedGame) -> Html
+    {
+        html!
+        {
+            <ol class="replay_moves">
+                {
+                    for replayed_game.notations.chunks(2).enumerate().map(|(idx, notations)|
+                    {
+                        html!
+                        {
+                            <li>
+                                {
+                                    for notations.iter().enumerate().map(|(shift, notation)|
+                                    {
+                                        let ply = idx * 2 + shift + 1;
+                                        let class = if ply == self.state.ply { "replay_move current_ply" } else { "replay_move" };
+                                        html!
+                                        {
+                                            <span class=class onclick=self.link.callback(move |_| Msg::GoTo(ply))>
+                                                { notation }
+                                            </span>
+                                        }
+                                    })
+                                }
+                            </li>
+                        }
+                    })
+                }
+            </ol>
+        }
+    }
+}
+
+
+impl Component for GameReplay
+{
+    type Message = Msg;
+    type Properties = Props;
+
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self
+    {
+        let storage = StorageService::new(Area::Local).expect("storage was disabled by the user");
+        let Json(record): Json<Result<GameRecord, Error>> = storage.restore(GAME_RECORD_KEY);
+        let replayed_game = record.ok().and_then(ReplayedGame::new);
+        Self
+        {
+            link,
+            props,
+            state: State { replayed_game, ply: 0 },
+            autoplay_task: None,
+        }
+    }
+
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender
+    {
+        let last_ply = self.last_ply();
+        match msg
+        {
+            Msg::ToStart => self.state.ply = 0,
+            Msg::StepBack => self.state.ply = self.state.ply.saturating_sub(1),
+            Msg::StepForward => self.state.ply = last_ply.min(self.state.ply + 1),
+            Msg::ToEnd => self.state.ply = last_ply,
+            Msg::GoTo(ply) => self.state.ply = last_ply.min(ply),
+            Msg::ToggleAutoplay =>
+                {
+                    if self.autoplay_task.take().is_none()
+                    {
+                        if self.state.ply == last_ply
+                        {
+                            self.state.ply = 0;
+                        }
+                        let callback = self.link.callback(|_| Msg::AutoplayTick);
+                        self.autoplay_task = Some(IntervalService::spawn(AUTOPLAY_INTERVAL, callback));
+                    }
+                },
+            Msg::AutoplayTick =>
+                {
+                    if self.state.ply < last_ply
+                    {
+                        self.state.ply += 1;
+                    }
+                    else
+                    {
+                        self.autoplay_task = None;
+                    }
+                },
+        }
+        true
+    }
+
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender
+    {
+        if self.props != props
+        {
+            self.props = props;
+            true
+        }
+        else
+        {
+            false
+        }
+    }
+
+
+    fn view(&self) -> Html
+    {
+        if let Some(replayed_game) = &self.state.replayed_game
+        {
+            let last_ply = self.last_ply();
+            html!
+            {
+                <main class="main">
+                    <div class="container">
+                        <div class="field">
+                            <div class="container">
+                                <h3>{ format!("Replay: {}", replayed_game.record.variant.as_str()) }</h3>
+                                { self.view_move_list(replayed_game) }
+                            </div>
+                            <div>
+                                <CheckersBoard
+                                    user=Rc::clone(&self.props.user),
+                                    is_in_game=false,
+                                    send_websocket_data=Callback::noop(),
+                                    piece_color=None,
+                                    variant=replayed_game.record.variant,
+                                    time_control=None,
+                                    is_rated=false,
+                                    websocket_game_response=None,
+                                    reset_websocket_game_response=Callback::noop(),
+                                    leave_game=Callback::noop(),
+                                    replay_board=Some(replayed_game.positions[self.state.ply].clone()),
+                                />
+                                <div class="replay_controls">
+                                    <button disabled=self.state.ply == 0 onclick=self.link.callback(|_| Msg::ToStart)>
+                                        { "|<" }
+                                    </button>
+                                    <button disabled=self.state.ply == 0 onclick=self.link.callback(|_| Msg::StepBack)>
+                                        { "<" }
+                                    </button>
+                                    <button onclick=self.link.callback(|_| Msg::ToggleAutoplay)>
+                                        { if self.autoplay_task.is_some() { "Pause" } else { "Play" } }
+                                    </button>
+                                    <button disabled=self.state.ply == last_ply onclick=self.link.callback(|_| Msg::StepForward)>
+                                        { ">" }
+                                    </button>
+                                    <button disabled=self.state.ply == last_ply onclick=self.link.callback(|_| Msg::ToEnd)>
+                                        { ">|" }
+                                    </button>
+                                </div>
+                            </div>
+                        </div>
+                    </div>
+                </main>
+            }
+        }
+        else
+        {
+            html!
+            {
+                <main class="main">
+                    <div class="container">
+                        <h3>{ "There is no recorded game yet" }</h3>
+                    </div>
+                </main>
+            }
+        }
+    }
+}
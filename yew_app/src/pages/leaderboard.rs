@@ -0,0 +1,311 @@
+use yew::prelude::*;
+use yew::services::fetch::{FetchService, FetchTask, Request, Response};
+use yew::format::{Nothing, Json};
+use anyhow::Error;
+
+use crate::types::{Leaderboard as LeaderboardPage, LeaderboardEntry, Variant, TimeControl};
+use crate::components::TIME_CONTROLS;
+
+
+type FetchResponse<T> = Response<Json<Result<T, Error>>>;
+
+
+const PER_PAGE: u32 = 20;
+
+
+struct State
+{
+    variant: Option<Variant>,
+    time_control: Option<TimeControl>,
+    min_games: u32,
+    page: u32,
+    leaderboard: Option<LeaderboardPage>,
+    error_message: Option<String>,
+}
+
+
+pub struct Leaderboard
+{
+    link: ComponentLink<Self>,
+    state: State,
+    fetch_task: Option<FetchTask>,
+}
+
+
+pub enum Msg
+{
+    ChooseVariant(String),
+    ChooseTimeControl(String),
+    UpdateMinGames(String),
+    RequestLeaderboard,
+    ChangePage(u32),
+    LeaderboardReceived(Result<LeaderboardPage, Error>),
+    RequestFailed(String),
+}
+
+
+impl Leaderboard
+{
+    fn leaderboard_url(&self) -> String
+    {
+        let mut url = format!(
+            "/leaderboard?page={}&per_page={}&min_games={}", self.state.page, PER_PAGE, self.state.min_games);
+        if let Some(variant) = &self.state.variant
+        {
+            url.push_str(&format!("&variant={}", variant.as_str()));
+        }
+        if let Some(time_control) = &self.state.time_control
+        {
+            url.push_str(&format!("&time_control={}", time_control.as_key()));
+        }
+        url
+    }
+
+
+    fn request_leaderboard(&self) -> FetchTask
+    {
+        let callback = self.link.callback(
+            move |response: FetchResponse<LeaderboardPage>|
+                {
+                    let (meta, Json(data)) = response.into_parts();
+                    if meta.status.is_success()
+                    {
+                        Msg::LeaderboardReceived(data)
+                    }
+                    else
+                    {
+                        Msg::RequestFailed(format!("Leaderboard is not available: {}", meta.status))
+                    }
+                },
+            );
+        let request = Request::get(self.leaderboard_url())
+            .body(Nothing)
+            .unwrap();
+        FetchService::fetch(request, callback).unwrap()
+    }
+
+
+    fn view_filters(&self) -> Html
+    {
+        html!
+        {
+            <div>
+                { "Rules: " }
+                <select
+                    onchange=self.link.callback(|e: ChangeData|
+                        match e
+                        {
+                            ChangeData::Select(select) => Msg::ChooseVariant(select.value()),
+                            _ => Msg::ChooseVariant(String::new()),
+                        })>
+                    <option value="" selected=self.state.variant.is_none()>{ "All" }</option>
+                    {
+                        for Variant::iterator().map(|variant: &Variant|
+                        html!
+                        {
+                            <option value=variant.as_str() selected=Some(*variant) == self.state.variant>
+                                { variant.as_str() }
+                            </option>
+                        })
+                    }
+                </select>
+                { " Time: " }
+                <select
+                    onchange=self.link.callback(|e: ChangeData|
+                        match e
+                        {
+                            ChangeData::Select(select) => Msg::ChooseTimeControl(select.value()),
+                            _ => Msg::ChooseTimeControl(String::new()),
+                        })>
+                    <option value="" selected=self.state.time_control.is_none()>{ "All" }</option>
+                    {
+                        for TIME_CONTROLS.iter().flatten().map(|time_control: &TimeControl|
+                        html!
+                        {
+                            <option value=time_control.as_key() selected=Some(*time_control) == self.state.time_control>
+                                { time_control.as_string() }
+                            </option>
+                        })
+                    }
+                </select>
+                { " Games at least: " }
+                <input
+                    type="number"
+                    min="0"
+                    value=self.state.min_games.to_string()
+                    onchange=self.link.callback(|e: ChangeData|
+                        match e
+                        {
+                            ChangeData::Value(value) => Msg::UpdateMinGames(value),
+                            _ => Msg::UpdateMinGames(String::new()),
+                        })
+                />
+            </div>
+        }
+    }
+
+
+    fn view_pages(&self, leaderboard: &LeaderboardPage) -> Html
+    {
+        let page = leaderboard.page;
+        let pages_number = ((leaderboard.total + leaderboard.per_page - 1) / leaderboard.per_page).max(1);
+        html!
+        {
+            <div>
+                <button
+                    disabled=(self.fetch_task.is_some() || page <= 1)
+                    onclick=self.link.callback(move |_| Msg::ChangePage(page - 1))>
+                    { "Previous" }
+                </button>
+                { format!(" {} / {} ", page, pages_number) }
+                <button
+                    disabled=(self.fetch_task.is_some() || page >= pages_number)
+                    onclick=self.link.callback(move |_| Msg::ChangePage(page + 1))>
+                    { "Next" }
+                </button>
+            </div>
+        }
+    }
+}
+
+
+impl Component for Leaderboard
+{
+    type Message = Msg;
+    type Properties = ();
+
+
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self
+    {
+        link.send_message(Msg::RequestLeaderboard);
+        Self
+        {
+            link,
+            state: State
+                {
+                    variant: None, time_control: None, min_games: 1, page: 1,
+                    leaderboard: None, error_message: None,
+                },
+            fetch_task: None,
+        }
+    }
+
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender
+    {
+        match msg
+        {
+            Msg::ChooseVariant(name) =>
+                {
+                    self.state.variant = Variant::from_name(&name);
+                    self.state.page = 1;
+                    self.link.send_message(Msg::RequestLeaderboard);
+                },
+            Msg::ChooseTimeControl(key) =>
+                {
+                    self.state.time_control = TimeControl::from_key(&key);
+                    self.state.page = 1;
+                    self.link.send_message(Msg::RequestLeaderboard);
+                },
+            Msg::UpdateMinGames(value) =>
+                {
+                    self.state.min_games = value.parse().unwrap_or(0);
+                    self.state.page = 1;
+                    self.link.send_message(Msg::RequestLeaderboard);
+                },
+            Msg::ChangePage(page) =>
+                {
+                    self.state.page = page;
+                    self.link.send_message(Msg::RequestLeaderboard);
+                },
+            Msg::RequestLeaderboard =>
+                {
+                    self.state.error_message = None;
+                    let task = self.request_leaderboard();
+                    self.fetch_task = Some(task);
+                },
+            Msg::LeaderboardReceived(response) =>
+                {
+                    self.fetch_task = None;
+                    match response
+                    {
+                        Ok(leaderboard) => self.state.leaderboard = Some(leaderboard),
+                        Err(e) => self.state.error_message = Some(e.to_string()),
+                    }
+                },
+            Msg::RequestFailed(message) =>
+                {
+                    self.fetch_task = None;
+                    self.state.error_message = Some(message);
+                },
+        }
+        true
+    }
+
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender
+    {
+        false
+    }
+
+
+    fn view(&self) -> Html
+    {
+        html!
+        {
+            <main class="main">
+                <div class="container">
+                    <h3>{ "Leaderboard" }</h3>
+                    { self.view_filters() }
+                    {
+                        if let Some(message) = &self.state.error_message
+                        {
+                            html! { <p>{ message }</p> }
+                        }
+                        else { html! {} }
+                    }
+                    {
+                        if let Some(leaderboard) = &self.state.leaderboard
+                        {
+                            html!
+                            {
+                                <>
+                                    <table class="checkers_leaderboard">
+                                        <thead>
+                                            <tr>
+                                                <th>{ "#" }</th>
+                                                <th>{ "Player" }</th>
+                                                <th>{ "Rating" }</th>
+                                                <th>{ "Wins" }</th>
+                                                <th>{ "Losses" }</th>
+                                                <th>{ "Draws" }</th>
+                                            </tr>
+                                        </thead>
+                                        <tbody>
+                                        {
+                                            for leaderboard.entries.iter().map(|entry: &LeaderboardEntry|
+                                            html!
+                                            {
+                                                <tr>
+                                                    <td>{ entry.rank }</td>
+                                                    <td>{ &entry.user_name }</td>
+                                                    <td>{ entry.rating }</td>
+                                                    <td>{ entry.wins }</td>
+                                                    <td>{ entry.losses }</td>
+                                                    <td>{ entry.draws }</td>
+                                                </tr>
+                                            })
+                                        }
+                                        </tbody>
+                                    </table>
+                                    { self.view_pages(leaderboard) }
+                                </>
+                            }
+                        }
+                        else { html! {} }
+                    }
+                </div>
+            </main>
+        }
+    }
+}
@@ -3,9 +3,15 @@ mod sign_in_user;
 mod register_user;
 mod user_info;
 mod checkers_game;
+mod game_replay;
+mod puzzles;
+mod leaderboard;
 
 pub use homepage::HomePage;
 pub use sign_in_user::SignInUser;
 pub use register_user::RegisterUser;
 pub use user_info::UserInfo;
-pub use checkers_game::{CheckersGame, ChatAction, GameAction, GAME_NAME};
+pub use checkers_game::{CheckersGame, ChatAction, GameAction, GAME_NAME, GAME_RECORD_KEY};
+pub use game_replay::GameReplay;
+pub use puzzles::Puzzles;
+pub use leaderboard::Leaderboard;
@@ -0,0 +1,325 @@
+use yew::prelude::*;
+use yew::services::fetch::{FetchService, FetchTask, Request, Response};
+use yew::format::{Nothing, Json};
+use anyhow::Error;
+
+use std::rc::Rc;
+
+use checkers_rules::Game;
+
+use crate::types::{AuthorizedUserResponse, Puzzle, PuzzleAttempt, PuzzleAttemptResult, PuzzleMoveResult, GameData, PieceColor};
+use crate::components::CheckersBoard;
+use crate::KEY;
+
+
+type FetchResponse<T> = Response<Json<Result<T, Error>>>;
+
+
+/// Side to move in the start position, the player plays it.
+fn puzzle_turn(puzzle: &Puzzle) -> Option<PieceColor>
+{
+    Game::from_fen(puzzle.variant, &puzzle.fen).ok().map(|game| game.turn)
+}
+
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct Props
+{
+    pub user: Rc<Option<AuthorizedUserResponse>>,
+    pub token: Rc<Option<String>>,
+}
+
+
+struct State
+{
+    puzzle: Option<Puzzle>,
+    /// Moves of the player checked by the server so far
+    moves: Vec<GameData>,
+    /// Reply of the opponent to the last correct move
+    reply: Option<GameData>,
+    attempt_result: Option<PuzzleAttemptResult>,
+    error_message: Option<String>,
+}
+
+
+pub struct Puzzles
+{
+    link: ComponentLink<Self>,
+    props: Props,
+    state: State,
+    fetch_task: Option<FetchTask>,
+}
+
+
+pub enum Msg
+{
+    RequestPuzzle,
+    PuzzleReceived(Result<Puzzle, Error>),
+    PlayMove(GameData),
+    MoveResultReceived(Result<PuzzleMoveResult, Error>),
+    RequestFailed(String),
+}
+
+
+impl Puzzles
+{
+    fn request_puzzle(&self, token: &str) -> FetchTask
+    {
+        let callback = self.link.callback(
+            move |response: FetchResponse<Puzzle>|
+                {
+                    let (meta, Json(data)) = response.into_parts();
+                    if meta.status.is_success()
+                    {
+                        Msg::PuzzleReceived(data)
+                    }
+                    else
+                    {
+                        Msg::RequestFailed(format!("Puzzle is not available: {}", meta.status))
+                    }
+                },
+            );
+        let request = Request::get("/puzzle/next")
+            .header(KEY, token)
+            .body(Nothing)
+            .unwrap();
+        FetchService::fetch(request, callback).unwrap()
+    }
+
+
+    fn send_move(&self, attempt: PuzzleAttempt, token: &str) -> FetchTask
+    {
+        let callback = self.link.callback(
+            move |response: FetchResponse<PuzzleMoveResult>|
+                {
+                    let (meta, Json(data)) = response.into_parts();
+                    if meta.status.is_success()
+                    {
+                        Msg::MoveResultReceived(data)
+                    }
+                    else
+                    {
+                        Msg::RequestFailed(format!("Move was not checked: {}", meta.status))
+                    }
+                },
+            );
+        let request = Request::post("/puzzle/move")
+            .header("Content-Type", "application/json")
+            .header(KEY, token)
+            .body(Json(&attempt))
+            .unwrap();
+        FetchService::fetch(request, callback).unwrap()
+    }
+
+
+    fn view_status(&self, puzzle: &Puzzle) -> Html
+    {
+        if let Some(attempt_result) = &self.state.attempt_result
+        {
+            html!
+            {
+                <p>
+                    { if attempt_result.is_solved { "Solved! " } else { "Wrong move. " } }
+                    { format!("Your rating: {} ({:+})", attempt_result.player_rating, attempt_result.rating_change) }
+                </p>
+            }
+        }
+        else
+        {
+            html!
+            {
+                <p>
+                    {
+                        match puzzle_turn(puzzle)
+                        {
+                            Some(PieceColor::White) => "White to move, find the best line. ",
+                            Some(PieceColor::Black) => "Black to move, find the best line. ",
+                            None => "",
+                        }
+                    }
+                    { format!("Your rating: {}", puzzle.player_rating) }
+                </p>
+            }
+        }
+    }
+}
+
+
+impl Component for Puzzles
+{
+    type Message = Msg;
+    type Properties = Props;
+
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self
+    {
+        link.send_message(Msg::RequestPuzzle);
+        Self
+        {
+            link, props,
+            state: State { puzzle: None, moves: Vec::new(), reply: None, attempt_result: None, error_message: None },
+            fetch_task: None,
+        }
+    }
+
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender
+    {
+        match msg
+        {
+            Msg::RequestPuzzle =>
+                {
+                    if let Some(token) = &*self.props.token
+                    {
+                        // the board is shown again from the start position of the next puzzle
+                        self.state.puzzle = None;
+                        self.state.moves = Vec::new();
+                        self.state.reply = None;
+                        self.state.attempt_result = None;
+                        self.state.error_message = None;
+                        let task = self.request_puzzle(token);
+                        self.fetch_task = Some(task);
+                    }
+                    else { return false; }
+                },
+            Msg::PuzzleReceived(response) =>
+                {
+                    self.fetch_task = None;
+                    match response
+                    {
+                        Ok(puzzle) => self.state.puzzle = Some(puzzle),
+                        Err(e) => self.state.error_message = Some(e.to_string()),
+                    }
+                },
+            Msg::PlayMove(game_data) =>
+                {
+                    match (&self.state.puzzle, &*self.props.token)
+                    {
+                        (Some(puzzle), Some(token)) =>
+                            {
+                                self.state.moves.push(game_data);
+                                let attempt = PuzzleAttempt { puzzle_id: puzzle.id, moves: self.state.moves.clone() };
+                                let task = self.send_move(attempt, token);
+                                self.fetch_task = Some(task);
+                            },
+                        _ => return false,
+                    }
+                },
+            Msg::MoveResultReceived(response) =>
+                {
+                    self.fetch_task = None;
+                    match response
+                    {
+                        Ok(move_result) =>
+                            {
+                                self.state.reply = move_result.reply;
+                                self.state.attempt_result = move_result.result;
+                            },
+                        Err(e) => self.state.error_message = Some(e.to_string()),
+                    }
+                },
+            Msg::RequestFailed(message) =>
+                {
+                    self.fetch_task = None;
+                    self.state.error_message = Some(message);
+                },
+        }
+        true
+    }
+
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender
+    {
+        if self.props != props
+        {
+            let is_signed_in = self.props.token.is_none() && props.token.is_some();
+            self.props = props;
+            if is_signed_in
+            {
+                self.link.send_message(Msg::RequestPuzzle);
+            }
+            true
+        }
+        else
+        {
+            false
+        }
+    }
+
+
+    fn view(&self) -> Html
+    {
+        if self.props.token.is_none()
+        {
+            return html!
+            {
+                <main class="main">
+                    <div class="container">
+                        <h3>{ "Sign in to solve puzzles" }</h3>
+                    </div>
+                </main>
+            };
+        }
+        html!
+        {
+            <main class="main">
+                <div class="container">
+                    <div class="field">
+                        <div class="container">
+                            <h3>{ "Puzzles" }</h3>
+                            {
+                                if let Some(message) = &self.state.error_message
+                                {
+                                    html! { <p>{ message }</p> }
+                                }
+                                else { html! {} }
+                            }
+                            {
+                                if let Some(puzzle) = &self.state.puzzle
+                                {
+                                    html!
+                                    {
+                                        <>
+                                            <p>{ format!("{}, puzzle rating: {}", puzzle.variant.as_str(), puzzle.rating) }</p>
+                                            { self.view_status(puzzle) }
+                                        </>
+                                    }
+                                }
+                                else { html! {} }
+                            }
+                            <button
+                                disabled=self.fetch_task.is_some()
+                                onclick=self.link.callback(|_| Msg::RequestPuzzle)>
+                                { "Next puzzle" }
+                            </button>
+                        </div>
+                        {
+                            if let Some(puzzle) = &self.state.puzzle
+                            {
+                                html!
+                                {
+                                    <CheckersBoard
+                                        user=Rc::clone(&self.props.user),
+                                        is_in_game=self.state.attempt_result.is_none(),
+                                        send_websocket_data=Callback::noop(),
+                                        piece_color=puzzle_turn(puzzle),
+                                        variant=puzzle.variant,
+                                        time_control=None,
+                                        is_rated=true,
+                                        websocket_game_response=None,
+                                        reset_websocket_game_response=Callback::noop(),
+                                        leave_game=Callback::noop(),
+                                        puzzle=Some(puzzle.clone()),
+                                        puzzle_move=self.link.callback(Msg::PlayMove),
+                                        puzzle_reply=self.state.reply.clone(),
+                                    />
+                                }
+                            }
+                            else { html! {} }
+                        }
+                    </div>
+                </div>
+            </main>
+        }
+    }
+}
@@ -1,13 +1,13 @@
 use yew::prelude::*;
 use yew::services::fetch::{FetchService, FetchTask, Request, Response};
-use yew::format::Json;
+use yew::format::{Json, Nothing};
 use anyhow::Error;
 
 use validator;
 
 use std::rc::Rc;
 
-use crate::types::{AuthorizedUserResponse, UserUpdateDataRequest};
+use crate::types::{AuthorizedUserResponse, UserUpdateDataRequest, UserStats, ResultCounts, OpponentStats};
 use crate::KEY;
 
 use crate::components::AllUsers;
@@ -29,7 +29,8 @@ struct State
     edited_password: Option<String>,
     edited_retyped_password: Option<String>,
     error_message: Option<String>,
-    data_update_success_message: Option<String>
+    data_update_success_message: Option<String>,
+    user_stats: Option<UserStats>,
 }
 
 
@@ -45,6 +46,7 @@ impl Default for State
                 edited_retyped_password: None,
                 error_message: None,
                 data_update_success_message: None,
+                user_stats: None,
             }
         }
 }
@@ -56,6 +58,7 @@ pub struct UserInfo
     props: Props,
     state: State,
     fetch_task: Option<FetchTask>,
+    stats_fetch_task: Option<FetchTask>,
 }
 
 
@@ -68,7 +71,9 @@ pub enum Msg
     Save,
     UpdateUser(UserUpdateDataRequest),
     SuccessfulUpdate(Result<String, Error>),
-    UnsuccessfulUpdate(Result<String, Error>)
+    UnsuccessfulUpdate(Result<String, Error>),
+    RequestUserStats,
+    UserStatsReceived(Option<UserStats>),
 }
 
 
@@ -198,6 +203,87 @@ impl UserInfo
             .unwrap();
         FetchService::fetch(request, callback).unwrap()
     }
+
+
+    fn request_user_stats(&self, user_name: &str) -> FetchTask
+    {
+        let callback = self.link.callback(
+            move |response: Response<Json<Result<UserStats, Error>>>|
+                {
+                    let (meta, Json(data)) = response.into_parts();
+                    if meta.status.is_success()
+                    {
+                        Msg::UserStatsReceived(data.ok())
+                    }
+                    else
+                    {
+                        Msg::UserStatsReceived(None)
+                    }
+                },
+            );
+        let request = Request::get(format!("/game/user_stats/{}", user_name))
+            .body(Nothing)
+            .unwrap();
+        FetchService::fetch(request, callback).unwrap()
+    }
+
+
+    fn view_result_counts(&self, title: &str, results: &ResultCounts) -> Html
+    {
+        html!
+        {
+            <tr>
+                <td>{ title }</td>
+                <td>{ results.games }</td>
+                <td>{ results.wins }</td>
+                <td>{ results.losses }</td>
+                <td>{ results.draws }</td>
+            </tr>
+        }
+    }
+
+
+    fn view_user_stats(&self) -> Html
+    {
+        let user_stats = match &self.state.user_stats
+        {
+            Some(user_stats) => user_stats,
+            None => return html! {},
+        };
+        html!
+        {
+            <div class="user_info_container">
+                <h3>{ "Statistics." }</h3>
+                <table class="user_stats">
+                    <thead>
+                        <tr>
+                            <th></th>
+                            <th>{ "Games" }</th>
+                            <th>{ "Wins" }</th>
+                            <th>{ "Losses" }</th>
+                            <th>{ "Draws" }</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        { self.view_result_counts("Total", &user_stats.total) }
+                        { self.view_result_counts("White", &user_stats.as_white) }
+                        { self.view_result_counts("Black", &user_stats.as_black) }
+                        {
+                            for user_stats.frequent_opponents.iter().map(|opponent: &OpponentStats|
+                                self.view_result_counts(&format!("vs {}", opponent.user_name), &opponent.results))
+                        }
+                    </tbody>
+                </table>
+                {
+                    if let Some(streak) = &user_stats.current_streak
+                    {
+                        html! { <p>{ format!("Current streak: {} x {}", streak.length, streak.result) }</p> }
+                    }
+                    else { html! {} }
+                }
+            </div>
+        }
+    }
 }
 
 
@@ -209,10 +295,11 @@ impl Component for UserInfo
 
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self
     {
+        link.send_message(Msg::RequestUserStats);
         Self
         {
             props, link, state: State::default(),
-            fetch_task: None
+            fetch_task: None, stats_fetch_task: None,
         }
     }
 
@@ -257,6 +344,20 @@ impl Component for UserInfo
                     self.props.sign_out.emit(());
                 },
             Msg::UnsuccessfulUpdate(message) => self.state.error_message = Some(message.unwrap()),
+            Msg::RequestUserStats =>
+                {
+                    if let Some(user) = &*self.props.user
+                    {
+                        let task = self.request_user_stats(&user.user_name);
+                        self.stats_fetch_task = Some(task);
+                    }
+                    return false;
+                },
+            Msg::UserStatsReceived(user_stats) =>
+                {
+                    self.stats_fetch_task = None;
+                    self.state.user_stats = user_stats;
+                },
         }
         true
     }
@@ -266,7 +367,12 @@ impl Component for UserInfo
     {
         if self.props != props
         {
+            let is_user_changed = self.props.user != props.user;
             self.props = props;
+            if is_user_changed
+            {
+                self.link.send_message(Msg::RequestUserStats);
+            }
             true
         }
         else
@@ -339,6 +445,8 @@ impl Component for UserInfo
 
                                             </div>
 
+                                            { self.view_user_stats() }
+
                                             {
                                                 if user.is_superuser
                                                 {
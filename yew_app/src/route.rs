@@ -10,6 +10,12 @@ pub enum AppRoute
     RegisterUser,
     #[to = "/#auth/user_info"]
     UserInfo,
+    #[to = "/#checkers/replay"]
+    GameReplay,
+    #[to = "/#checkers/puzzles"]
+    Puzzles,
+    #[to = "/#checkers/leaderboard"]
+    Leaderboard,
     #[to = "/#checkers"]
     CheckersGame,
     #[to = "/"]
@@ -1,12 +1,22 @@
 use serde::{Deserialize, Serialize};
 
+use std::collections::HashMap;
+
+pub use checkers_rules::
+    {
+        PieceColor, CheckerPosition, CheckerPiece, GameData, AllowableMove, Variant, TimeControl, ClockData, Board,
+        Analysis,
+    };
+
 
 #[derive(Deserialize, PartialEq, Clone)]
 pub struct AuthorizedUserResponse
 {
     pub user_name: String,
     pub email: String,
-    pub is_superuser: bool
+    pub is_superuser: bool,
+    #[serde(default)]
+    pub rating: i32,
 }
 
 
@@ -88,79 +98,226 @@ pub struct ChatMessageResponse
 pub struct ChatMessage(pub String);
 
 
-#[derive(Hash, Eq, PartialEq, Debug, Clone)]
-pub struct OnlineUser(pub String);
+#[derive(Deserialize, Hash, Eq, PartialEq, Debug, Clone)]
+pub struct OnlineUser
+{
+    pub user_name: String,
+    pub rating: i32,
+}
+
+
+/// Game of the tournament round started by the server for the player.
+#[derive(Deserialize)]
+pub struct TournamentGameReady
+{
+    pub tournament_name: String,
+    pub round: i32,
+    pub opponent: String,
+    pub piece_color: PieceColor,
+}
+
+
+/// Rules of the game looked for in the matchmaking queue.
+#[derive(Serialize)]
+pub struct GameSearch
+{
+    pub variant: Variant,
+    pub time_control: Option<TimeControl>,
+    pub is_rated: bool,
+}
+
+
+#[derive(Serialize, Deserialize)]
+pub struct InvitationData
+{
+    pub user_name: String,
+    pub variant: Variant,
+    pub time_control: Option<TimeControl>,
+    /// Position the game starts from in the FEN notation
+    #[serde(default)]
+    pub initial_fen: Option<String>,
+    /// Hints are turned off in rated games
+    #[serde(default)]
+    pub is_rated: bool,
+    /// Color of the inviting user, it is chosen at random when absent
+    #[serde(default)]
+    pub color_preference: Option<PieceColor>,
+}
 
 
 #[derive(PartialEq, Clone)]
 pub struct SentInvitation
 {
-    pub to_user: String
+    pub to_user: String,
+    pub variant: Variant,
+    pub time_control: Option<TimeControl>,
+    pub is_rated: bool,
 }
 
 
 pub struct ReceivedInvitation
 {
-    pub from_user: String
+    pub from_user: String,
+    pub variant: Variant,
+    pub time_control: Option<TimeControl>,
+    pub initial_fen: Option<String>,
+    pub is_rated: bool,
+    pub color_preference: Option<PieceColor>,
 }
 
 
-#[derive(PartialEq, Clone, Debug, Serialize, Deserialize, Eq, Hash)]
-pub enum PieceColor
+/// Moves of the last game kept in the local storage to be replayed later.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GameRecord
 {
-    White,
-    Black,
+    pub variant: Variant,
+    pub initial_fen: String,
+    pub moves: Vec<GameData>,
 }
 
 
-impl PieceColor
+/// Current position of the game sent by the server on request and when the player comes back to the game.
+#[derive(Serialize, Deserialize)]
+pub struct PositionSnapshot
 {
-    pub fn opposite(&self) -> Self
-    {
-        if *self == PieceColor::White
-        {
-            PieceColor::Black
-        }
-        else
-        {
-            PieceColor::White
-        }
-    }
+    pub fen: String,
+    pub clock: Option<ClockData>,
+    /// Number of sessions watching the game
+    #[serde(default)]
+    pub spectators: usize,
 }
 
 
-#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
-pub struct CheckerPosition
+/// Game played at the moment, it is listed in the chat to be watched.
+#[derive(Deserialize, PartialEq, Clone)]
+pub struct LiveGame
 {
-    pub column: u8,
-    pub line: u8,
+    /// Name of the game room
+    pub room: String,
+    pub white_player: String,
+    pub black_player: String,
+    pub variant: Variant,
+    pub time_control: Option<TimeControl>,
+    pub is_rated: bool,
+    pub spectators: usize,
 }
 
 
-#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
-pub struct CheckerPiece
+/// Game of the player, it is sent when the game starts and when the player comes back to it.
+#[derive(Deserialize)]
+pub struct JoinedGame
 {
-    pub id: u8,
-    pub is_crowned: bool,
-    pub position: CheckerPosition,
+    pub game: LiveGame,
+    pub piece_color: PieceColor,
+    pub position: Option<PositionSnapshot>,
 }
 
 
-#[derive(Serialize, Deserialize, Clone)]
-pub struct GameData
+/// Position sent to the engine to get a hint.
+#[derive(Serialize)]
+pub struct AnalysisRequest
+{
+    pub variant: Variant,
+    pub checker_pieces: HashMap<PieceColor, Vec<CheckerPiece>>,
+    pub turn: PieceColor,
+}
+
+
+/// Puzzle position, every move of the player is checked by the server.
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct Puzzle
+{
+    pub id: i32,
+    pub variant: Variant,
+    pub fen: String,
+    pub rating: i32,
+    pub player_rating: i32,
+}
+
+
+#[derive(Serialize)]
+pub struct PuzzleAttempt
+{
+    pub puzzle_id: i32,
+    pub moves: Vec<GameData>,
+}
+
+
+#[derive(Deserialize)]
+pub struct PuzzleAttemptResult
+{
+    pub is_solved: bool,
+    pub player_rating: i32,
+    pub rating_change: i32,
+}
+
+
+/// Reply of the opponent to the correct move, the result is sent when the attempt is over.
+#[derive(Deserialize)]
+pub struct PuzzleMoveResult
+{
+    pub reply: Option<GameData>,
+    pub result: Option<PuzzleAttemptResult>,
+}
+
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct LeaderboardEntry
+{
+    pub rank: u32,
+    pub user_name: String,
+    pub rating: i32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+
+/// Page of the players ordered by rating.
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct Leaderboard
+{
+    pub entries: Vec<LeaderboardEntry>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u32,
+}
+
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+pub struct ResultCounts
+{
+    pub games: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct OpponentStats
+{
+    pub user_name: String,
+    pub results: ResultCounts,
+}
+
+
+/// Games in a row with the same result, it is one of `win`, `loss` or `draw`.
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct Streak
 {
-    pub opponent_piece_color: PieceColor,
-    pub piece_previous_position: CheckerPosition,
-    pub piece_new_position: CheckerPosition,
-    pub captured_piece_position: Option<CheckerPosition>,
-    pub is_opponent_step: bool,
+    pub result: String,
+    pub length: u32,
 }
 
 
-#[derive(Debug)]
-pub struct AllowableMove
+/// Results of all finished games of the user.
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct UserStats
 {
-    pub checker_id: u8,
-    pub next_position: CheckerPosition,
-    pub captured_piece_position: Option<CheckerPosition>
+    pub total: ResultCounts,
+    pub as_white: ResultCounts,
+    pub as_black: ResultCounts,
+    pub frequent_opponents: Vec<OpponentStats>,
+    pub current_streak: Option<Streak>,
 }